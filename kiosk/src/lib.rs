@@ -0,0 +1,28 @@
+//! The ticket kiosk's screens, and what they show, for the `hires_text`
+//! demo to drive.
+//!
+//! Like `textui`, this knows nothing of the hardware, so that the screens
+//! and the moves between them can be tested on the host. The demo reads the
+//! controller's lines and the buttons, and passes what changed to `Screens`
+//! once a frame.
+
+#![cfg_attr(not(test), no_std)]
+
+pub mod payment;
+pub mod schedule;
+pub mod screens;
+pub mod strings;
+pub mod theme;
+
+/// Size of the text mode the screens are laid out for.
+pub const COLS: usize = 80;
+pub const ROWS: usize = 37;
+
+pub const WHITE: u8 = 0b11_11_11;
+pub const BLACK: u8 = 0b00_00_00;
+pub const DK_GRAY: u8 = 0b01_01_01;
+pub const RED: u8 = 0b00_00_11;
+pub const BLUE: u8 = 0b11_00_00;
+
+/// Frames per second, as paced by `sync_to_vblank`.
+pub const FPS: u32 = 60;
@@ -0,0 +1,82 @@
+//! How far along a payment is, and a simulated payment to stand in for one
+//! until the kiosk has a card terminal and coin acceptor to ask.
+//!
+//! The paying screen only ever asks a `PaymentProgress` how the payment is
+//! going. The simulation here goes through steadily, a frame at a time; a
+//! driver for the real hardware would instead keep count from what the
+//! terminal reports, and take its place without the screen noticing.
+
+use crate::FPS;
+
+/// A payment being taken, as far as the paying screen needs to know.
+pub trait PaymentProgress {
+    /// Starts taking a new payment, with nothing paid yet.
+    fn begin(&mut self);
+
+    /// Moves the payment on. Called once a frame while the payment is being
+    /// taken, and not while it is held up, such as while the passenger is
+    /// asked whether to cancel.
+    fn poll(&mut self);
+
+    /// Stops taking the payment, keeping count of what went through so that
+    /// it can be given back.
+    fn cancel(&mut self);
+
+    /// Returns how much of the payment has gone through, out of `total`.
+    fn done(&self) -> u32;
+    fn total(&self) -> u32;
+
+    fn is_complete(&self) -> bool {
+        self.done() >= self.total()
+    }
+}
+
+/// How long the simulated payment takes to go through.
+pub const SIMULATED_SECONDS: u32 = 3;
+
+/// A payment that goes through a frame at a time, in `SIMULATED_SECONDS`.
+#[derive(Copy, Clone, Debug)]
+pub struct SimulatedPayment {
+    frames: u32,
+    taking: bool,
+}
+
+impl SimulatedPayment {
+    pub const fn new() -> Self {
+        SimulatedPayment {
+            frames: 0,
+            taking: false,
+        }
+    }
+}
+
+impl Default for SimulatedPayment {
+    fn default() -> Self {
+        SimulatedPayment::new()
+    }
+}
+
+impl PaymentProgress for SimulatedPayment {
+    fn begin(&mut self) {
+        self.frames = 0;
+        self.taking = true;
+    }
+
+    fn poll(&mut self) {
+        if self.taking && !self.is_complete() {
+            self.frames += 1;
+        }
+    }
+
+    fn cancel(&mut self) {
+        self.taking = false;
+    }
+
+    fn done(&self) -> u32 {
+        self.frames
+    }
+
+    fn total(&self) -> u32 {
+        SIMULATED_SECONDS * FPS
+    }
+}
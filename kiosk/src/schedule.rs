@@ -0,0 +1,148 @@
+//! The departures the kiosk knows of, and a made-up timetable to fill them
+//! from until there is a live feed.
+//!
+//! Screens only ever read a `Schedule`'s departures. Whatever keeps it up to
+//! date, the demo timetable here or later a feed over the serial port, does
+//! so with `insert`, `clear` and `count_down`, so one can replace the other
+//! without the screens noticing.
+
+use textui::board::Departure;
+
+/// Most departures a `Schedule` holds.
+pub const SCHEDULE_CAP: usize = 16;
+
+/// Departures soonest first, counted down a minute at a time.
+#[derive(Copy, Clone, Debug)]
+pub struct Schedule {
+    departures: [Departure; SCHEDULE_CAP],
+    len: usize,
+}
+
+impl Schedule {
+    pub const fn new() -> Self {
+        Schedule {
+            departures: [Departure::EMPTY; SCHEDULE_CAP],
+            len: 0,
+        }
+    }
+
+    /// Returns the departures, soonest first.
+    pub fn departures(&self) -> &[Departure] {
+        &self.departures[..self.len]
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Adds `d` in order of minutes to go, after any leaving at the same
+    /// time. If the schedule is full, the last departure makes room, unless
+    /// `d` would be last itself; returns false if `d` is left out.
+    pub fn insert(&mut self, d: Departure) -> bool {
+        let at = self
+            .departures()
+            .iter()
+            .position(|e| e.minutes > d.minutes)
+            .unwrap_or(self.len);
+        if at == SCHEDULE_CAP {
+            return false;
+        }
+        let end = usize::min(self.len, SCHEDULE_CAP - 1);
+        for i in (at..end).rev() {
+            self.departures[i + 1] = self.departures[i];
+        }
+        self.departures[at] = d;
+        self.len = end + 1;
+        true
+    }
+
+    /// Counts every departure down a minute. Those that get to zero have
+    /// left and are taken out, the rest moving up in their place.
+    pub fn count_down(&mut self) {
+        let mut kept = 0;
+        for i in 0..self.len {
+            let mut d = self.departures[i];
+            if d.minutes <= 1 {
+                continue;
+            }
+            d.minutes -= 1;
+            self.departures[kept] = d;
+            kept += 1;
+        }
+        self.len = kept;
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule::new()
+    }
+}
+
+/// A line running at regular times: every `headway` minutes, at `offset`
+/// minutes into each.
+struct Service {
+    line: &'static [u8],
+    destination: &'static [u8],
+    headway: u32,
+    offset: u32,
+}
+
+/// Stand-in timetable for lines 1 to 3.
+static SERVICES: [Service; 3] = [
+    Service {
+        line: b"1",
+        destination: b"Central",
+        headway: 6,
+        offset: 2,
+    },
+    Service {
+        line: b"2",
+        destination: b"Riverside",
+        headway: 10,
+        offset: 5,
+    },
+    Service {
+        line: b"3",
+        destination: b"Airport",
+        headway: 15,
+        offset: 9,
+    },
+];
+
+/// How many minutes ahead the demo timetable fills a schedule.
+pub const HORIZON: u32 = 30;
+
+impl Service {
+    /// Returns whether the service leaves at minute `minute`.
+    fn leaves_at(&self, minute: u32) -> bool {
+        minute % self.headway == self.offset
+    }
+
+    fn departure(&self, minutes: u32) -> Departure {
+        Departure::new(self.line, self.destination, minutes as u16)
+    }
+}
+
+/// Fills `schedule` afresh with the demo timetable's departures in the
+/// `HORIZON` minutes after minute `now`.
+pub fn fill(schedule: &mut Schedule, now: u32) {
+    schedule.clear();
+    for ahead in 1..=HORIZON {
+        for s in SERVICES.iter() {
+            if s.leaves_at(now.wrapping_add(ahead)) {
+                schedule.insert(s.departure(ahead));
+            }
+        }
+    }
+}
+
+/// Adds the departures that have just come within the `HORIZON`, now that
+/// it is minute `now`. Call after `Schedule::count_down`, once a minute.
+pub fn top_up(schedule: &mut Schedule, now: u32) {
+    for s in SERVICES.iter() {
+        if s.leaves_at(now.wrapping_add(HORIZON)) {
+            schedule.insert(s.departure(HORIZON));
+        }
+    }
+}
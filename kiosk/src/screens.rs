@@ -0,0 +1,2811 @@
+//! The kiosk's screens, and the registry that switches between them.
+//!
+//! Each screen draws itself whole in `on_enter`, keeps whatever moves on it
+//! up to date in `on_tick`, and says in `on_event` where to go next. The main
+//! loop only passes events to `Screens` and ticks it; nothing about any
+//! particular screen is drawn there.
+
+use textui::anim::Animations;
+use textui::banner::{banner_width, draw_banner, FULL_BLOCK, GLYPH_H};
+use textui::board::{DepartureBoard, BOARD_CAP, LINE_W};
+use textui::cart::{Cart, CartError, CartView, Catalog, CART_CAP};
+use textui::chart::LIGHT_SHADE;
+use textui::chrome::{
+    draw_screen_border, draw_title_bar, render_spec, Corner, ScreenSpec,
+    SoftKeys, HEADER_ROWS, MESSAGE_PAD,
+};
+use textui::clock::Countdown;
+use textui::code::draw_code;
+use textui::confetti::Confetti;
+use textui::icons::{
+    draw_icon, IconLabel, ICON_CARD, ICON_COIN, ICON_OK, ICON_TAP, ICON_WARN,
+};
+use textui::input::{Bounds, NumberField};
+use textui::map::{LineMap, MapLine};
+use textui::overlay::{Confirm, Toast, DIALOG_MAX_H};
+use textui::price::Price;
+use textui::reference::{Reference, References};
+use textui::widget::{
+    Blink, BlinkOff, Button, Header, HeaderStyle, Marquee, MenuEvent,
+    MessageBox, PagedList, Percent, ProgressBar, RadioGroup, Spinner,
+    SPINNER_BLOCKS,
+};
+use textui::{BoxStyle, Cursor, CursorError, Rect, Widget};
+
+use crate::payment::{PaymentProgress, SimulatedPayment};
+use crate::schedule::Schedule;
+use crate::strings::{
+    language, set_language, tr, tr_run, Language, StringId, LANGUAGE_NAMES,
+};
+use crate::theme::{theme, STANDARD};
+use crate::{BLACK, COLS, FPS, RED, WHITE};
+
+/// How the text of the message on the start screen blinks, in a box that
+/// stays: on for `START_BLINK_DUTY` percent of every `START_BLINK_FRAMES`
+/// frames.
+const START_BLINK_FRAMES: u32 = FPS;
+const START_BLINK_DUTY: u32 = 70;
+/// Size of the confirmation question, drawn over the middle of the screen it
+/// is about.
+const CONFIRM_H: usize = 7;
+const CONFIRM_W: usize = 30;
+/// Rows for the question in a dialog `CONFIRM_H` high.
+const CONFIRM_ROWS: usize = 3;
+/// Number of frames the payment message spends in each of its two colors.
+const PAY_FLASH_FRAMES: u32 = 30;
+/// Area of the progress bar on the payment screen.
+const PAY_BAR: Rect = Rect::new(24, 25, 1, 30);
+/// Area of the activity indicator shown while the payment goes through.
+const PAY_SPINNER: Rect = Rect::new(26, 33, 1, 15);
+/// Number of frames the payment screen stays up once the payment has gone
+/// through, before thanking the passenger.
+const PAID_FRAMES: u32 = FPS;
+/// Number of frames each spinner glyph is shown for.
+const SPINNER_FRAMES: u32 = 8;
+/// Where the payment countdown goes, after its label, and how long it gives.
+const PAY_LABEL_COL: usize = 26;
+const PAY_TIMER: Rect = Rect::new(29, PAY_LABEL_COL + 24, 1, 5);
+const PAY_SECONDS: u32 = 60;
+/// Row of the short messages that pop up over a screen, and how long they
+/// stay up.
+const TOAST_ROW: usize = 31;
+const TOAST_FRAMES: u32 = 3 * FPS;
+
+/// Screen with a plain title and nothing else, for the others to fill in.
+fn plain_spec() -> ScreenSpec<'static> {
+    let t = theme();
+    ScreenSpec {
+        header: Header {
+            title: b"",
+            subtitle: None,
+            style: HeaderStyle {
+                fg: t.title_fg,
+                bg: t.title_bg,
+                subtitle_fg: t.subtitle_fg,
+            },
+        },
+        border: BoxStyle::SINGLE,
+        fg: t.body_fg,
+        bg: t.body_bg,
+        fill: b' ',
+        fill_fg: t.body_fg,
+        message: None,
+        keys: None,
+    }
+}
+
+/// A message box with no lines yet, for a screen to fill in.
+fn message_box(shadow: bool) -> MessageBox<'static> {
+    let t = theme();
+    MessageBox {
+        lines: &[],
+        fg: t.box_fg,
+        bg: t.box_bg,
+        shadow,
+    }
+}
+
+/// Labels of the keys answering a question, yes then no.
+fn answers() -> [&'static [u8]; 2] {
+    [tr(StringId::KeyYes), tr(StringId::KeyNo)]
+}
+
+/// Headings of a `CartView`'s columns, item to total.
+fn cart_headings() -> [&'static [u8]; 4] {
+    [
+        tr(StringId::ItemHeading),
+        tr(StringId::QtyHeading),
+        tr(StringId::EachHeading),
+        tr(StringId::Total),
+    ]
+}
+
+/// Soft keys labelled `labels`, in the order of `Corner::ALL`.
+fn soft_keys(labels: [Option<&'static [u8]>; 4]) -> Option<SoftKeys<'static>> {
+    Some(SoftKeys::new(&theme().keys).with_labels(labels))
+}
+
+/// The payment screen, but for its text; the line under its title and its
+/// message depend on the way of paying.
+fn pay_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        border: BoxStyle::DOUBLE,
+        message: Some(message_box(true)),
+        keys: soft_keys([None, None, Some(tr(StringId::KeyBack)), None]),
+        ..plain_spec()
+    }
+}
+
+/// The refund after a payment is cancelled, and how many frames it takes.
+fn refund_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        message: Some(message_box(true)),
+        ..plain_spec()
+    }
+}
+const REFUND_FRAMES: u32 = 3 * FPS;
+/// Row saying how much is refunded, between the message and the progress.
+const REFUND_ROW: usize = 22;
+
+/// The kiosk taken out of service, with its apology under a banner, and a
+/// row below for the reason, if there is one.
+fn out_spec() -> ScreenSpec<'static> {
+    let t = theme();
+    let mut spec = ScreenSpec {
+        message: Some(message_box(true)),
+        ..plain_spec()
+    };
+    spec.header.style = HeaderStyle {
+        fg: t.danger_fg,
+        bg: t.danger,
+        subtitle_fg: t.danger_fg,
+    };
+    spec
+}
+const OUT_BANNER_ROW: usize = 6;
+const OUT_REASON_ROW: usize = 27;
+
+/// The start message as it blinks; the start screen has drawn its shadow
+/// already.
+static START_BLINK_MSG: TrMessage = TrMessage {
+    first: StringId::StartPrompt1,
+    last: StringId::StartPrompt3,
+    shadow: false,
+};
+
+fn start_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        fill: 0xB0, // Light shade
+        fill_fg: theme().shade_fg,
+        message: Some(message_box(true)),
+        ..plain_spec()
+    }
+}
+
+fn thanks_spec() -> ScreenSpec<'static> {
+    let t = theme();
+    ScreenSpec {
+        message: Some(MessageBox {
+            lines: &[],
+            fg: t.success_fg,
+            bg: t.success_dim,
+            shadow: false,
+        }),
+        ..plain_spec()
+    }
+}
+
+/// Tickets for sale, `TICKETS_PER_LINE` to each line's screen.
+const TICKETS: [&[u8]; 6] = [b"A", b"B", b"C", b"D", b"E", b"F"];
+const TICKETS_PER_LINE: usize = 2;
+/// Price of each of `TICKETS` in pence, and the width of the field it is
+/// shown in, beside the ticket's button.
+static TICKET_PRICES: [i32; 6] = [250, 320, 450, 180, 1200, 2750];
+const PRICE_W: usize = 8;
+static CATALOG: Catalog = Catalog {
+    names: &TICKETS,
+    prices: &TICKET_PRICES,
+};
+/// Area of the departure board on the start screen, and the keys to the
+/// network map, more of the board, and the choice of language, with their
+/// labels and the captions beside them.
+const BOARD: Rect = Rect::new(25, 20, 4, 40);
+static START_KEYS: [(Corner, StringId, StringId); 3] = [
+    (Corner::TopLeft, StringId::KeyMap, StringId::NetworkMap),
+    (Corner::TopRight, StringId::KeyTime, StringId::Departures),
+    (Corner::BottomLeft, StringId::KeyLang, StringId::Language),
+];
+/// Area of the departures screen's board, the row of the headings over its
+/// columns, and the row under it saying if there is nothing on it.
+const DEPARTURES: Rect =
+    Rect::new(HEADER_ROWS + 3, (COLS - 48) / 2, BOARD_CAP, 48);
+const DEPARTURES_HEADINGS_ROW: usize = DEPARTURES.row - 1;
+const DEPARTURES_NONE_ROW: usize = DEPARTURES.row + DEPARTURES.h + 1;
+
+/// How long the start screen waits for someone before showing the attract
+/// loop, how long the loop shows each of its pages, and where it invites
+/// passers-by to start, above the news.
+const ATTRACT_IDLE_FRAMES: u32 = 60 * FPS;
+const ATTRACT_PAGE_FRAMES: u32 = 10 * FPS;
+const ATTRACT_INVITE: Rect = Rect::new(32, 1, 1, COLS - 2);
+
+fn departures_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        keys: soft_keys([None, None, Some(tr(StringId::KeyBack)), None]),
+        ..plain_spec()
+    }
+}
+
+/// The network, one line for each of the ticket screens, and the area of the
+/// map of it. The lines are named as their ticket screens are titled, and
+/// keep their own colors whatever the theme.
+static NETWORK: [MapLine; LINES] = [
+    MapLine {
+        name: b"",
+        color: RED,
+        stations: &[
+            b"Northgate",
+            b"Museum",
+            b"Market Square",
+            b"Central",
+            b"Harbour",
+            b"Seaview",
+        ],
+        interchanges: &[2, 3],
+    },
+    MapLine {
+        name: b"",
+        color: 0b00_11_00, // Green
+        stations: &[
+            b"Westfield",
+            b"Old Town",
+            b"Market Square",
+            b"University",
+            b"Riverside",
+        ],
+        interchanges: &[2, 4],
+    },
+    MapLine {
+        name: b"",
+        color: 0b00_11_11, // Yellow
+        stations: &[
+            b"Central",
+            b"Stadium",
+            b"Riverside",
+            b"Business Park",
+            b"Airport",
+        ],
+        interchanges: &[0, 2],
+    },
+];
+const NETWORK_MAP: Rect = Rect::new(HEADER_ROWS + 1, 9, 27, COLS - 18);
+
+/// Where the languages are listed, each under its own name, and the
+/// question above them.
+const LANGUAGE_LIST: Rect = Rect::new(14, (COLS - 16) / 2 + 1, 2, 16);
+const LANGUAGE_PROMPT_ROW: usize = 7;
+
+/// Area of the code made from the transaction's reference on the thank-you
+/// screen: nine modules a side, plus the quiet zone. The reference itself is
+/// spelled out in a banner at `REFERENCE_ROW`, under its caption.
+const TICKET_CODE: Rect = Rect::new(14, 54, 11, 22);
+const REFERENCE_ROW: usize = 28;
+/// Where confetti falls on the thank-you screen: inside the border, from
+/// under the title to above the news, a row every `CONFETTI_PERIOD` frames.
+const CONFETTI: Rect = Rect::new(HEADER_ROWS, 1, 30, COLS - 2);
+const CONFETTI_PIECES: usize = 30;
+const CONFETTI_PERIOD: u32 = 6;
+/// Area of the list of what is being bought, above the confirmation question.
+const CART_VIEW: Rect = Rect::new(HEADER_ROWS + 1, (COLS - 40) / 2, 7, 40);
+/// Area under the message on the ticket screens for the page indicator.
+const PAGE_LABEL: Rect = Rect::new(22, 34, 1, 11);
+
+/// How many of a ticket can be bought at once.
+const QTY_MAX: u32 = 10;
+/// Rows of the quantity screen's question, its quantity in block digits, and
+/// the price each and in all.
+const QTY_PROMPT_ROW: usize = 6;
+const QTY_ROW: usize = 9;
+const QTY_EACH_ROW: usize = 16;
+const QTY_TOTAL_ROW: usize = 18;
+/// Number of frames the quantity flashes for when it won't go any further.
+const QTY_FLASH_FRAMES: u32 = FPS / 3;
+/// Where the ways of paying are listed, each with its icon two columns to
+/// the left, and the question above them.
+const METHOD_LIST: Rect = Rect::new(14, (COLS - 16) / 2 + 1, 3, 16);
+const METHOD_PROMPT_ROW: usize = 7;
+/// Ways of paying, in the order they are listed.
+const METHODS: [PayMethod; 3] =
+    [PayMethod::Cash, PayMethod::Card, PayMethod::Contactless];
+
+/// A list to choose from, stepped through with the top keys: the ways of
+/// paying, the languages, or the lines on the map.
+fn choice_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        keys: soft_keys([
+            Some(tr(StringId::KeyUp)),
+            Some(tr(StringId::KeyDown)),
+            Some(tr(StringId::KeyBack)),
+            Some(tr(StringId::KeyOk)),
+        ]),
+        ..plain_spec()
+    }
+}
+
+/// Where the cart screen lists the cart, with room for all of it, and says
+/// when there is nothing in it.
+const CART_LIST: Rect = Rect::new(6, (COLS - 40) / 2, CART_CAP + 5, 40);
+const CART_EMPTY_ROW: usize = CART_LIST.row + CART_LIST.h + 1;
+/// Row of the line screens saying what is in the cart so far.
+const LINE_CART_ROW: usize = 24;
+/// Row of the payment screen's amount to pay.
+const PAY_TOTAL_ROW: usize = 22;
+
+fn cart_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        keys: soft_keys([
+            Some(tr(StringId::KeyNext)),
+            Some(tr(StringId::KeyDel)),
+            Some(tr(StringId::KeyBack)),
+            Some(tr(StringId::KeyPay)),
+        ]),
+        ..plain_spec()
+    }
+}
+
+/// Soft keys with no labels, for where they go; screens with keys of their
+/// own draw them in the theme's colors.
+static SOFT_KEYS: SoftKeys = SoftKeys::new(&STANDARD.keys);
+
+fn quantity_spec() -> ScreenSpec<'static> {
+    ScreenSpec {
+        keys: soft_keys([
+            Some(b"-"),
+            Some(b"+"),
+            Some(tr(StringId::KeyBack)),
+            Some(tr(StringId::KeyAdd)),
+        ]),
+        ..plain_spec()
+    }
+}
+
+/// Number of lines, each with a page of `TICKETS`.
+const LINES: usize = TICKETS.len().div_ceil(TICKETS_PER_LINE);
+
+/// The screen for line `line`'s tickets, but for its text. The keys are its
+/// tickets, with ways to the lines either side below them, or QUIT before
+/// the first and after the last; once there is something in the cart, the
+/// last line's QUIT goes to the cart instead. Another line takes only more
+/// tickets, its title in the strings and its place in `NETWORK`.
+fn line_spec(line: usize, cart: &Cart) -> ScreenSpec<'static> {
+    let first = line * TICKETS_PER_LINE;
+    let prev = if line > 0 {
+        StringId::KeyPrev
+    } else {
+        StringId::KeyQuit
+    };
+    let next = if line + 1 < LINES {
+        StringId::KeyNext
+    } else if !cart.is_empty() {
+        StringId::KeyCart
+    } else {
+        StringId::KeyQuit
+    };
+    ScreenSpec {
+        message: Some(message_box(false)),
+        keys: soft_keys([
+            TICKETS.get(first).cloned(),
+            TICKETS.get(first + 1).cloned(),
+            Some(tr(prev)),
+            Some(tr(next)),
+        ]),
+        ..plain_spec()
+    }
+}
+
+/// Names of the screens, for moving between them.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScreenId {
+    Error,
+    Start,
+    Paying,
+    /// A question about the screen before, drawn over it.
+    Confirm,
+    /// Tickets for a line, counting from 0.
+    Line(usize),
+    Thanks,
+    /// How many to buy of a ticket, counting in `TICKETS`.
+    Quantity(usize),
+    /// How to pay, once the tickets are confirmed.
+    Method,
+    /// Giving back what was paid, once a payment is cancelled.
+    Refunding,
+    /// Closed to passengers, until an operator opens it again.
+    OutOfService,
+    /// What is in the cart, to take things out or check out.
+    Cart,
+    /// The next departures, more of them than the start screen has room for.
+    Departures,
+    /// The lines and their stations, to choose one to buy tickets for.
+    Map,
+    /// Which language the screens are in.
+    Language,
+    /// The departures, the map and a promotion in turn, shown when nobody
+    /// has touched the kiosk for a while.
+    Attract,
+}
+
+/// Input lines asking for the ticket screens, and, once on them, pressing
+/// the NEXT key; going back to `LINES_TICKETS` lets go of it. The controller
+/// has no code of its own for each line, so that any number of them can be
+/// stepped through.
+const LINES_TICKETS: u8 = 0b100;
+const LINES_NEXT: u8 = 0b101;
+
+impl ScreenId {
+    /// Returns the screen the controller asks for with input lines `s`,
+    /// coming from some other screen.
+    pub fn for_lines(s: u8) -> Self {
+        match s {
+            0b001 => ScreenId::Start,
+            0b010 => ScreenId::Paying,
+            0b011 => ScreenId::Confirm,
+            LINES_TICKETS | LINES_NEXT => ScreenId::Line(0),
+            0b110 => ScreenId::Thanks,
+            0b111 => ScreenId::Quantity(0),
+            _ => ScreenId::Error,
+        }
+    }
+
+    /// Returns the name of the screen, for the status row.
+    pub fn name(self) -> &'static [u8] {
+        match self {
+            ScreenId::Error => b"Error",
+            ScreenId::Start => b"Start",
+            ScreenId::Paying => b"Paying",
+            ScreenId::Confirm => b"Confirm",
+            ScreenId::Line(_) => b"Line",
+            ScreenId::Thanks => b"Thanks",
+            ScreenId::Quantity(_) => b"Quantity",
+            ScreenId::Method => b"Method",
+            ScreenId::Refunding => b"Refunding",
+            ScreenId::OutOfService => b"Out of service",
+            ScreenId::Cart => b"Cart",
+            ScreenId::Departures => b"Departures",
+            ScreenId::Map => b"Map",
+            ScreenId::Language => b"Language",
+            ScreenId::Attract => b"Attract",
+        }
+    }
+}
+
+/// Something that happened, for the current screen to answer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InputEvent {
+    /// The controller's three input lines changed, to this value.
+    Lines(u8),
+    /// The button by the soft key in this corner was pressed.
+    Key(Corner),
+}
+
+/// Where to go on from the current screen.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Transition {
+    To(ScreenId),
+    /// Back to the screen before the current one.
+    Back,
+}
+
+/// Goes to the screen the controller asks for, if `ev` is a change of the
+/// input lines. Most screens do this, whatever else they answer.
+fn follow_lines(ev: InputEvent) -> Option<Transition> {
+    match ev {
+        InputEvent::Lines(s) => Some(Transition::To(ScreenId::for_lines(s))),
+        _ => None,
+    }
+}
+
+/// One of the kiosk's screens. Pass the window from `screen_area` to all of
+/// these.
+pub trait Screen {
+    /// Draws the whole screen, over whatever was there.
+    fn on_enter(&mut self, c: &mut Cursor);
+
+    /// Answers `ev`, returning where to go next, if anywhere. By default the
+    /// input lines choose the screen, and nothing else does.
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        follow_lines(ev)
+    }
+
+    /// Brings whatever moves on the screen up to frame `frame` of the main
+    /// loop, returning where to go next if the screen is done. Called every
+    /// frame the screen is current, starting with the one it was entered on,
+    /// or the one after if it was entered by another screen's tick.
+    fn on_tick(
+        &mut self,
+        _c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        None
+    }
+
+    /// Leaves the screen, just before the next one is drawn over it.
+    fn on_exit(&mut self, _c: &mut Cursor) {}
+}
+
+/// Shown on power-up, when the controller asks for no screen in particular,
+/// and when a payment runs out of time.
+pub struct ErrorScreen;
+
+impl Screen for ErrorScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        draw_error(c);
+    }
+}
+
+fn draw_error(c: &mut Cursor) {
+
+    // reset
+    let t = theme();
+    c.bg = t.box_bg;
+    c.fg = t.box_fg;
+    c.clear();
+
+    // title
+    let error = tr(StringId::Error);
+    draw_title_bar(c, error, t.danger, t.danger_fg);
+    draw_screen_border(c, &BoxStyle::SINGLE);
+
+    // banner
+    draw_banner(c, 15, COLS.saturating_sub(banner_width(error)) / 2, error);
+    let label = IconLabel {
+        icon: ICON_WARN,
+        text: tr(StringId::ErrorLabel),
+        icon_fg: t.accent,
+        fg: t.box_fg,
+        bg: t.box_bg,
+    };
+    let (_, w) = label.min_size();
+    label.draw(c, Rect::new(22, COLS.saturating_sub(w) / 2, 1, w));
+
+    c.bg = BLACK;
+}
+
+/// Welcome, with a blinking prompt and the next departures.
+pub struct StartScreen {
+    blink: Blink<'static>,
+    board: DepartureBoard,
+    pub schedule: Schedule,
+    /// Whether the screen has been drawn since the last tick.
+    fresh: bool,
+    /// Frame of the first tick since the screen was drawn. Every button
+    /// leaves the screen, so nobody has touched the kiosk since.
+    idle_since: Option<u32>,
+}
+
+impl StartScreen {
+    pub fn new() -> Self {
+        // Put over the message, and blanked in its color, by `on_enter`.
+        let mut blink = Blink::new(
+            &START_BLINK_MSG,
+            Rect::new(0, 0, 0, 0),
+            START_BLINK_FRAMES,
+        );
+        blink.duty = START_BLINK_DUTY;
+        StartScreen {
+            blink,
+            board: DepartureBoard::new(BOARD),
+            schedule: Schedule::new(),
+            fresh: false,
+            idle_since: None,
+        }
+    }
+}
+
+impl Default for StartScreen {
+    fn default() -> Self {
+        StartScreen::new()
+    }
+}
+
+impl Screen for StartScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let prompt = tr_run(StringId::StartPrompt1, StringId::StartPrompt3);
+        let spec = translated(start_spec(), StringId::Welcome, prompt);
+        render_spec(c, &spec);
+        let (h, w) = c.size();
+        if let Some(rect) = spec.message_rect(h, w) {
+            self.blink.rect = rect;
+        }
+        // Off, the text goes, leaving the box it was in.
+        self.blink.off = BlinkOff::Blank(theme().box_bg);
+        // Only the keys it has, so as not to blank the others' corners of
+        // the shading, with their captions on a blank either side.
+        for &(corner, label, caption) in START_KEYS.iter() {
+            let key = SOFT_KEYS.rect(corner, w);
+            Button::new(tr(label), key, &theme().keys).redraw(c);
+            let caption = tr(caption);
+            let col = match corner {
+                Corner::TopLeft | Corner::BottomLeft => key.col + key.w + 1,
+                _ => key.col.saturating_sub(caption.len() + 2),
+            };
+            c.fill_rect(key.row + 1, col, 1, caption.len() + 2, b' ');
+            c.puts_at(key.row + 1, col + 1, caption).ok();
+        }
+        self.board.due = tr(StringId::Due);
+        self.board.invalidate();
+        self.fresh = true;
+        self.idle_since = None;
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        match ev {
+            InputEvent::Key(Corner::TopLeft) => {
+                Some(Transition::To(ScreenId::Map))
+            }
+            InputEvent::Key(Corner::TopRight) => {
+                Some(Transition::To(ScreenId::Departures))
+            }
+            InputEvent::Key(Corner::BottomLeft) => {
+                Some(Transition::To(ScreenId::Language))
+            }
+            // Any other button starts, as the message asks.
+            InputEvent::Key(_) => Some(Transition::To(ScreenId::Line(0))),
+            _ => follow_lines(ev),
+        }
+    }
+
+    fn on_tick(&mut self, c: &mut Cursor, frame: u32) -> Option<Transition> {
+        let since = *self.idle_since.get_or_insert(frame);
+        if frame.wrapping_sub(since) >= ATTRACT_IDLE_FRAMES {
+            return Some(Transition::To(ScreenId::Attract));
+        }
+        self.board.update(c, self.schedule.departures());
+        let mut anims = Animations::new();
+        anims.push(&mut self.blink);
+        if self.fresh {
+            anims.invalidate();
+            self.fresh = false;
+        }
+        anims.tick_all(frame);
+        anims.draw_all(c);
+        None
+    }
+}
+
+/// Ways of paying.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PayMethod {
+    Cash,
+    Card,
+    Contactless,
+}
+
+impl PayMethod {
+    fn icon(self) -> u8 {
+        match self {
+            PayMethod::Cash => ICON_COIN,
+            PayMethod::Card => ICON_CARD,
+            PayMethod::Contactless => ICON_TAP,
+        }
+    }
+
+    /// Returns what the payment screen says under its title, and the second
+    /// line of its message, whose first word is picked out in yellow.
+    fn instructions(self) -> (StringId, StringId) {
+        match self {
+            PayMethod::Cash => (StringId::CashRules, StringId::InsertCoins),
+            PayMethod::Card => (StringId::CardRules, StringId::InsertCard),
+            PayMethod::Contactless => {
+                (StringId::ContactlessRules, StringId::TapCard)
+            }
+        }
+    }
+}
+
+/// What the customer has chosen so far, for the screens further on.
+///
+/// Screens that change the cart are given a copy of it on the way in, and
+/// `Screens` takes it back on the way out.
+#[derive(Copy, Clone, Debug)]
+pub struct Transaction {
+    pub cart: Cart,
+    pub method: PayMethod,
+    /// Amount paid so far, in pence, as of leaving the payment screen.
+    pub inserted: i32,
+    /// Reference for the passenger to quote, and for the printed ticket,
+    /// given once the transaction is paid for.
+    pub reference: Option<Reference>,
+}
+
+impl Transaction {
+    /// Nothing chosen or paid yet, and paying as the kiosk always has.
+    pub const fn new() -> Self {
+        Transaction {
+            cart: Cart::new(),
+            method: PayMethod::Contactless,
+            inserted: 0,
+            reference: None,
+        }
+    }
+}
+
+impl Default for Transaction {
+    fn default() -> Self {
+        Transaction::new()
+    }
+}
+
+/// Waiting for a payment, with a countdown to pay within, and a key to
+/// cancel it. Goes on to the thank-you screen once the payment, asked of
+/// `P`, has gone through, or if the time runs out first, to give back what
+/// was paid.
+pub struct PayingScreen<P: PaymentProgress = SimulatedPayment> {
+    /// How the payment is being made, which the instructions are for.
+    pub method: PayMethod,
+    /// Amount to pay, in pence.
+    pub due: i32,
+    payment: P,
+    countdown: Countdown,
+    spinner: Spinner<'static>,
+    toast: Toast<'static>,
+    /// Whether to cancel, asked over the screen.
+    cancel: Confirm<'static>,
+    /// Whether the question should be up; the next tick puts it up or takes
+    /// it down to match.
+    asking: bool,
+    /// Frames since the payment went through.
+    paid: u32,
+    /// Whether the screen has been ticked since it was drawn.
+    ticked: bool,
+    /// Area of the message, which flashes.
+    message: Rect,
+}
+
+impl PayingScreen {
+    pub fn new() -> Self {
+        PayingScreen::with_payment(SimulatedPayment::new())
+    }
+}
+
+impl Default for PayingScreen {
+    fn default() -> Self {
+        PayingScreen::new()
+    }
+}
+
+impl<P: PaymentProgress> PayingScreen<P> {
+    pub fn with_payment(payment: P) -> Self {
+        let mut countdown =
+            Countdown::from_seconds(PAY_TIMER, PAY_SECONDS, FPS);
+        countdown.pause();
+        // Labelled and colored on the way in, in the language and theme of
+        // the time.
+        PayingScreen {
+            method: PayMethod::Contactless,
+            due: 0,
+            payment,
+            countdown,
+            spinner: Spinner::new(
+                PAY_SPINNER,
+                &SPINNER_BLOCKS,
+                SPINNER_FRAMES,
+                b"",
+            ),
+            toast: Toast::new(TOAST_ROW),
+            cancel: Confirm::new(b"", Corner::BottomLeft, Corner::BottomRight),
+            asking: false,
+            paid: 0,
+            ticked: false,
+            message: Rect::new(0, 0, 0, 0),
+        }
+    }
+
+    /// Gives the full time to pay again.
+    pub fn restart(&mut self) {
+        self.countdown.reset();
+    }
+
+    /// Returns how much has been paid so far, in pence. Cash comes in a 10p
+    /// coin at a time; a card pays all at once when the payment goes through.
+    pub fn inserted(&self) -> i32 {
+        let total = i64::from(u32::max(self.payment.total(), 1));
+        let done = i64::from(self.payment.done()).min(total);
+        match self.method {
+            PayMethod::Cash => {
+                (i64::from(self.due) * done / total) as i32 / 10 * 10
+            }
+            _ if self.payment.is_complete() => self.due,
+            _ => 0,
+        }
+    }
+
+    /// Puts the question up or takes it down to match `asking`, stopping
+    /// the clock while it is up and putting the keys back after. Returns
+    /// the error if there is no room to ask.
+    fn update_question(&mut self, c: &mut Cursor) -> Result<(), CursorError> {
+        if self.asking == self.cancel.is_open() {
+            return Ok(());
+        }
+        let spec = pay_spec();
+        let mut c = c.styled(spec.fg, spec.bg);
+        if self.asking {
+            self.countdown.pause();
+            clear_captions(&mut c);
+            let question = tr(StringId::CancelQuestion);
+            self.cancel.dialog.message = question;
+            let rect = dialog_rect(&c, question);
+            self.cancel.open(&mut c, rect)?;
+        } else {
+            self.cancel.close(&mut c);
+            if let Some(keys) = spec.keys {
+                keys.draw(&mut c);
+            }
+            draw_captions(&mut c, tr(StringId::Cancel), b"");
+            if !self.payment.is_complete() {
+                self.countdown.resume();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<P: PaymentProgress> Screen for PayingScreen<P> {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let (subtitle, ask) = self.method.instructions();
+        let ask = tr(ask);
+        let lines = [tr(StringId::Please), ask];
+        let t = theme();
+        let mut spec = pay_spec();
+        spec.header.title = tr(StringId::Payment);
+        spec.header.subtitle = Some(tr(subtitle));
+        if let Some(message) = spec.message.as_mut() {
+            message.lines = &lines;
+        }
+        render_spec(c, &spec);
+        let (h, w) = c.size();
+        if let Some(rect) = spec.message_rect(h, w) {
+            self.message = rect;
+            let row = rect.row + MESSAGE_PAD + 1;
+            let col = rect.col + (rect.w - ask.len()) / 2;
+            let len = ask.iter().position(|&b| b == b' ');
+            let word = Rect::new(row, col, 1, len.unwrap_or(ask.len()));
+            c.recolor_region(word, Some(t.accent), None);
+        }
+        draw_captions(c, tr(StringId::Cancel), b"");
+        draw_priced(c, PAY_TOTAL_ROW, tr(StringId::TotalToPay), self.due);
+
+        // countdown label, up to the time, which is drawn by `on_tick`
+        let label_w = PAY_TIMER.col - 1 - PAY_LABEL_COL;
+        let label = tr(StringId::PayWithin);
+        c.puts_truncated_at(PAY_TIMER.row, PAY_LABEL_COL, label, label_w)
+            .ok();
+        self.spinner.label = tr(StringId::Processing);
+        self.spinner.fg = t.body_fg;
+        self.spinner.bg = t.body_bg;
+        self.countdown.fg = t.body_fg;
+        self.countdown.bg = t.body_bg;
+        self.toast.fg = t.success_fg;
+        self.toast.bg = t.success;
+        self.cancel.dialog.fg = t.danger_fg;
+        self.cancel.dialog.bg = t.danger;
+        self.cancel.dialog.buttons = answers();
+        self.cancel.yes_style = &t.yes_keys;
+        self.cancel.no_style = &t.no_keys;
+
+        self.payment.begin();
+        self.countdown.resume();
+        self.asking = false;
+        self.paid = 0;
+        self.ticked = false;
+        // Nothing from a visit before is still up.
+        self.toast.dismiss();
+        self.toast.invalidate();
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        match ev {
+            InputEvent::Key(corner) if self.asking => {
+                if corner == self.cancel.yes {
+                    return Some(Transition::To(ScreenId::Refunding));
+                }
+                if corner == self.cancel.no {
+                    self.asking = false;
+                }
+                None
+            }
+            InputEvent::Key(Corner::BottomLeft) => {
+                self.asking = true;
+                None
+            }
+            _ => follow_lines(ev),
+        }
+    }
+
+    fn on_tick(&mut self, c: &mut Cursor, frame: u32) -> Option<Transition> {
+        if self.update_question(c).is_err() {
+            // Nowhere to ask whether to cancel, so the press is taken as
+            // meaning it.
+            return Some(Transition::To(ScreenId::Refunding));
+        }
+        if self.asking {
+            return None;
+        }
+        if self.paid >= PAID_FRAMES {
+            return Some(Transition::To(ScreenId::Thanks));
+        }
+        let fresh = !self.ticked;
+        self.ticked = true;
+        let ticked = self.countdown.tick();
+        if self.countdown.expired() {
+            // Out of time. Whatever was paid goes back, and with nothing
+            // paid there's nothing to do but start over. Coming back
+            // without a confirmation, as the controller can, gives the
+            // full time again.
+            self.payment.cancel();
+            self.countdown.reset();
+            let to = if self.inserted() > 0 {
+                ScreenId::Refunding
+            } else {
+                ScreenId::Start
+            };
+            return Some(Transition::To(to));
+        }
+        if ticked || fresh {
+            self.countdown.redraw(c);
+        }
+
+        // Flash the payment message to draw the eye.
+        let t = theme();
+        let bg = if (frame / PAY_FLASH_FRAMES).is_multiple_of(2) {
+            t.box_bg
+        } else {
+            t.danger
+        };
+        c.recolor_region(self.message, None, Some(bg));
+
+        if self.payment.is_complete() {
+            self.paid += 1;
+        } else {
+            self.payment.poll();
+            if self.payment.is_complete() {
+                // Gone through: no more hurry, or waiting.
+                self.countdown.pause();
+                let received = tr(StringId::PaymentReceived);
+                self.toast.show(received, TOAST_FRAMES);
+                let mut c = c.styled(t.body_fg, t.body_bg);
+                let r = PAY_SPINNER;
+                c.fill_rect(r.row, r.col, r.h, r.w, b' ');
+            } else {
+                let mut anims = Animations::new();
+                anims.push(&mut self.spinner);
+                if fresh {
+                    anims.invalidate();
+                }
+                anims.tick_all(frame);
+                anims.draw_all(c);
+            }
+        }
+        ProgressBar {
+            rect: PAY_BAR,
+            value: self.payment.done(),
+            max: self.payment.total(),
+            fill: FULL_BLOCK,
+            empty: LIGHT_SHADE,
+            fg: t.box_fg,
+            bg: t.box_bg,
+            percent: Percent::Overlay,
+        }
+        .redraw(c);
+        self.toast.draw(c);
+        None
+    }
+
+    fn on_exit(&mut self, c: &mut Cursor) {
+        self.cancel.close(c);
+        self.asking = false;
+        self.countdown.pause();
+        // Cancelled, or run out of time, if it hasn't gone through.
+        if !self.payment.is_complete() {
+            self.payment.cancel();
+        }
+    }
+}
+
+/// Whether to go ahead with what was chosen, asked over the screen it was
+/// chosen on.
+pub struct ConfirmScreen {
+    confirm: Confirm<'static>,
+    cart: CartView<'static>,
+}
+
+impl ConfirmScreen {
+    pub fn new() -> Self {
+        // Colored on the way in, in the theme of the time.
+        ConfirmScreen {
+            confirm: Confirm::new(b"", Corner::BottomLeft, Corner::BottomRight),
+            cart: CartView::new(CATALOG, CART_VIEW),
+        }
+    }
+
+    /// Asks about `cart`, from its first page.
+    pub fn show(&mut self, cart: Cart) {
+        self.cart.cart = cart;
+        self.cart.set_page(0);
+    }
+}
+
+impl Default for ConfirmScreen {
+    fn default() -> Self {
+        ConfirmScreen::new()
+    }
+}
+
+impl Screen for ConfirmScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let question = tr(StringId::Continue);
+        self.confirm.dialog.message = question;
+        let rect = dialog_rect(c, question);
+        let t = theme();
+        self.confirm.dialog.fg = t.box_fg;
+        self.confirm.dialog.bg = t.box_bg;
+        self.confirm.dialog.buttons = answers();
+        self.confirm.yes_style = &t.yes_keys;
+        self.confirm.no_style = &t.no_keys;
+        self.cart.fg = t.box_fg;
+        self.cart.bg = t.box_bg;
+        self.cart.headings = cart_headings();
+        // Only the answers have buttons now.
+        c.bg = t.body_bg;
+        self.cart.redraw(c);
+        if self.confirm.open(c, rect).is_err() {
+            // No room to keep what is under the dialog, as with a question
+            // too long for it. Ask on the screen itself instead, which is
+            // drawn over whichever way the question is answered.
+            draw_centered(c, rect.row + 1, question);
+            self.confirm.keys().draw(c);
+            return;
+        }
+        // Left of the question, inside the frame.
+        draw_icon(c, rect.row + 1, rect.col + 1, ICON_OK, t.success);
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        match ev {
+            InputEvent::Key(corner) if corner == self.confirm.yes => {
+                Some(Transition::To(ScreenId::Method))
+            }
+            InputEvent::Key(corner) if corner == self.confirm.no => {
+                Some(Transition::Back)
+            }
+            _ => follow_lines(ev),
+        }
+    }
+
+    fn on_exit(&mut self, c: &mut Cursor) {
+        self.confirm.close(c);
+    }
+}
+
+/// Returns the tickets for sale, on the page for line `line`.
+fn line_tickets(line: usize) -> PagedList<'static> {
+    let mut tickets = PagedList::new(&TICKETS, TICKETS_PER_LINE, PAGE_LABEL);
+    tickets.label = tr(StringId::Page);
+    tickets.set_page(line);
+    tickets
+}
+
+/// The tickets for a line, in the top corners, with ways to the other lines
+/// below them.
+pub struct LineScreen {
+    /// Line shown, counting from 0.
+    pub line: usize,
+    /// What is in the cart, for the total under the tickets.
+    pub cart: Cart,
+}
+
+impl Screen for LineScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        // Only the page indicator is drawn by the list; the tickets are
+        // buttons.
+        let tickets = line_tickets(self.line);
+        let line = tickets.page();
+        let mut spec = translated(
+            line_spec(line, &self.cart),
+            StringId::Line1,
+            tr_run(StringId::ChooseTicket1, StringId::ChooseTicket3),
+        );
+        spec.header.title = line_title(line);
+        render_spec(c, &spec);
+        let right_col = PAGE_LABEL.col + PAGE_LABEL.w - 1;
+        tickets.draw_indicator(c, PAGE_LABEL.row, right_col);
+
+        // prices beside the tickets' keys
+        let items = tickets.page_items();
+        let first = line * TICKETS_PER_LINE;
+        let corners = [Corner::TopLeft, Corner::TopRight];
+        for (i, &corner) in corners.iter().enumerate().take(items.len()) {
+            let key = SOFT_KEYS.rect(corner, COLS);
+            let col = match corner {
+                Corner::TopLeft => key.col + key.w + 1,
+                _ => key.col - 1 - PRICE_W,
+            };
+            let mut price = Price::new(
+                TICKET_PRICES[first + i],
+                Rect::new(key.row + 1, col, 1, PRICE_W),
+            );
+            price.fg = spec.fg;
+            price.bg = spec.bg;
+            price.redraw(c);
+        }
+
+        if !self.cart.is_empty() {
+            let total = self.cart.total(&CATALOG);
+            draw_priced(c, LINE_CART_ROW, tr(StringId::InYourCart), total);
+        }
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        let tickets = line_tickets(self.line);
+        let line = tickets.page();
+        let key = match ev {
+            InputEvent::Key(corner) => corner,
+            // The controller can only step on, so it goes round.
+            InputEvent::Lines(LINES_NEXT) => {
+                let next = (line + 1) % tickets.pages();
+                return Some(Transition::To(ScreenId::Line(next)));
+            }
+            // NEXT let go of.
+            InputEvent::Lines(LINES_TICKETS) => return None,
+            _ => return follow_lines(ev),
+        };
+        let first = line * TICKETS_PER_LINE;
+        let to = match key {
+            Corner::TopLeft => ScreenId::Quantity(first),
+            Corner::TopRight if tickets.page_items().len() > 1 => {
+                ScreenId::Quantity(first + 1)
+            }
+            Corner::TopRight => return None,
+            Corner::BottomLeft if line > 0 => ScreenId::Line(line - 1),
+            Corner::BottomRight if line + 1 < tickets.pages() => {
+                ScreenId::Line(line + 1)
+            }
+            Corner::BottomRight if !self.cart.is_empty() => ScreenId::Cart,
+            // QUIT, from the first line or the last.
+            Corner::BottomLeft | Corner::BottomRight => ScreenId::Start,
+        };
+        Some(Transition::To(to))
+    }
+}
+
+/// Returns `spec` in the current language, with `title` for its title and
+/// `lines` for its message, if it has one.
+fn translated(
+    mut spec: ScreenSpec<'static>,
+    title: StringId,
+    lines: &'static [&'static [u8]],
+) -> ScreenSpec<'static> {
+    spec.header.title = tr(title);
+    if let Some(message) = spec.message.as_mut() {
+        message.lines = lines;
+    }
+    spec
+}
+
+/// A message box with a run of strings for its lines, looked up with its
+/// colors each time it is drawn, for widgets that hold on to what they draw.
+struct TrMessage {
+    first: StringId,
+    last: StringId,
+    shadow: bool,
+}
+
+impl TrMessage {
+    fn message(&self) -> MessageBox<'static> {
+        MessageBox {
+            lines: tr_run(self.first, self.last),
+            ..message_box(self.shadow)
+        }
+    }
+}
+
+impl Widget for TrMessage {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        self.message().draw(c, area)
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        self.message().min_size()
+    }
+}
+
+/// Returns the title of line `line`'s ticket screen, which is also its name
+/// on the map.
+fn line_title(line: usize) -> &'static [u8] {
+    tr_run(StringId::Line1, StringId::Line3)[line]
+}
+
+/// Blanks `row` inside the border and centers `text` on it, cut short if it
+/// doesn't fit, however long a translation makes it.
+fn draw_centered(c: &mut Cursor, row: usize, text: &[u8]) {
+    let (_, width) = c.size();
+    let inside = width.saturating_sub(2);
+    c.fill_rect(row, 1, 1, inside, b' ');
+    let len = usize::min(text.len(), inside);
+    c.puts_truncated_at(row, 1 + (inside - len) / 2, text, len)
+        .ok();
+}
+
+/// Returns where to ask `question` in a dialog `CONFIRM_W` wide, centered
+/// on the window: `CONFIRM_H` high, or higher if the question needs more
+/// rows than that leaves it.
+fn dialog_rect(c: &Cursor, question: &[u8]) -> Rect {
+    let (h, w) = c.size();
+    // Frame, and the buttons a row under the question.
+    let chrome = CONFIRM_H - CONFIRM_ROWS;
+    let (rows, _) = c.measure(question, CONFIRM_W - 4);
+    let rect_h = usize::min(usize::max(rows + chrome, CONFIRM_H), DIALOG_MAX_H);
+    Rect::center_within(&Rect::new(0, 0, h, w), CONFIRM_W, rect_h)
+}
+
+/// Draws `label` and then `cents`, centered together on `row`.
+fn draw_priced(c: &mut Cursor, row: usize, label: &[u8], cents: i32) {
+    let (_, width) = c.size();
+    let mut price = Price::new(cents, Rect::new(row, 0, 1, 0));
+    price.fg = c.fg;
+    price.bg = c.bg;
+    let (_, price_w) = price.min_size();
+    let col = width.saturating_sub(label.len() + 1 + price_w) / 2;
+    c.puts_at(row, col, label).ok();
+    price.rect = Rect::new(row, col + label.len() + 1, 1, price_w);
+    price.redraw(c);
+}
+
+/// Returns the row of the captions for the bottom keys, the column the left
+/// one starts at, and the one the right one ends at.
+fn captions(width: usize) -> (usize, usize, usize) {
+    let cancel = SOFT_KEYS.rect(Corner::BottomLeft, width);
+    let confirm = SOFT_KEYS.rect(Corner::BottomRight, width);
+    (cancel.row + 1, cancel.col + cancel.w + 1, confirm.col - 2)
+}
+
+/// Says what the bottom keys do beside them, where their labels are too
+/// short to.
+fn draw_captions(c: &mut Cursor, left: &[u8], right: &[u8]) {
+    let (_, width) = c.size();
+    let (row, left_col, right_col) = captions(width);
+    c.puts_at(row, left_col, left).ok();
+    c.puts_right(row, right_col, right);
+}
+
+fn clear_captions(c: &mut Cursor) {
+    let (_, width) = c.size();
+    let (row, left, right) = captions(width);
+    c.fill_rect(row, left, 1, right + 1 - left, b' ');
+}
+
+/// How many of a ticket to buy, shown big, stepped with the top keys, and
+/// priced below.
+pub struct QuantityScreen {
+    /// Ticket being bought, counting in `TICKETS`.
+    ticket: usize,
+    /// How many, between 1 and `QTY_MAX`; only its value is used.
+    quantity: NumberField,
+    /// Frames left of the flash after running into one of the bounds.
+    flash: u32,
+    /// Whether the quantity has changed since it was drawn.
+    dirty: bool,
+    /// The cart, to add the tickets to.
+    pub cart: Cart,
+    toast: Toast<'static>,
+}
+
+impl QuantityScreen {
+    pub fn new() -> Self {
+        let rect = Rect::new(QTY_ROW, 0, GLYPH_H, COLS);
+        // Colored on the way in, in the theme of the time.
+        QuantityScreen {
+            ticket: 0,
+            quantity: NumberField::new(rect, 1, QTY_MAX, Bounds::Clamp),
+            flash: 0,
+            dirty: false,
+            cart: Cart::new(),
+            toast: Toast::new(TOAST_ROW),
+        }
+    }
+
+    /// Starts over on `ticket`, with one of it.
+    pub fn choose(&mut self, ticket: usize) {
+        self.ticket = ticket;
+        self.quantity.set(1);
+    }
+
+    /// Steps the quantity up or down, flashing it if it was already as far
+    /// as it goes.
+    fn step(&mut self, up: bool) {
+        let before = self.quantity.value();
+        if up {
+            self.quantity.inc();
+        } else {
+            self.quantity.dec();
+        }
+        if self.quantity.value() == before {
+            self.flash = QTY_FLASH_FRAMES;
+        }
+        self.dirty = true;
+    }
+
+    /// Draws the quantity and the prices that depend on it.
+    fn draw_quantity(&self, c: &mut Cursor) {
+        let (_, width) = c.size();
+        // `QTY_MAX` has two digits at most.
+        let qty = self.quantity.value() as u8;
+        let digits = [b'0' + qty / 10, b'0' + qty % 10];
+        let text = if qty < 10 { &digits[1..] } else { &digits[..] };
+        let widest = banner_width(b"00");
+        let col = (width - widest) / 2;
+        c.fill_rect(QTY_ROW, col, GLYPH_H, widest, b' ');
+        {
+            let fg = if self.flash > 0 { theme().danger } else { c.fg };
+            let mut c = c.styled(fg, c.bg);
+            let col = (width - banner_width(text)) / 2;
+            draw_banner(&mut c, QTY_ROW, col, text);
+        }
+        let each = CATALOG.price(self.ticket);
+        let total = each.saturating_mul(self.quantity.value() as i32);
+        c.fill_rect(QTY_TOTAL_ROW, 1, 1, width - 2, b' ');
+        draw_priced(c, QTY_TOTAL_ROW, tr(StringId::Total), total);
+    }
+}
+
+impl Default for QuantityScreen {
+    fn default() -> Self {
+        QuantityScreen::new()
+    }
+}
+
+impl Screen for QuantityScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        render_spec(c, &translated(quantity_spec(), StringId::Quantity, &[]));
+        self.toast.fg = theme().danger_fg;
+        self.toast.bg = theme().danger;
+        let (_, width) = c.size();
+        let name = CATALOG.name(self.ticket);
+        let (before, after) =
+            (tr(StringId::HowMany), tr(StringId::HowManyAfter));
+        let prompt_w = before.len() + name.len() + after.len();
+        c.goto(QTY_PROMPT_ROW, width.saturating_sub(prompt_w) / 2);
+        c.puts(before);
+        c.puts(name);
+        c.puts(after);
+        let each = CATALOG.price(self.ticket);
+        draw_priced(c, QTY_EACH_ROW, tr(StringId::Each), each);
+
+        draw_captions(c, tr(StringId::Cancel), tr(StringId::AddToCart));
+
+        self.flash = 0;
+        self.draw_quantity(c);
+        self.dirty = false;
+        self.toast.dismiss();
+        self.toast.invalidate();
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        match ev {
+            InputEvent::Key(Corner::TopLeft) => self.step(false),
+            InputEvent::Key(Corner::TopRight) => self.step(true),
+            InputEvent::Key(Corner::BottomLeft) => {
+                return Some(Transition::Back)
+            }
+            InputEvent::Key(Corner::BottomRight) => {
+                let qty = self.quantity.value();
+                match self.cart.add(self.ticket, qty) {
+                    // Back to the tickets, for more.
+                    Ok(()) => {
+                        let line = self.ticket / TICKETS_PER_LINE;
+                        return Some(Transition::To(ScreenId::Line(line)));
+                    }
+                    Err(CartError::Full) => {
+                        self.toast.show(tr(StringId::CartFull), TOAST_FRAMES)
+                    }
+                }
+            }
+            _ => return follow_lines(ev),
+        }
+        None
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        if self.flash > 0 {
+            self.flash -= 1;
+            // Back to its own color.
+            self.dirty |= self.flash == 0;
+        }
+        if self.dirty {
+            self.draw_quantity(c);
+            self.dirty = false;
+        }
+        self.toast.draw(c);
+        None
+    }
+}
+
+/// How to pay, one of `METHODS` with its icon, stepped through with the top
+/// keys. Cash can't be chosen while the coin acceptor is out of order.
+pub struct MethodScreen {
+    methods: RadioGroup<'static>,
+    /// Whether the coin acceptor has reported a fault.
+    coin_fault: bool,
+    /// Whether the choice has moved since it was drawn.
+    dirty: bool,
+}
+
+impl MethodScreen {
+    pub fn new() -> Self {
+        // Labelled and colored on the way in, in the language and theme of
+        // the time.
+        let labels = tr_run(StringId::Cash, StringId::Contactless);
+        let mut methods = RadioGroup::new(labels, METHOD_LIST, 2);
+        methods.focused = true;
+        MethodScreen {
+            methods,
+            coin_fault: false,
+            dirty: false,
+        }
+    }
+
+    /// Notes whether the coin acceptor is out of order, which the screen
+    /// shows on its next tick.
+    pub fn set_coin_fault(&mut self, fault: bool) {
+        self.coin_fault = fault;
+    }
+
+    /// Returns the way of paying chosen.
+    pub fn method(&self) -> PayMethod {
+        METHODS[self.methods.selected()]
+    }
+
+    /// Disables the ways of paying that can't be used now, choosing another
+    /// if the one chosen is among them. Returns whether anything changed.
+    fn update_disabled(&mut self) -> bool {
+        let cash = METHODS.iter().position(|&m| m == PayMethod::Cash);
+        let disabled = match cash {
+            Some(i) if self.coin_fault => 1 << i,
+            _ => 0,
+        };
+        if disabled == self.methods.disabled {
+            return false;
+        }
+        self.methods.disabled = disabled;
+        let chosen = self.methods.selected();
+        let to = if self.methods.is_disabled(chosen) {
+            (0..METHODS.len())
+                .find(|&i| !self.methods.is_disabled(i))
+                .unwrap_or(chosen)
+        } else {
+            chosen
+        };
+        // Also takes the focus off anything just disabled.
+        self.methods.select(to);
+        true
+    }
+
+    fn draw_methods(&self, c: &mut Cursor) {
+        self.methods.redraw(c);
+        let rect = self.methods.rect;
+        for (i, method) in METHODS.iter().enumerate() {
+            let fg = if self.methods.is_disabled(i) {
+                self.methods.disabled_fg
+            } else {
+                self.methods.fg
+            };
+            draw_icon(c, rect.row + i, rect.col - 2, method.icon(), fg);
+        }
+    }
+}
+
+impl Default for MethodScreen {
+    fn default() -> Self {
+        MethodScreen::new()
+    }
+}
+
+impl Screen for MethodScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let spec = translated(choice_spec(), StringId::PaymentMethod, &[]);
+        render_spec(c, &spec);
+        draw_centered(c, METHOD_PROMPT_ROW, tr(StringId::HowToPay));
+        draw_captions(c, tr(StringId::Cancel), tr(StringId::Pay));
+        self.methods.items = tr_run(StringId::Cash, StringId::Contactless);
+        self.methods.fg = spec.fg;
+        self.methods.bg = spec.bg;
+        self.update_disabled();
+        self.draw_methods(c);
+        self.dirty = false;
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        let step = match ev {
+            InputEvent::Key(Corner::TopLeft) => MenuEvent::Up,
+            InputEvent::Key(Corner::TopRight) => MenuEvent::Down,
+            InputEvent::Key(Corner::BottomLeft) => {
+                return Some(Transition::To(ScreenId::Confirm))
+            }
+            InputEvent::Key(Corner::BottomRight) => {
+                return Some(Transition::To(ScreenId::Paying))
+            }
+            _ => return follow_lines(ev),
+        };
+        // The choice follows the focus, there being no key to choose with.
+        self.methods.handle(step);
+        self.methods.handle(MenuEvent::Select);
+        self.dirty = true;
+        None
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        if self.update_disabled() || self.dirty {
+            self.draw_methods(c);
+            self.dirty = false;
+        }
+        None
+    }
+}
+
+/// Giving back what was paid for a cancelled transaction, then back to the
+/// start.
+pub struct RefundingScreen {
+    /// Amount given back, in pence.
+    pub amount: i32,
+    /// Frames the refund has taken so far.
+    done: u32,
+}
+
+impl Screen for RefundingScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let message = tr_run(StringId::Cancelled1, StringId::Cancelled2);
+        render_spec(
+            c,
+            &translated(refund_spec(), StringId::Cancelled, message),
+        );
+        if self.amount > 0 {
+            let label = tr(StringId::Refunding);
+            draw_priced(c, REFUND_ROW, label, self.amount);
+        } else {
+            draw_centered(c, REFUND_ROW, tr(StringId::NoPayment));
+        }
+        self.done = 0;
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        if self.done >= REFUND_FRAMES {
+            return Some(Transition::To(ScreenId::Start));
+        }
+        // Stand-in for the coin hopper or card terminal giving it back.
+        ProgressBar {
+            rect: PAY_BAR,
+            value: self.done,
+            max: REFUND_FRAMES,
+            fill: FULL_BLOCK,
+            empty: LIGHT_SHADE,
+            fg: theme().box_fg,
+            bg: theme().box_bg,
+            percent: Percent::Overlay,
+        }
+        .redraw(c);
+        self.done += 1;
+        None
+    }
+}
+
+/// What is in the cart, one line selected to take out with DEL, and the way
+/// on to paying for it.
+pub struct CartScreen {
+    view: CartView<'static>,
+    toast: Toast<'static>,
+    /// Whether the cart has changed since it was drawn.
+    dirty: bool,
+}
+
+impl CartScreen {
+    pub fn new() -> Self {
+        // Colored on the way in, in the theme of the time.
+        CartScreen {
+            view: CartView::new(CATALOG, CART_LIST),
+            toast: Toast::new(TOAST_ROW),
+            dirty: false,
+        }
+    }
+
+    /// Returns the cart, as changed here.
+    pub fn cart(&self) -> Cart {
+        self.view.cart
+    }
+
+    /// Shows `cart`, with its first line selected.
+    pub fn set_cart(&mut self, cart: Cart) {
+        self.view.cart = cart;
+        self.view.selected = if cart.is_empty() { None } else { Some(0) };
+        self.view.set_page(0);
+    }
+
+    fn draw_cart(&mut self, c: &mut Cursor) {
+        if let Some(line) = self.view.selected {
+            self.view.set_page(line / self.view.page_size());
+        }
+        self.view.redraw(c);
+        let text = if self.view.cart.is_empty() {
+            tr(StringId::CartEmpty)
+        } else {
+            b""
+        };
+        draw_centered(c, CART_EMPTY_ROW, text);
+    }
+}
+
+impl Default for CartScreen {
+    fn default() -> Self {
+        CartScreen::new()
+    }
+}
+
+impl Screen for CartScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        render_spec(c, &translated(cart_spec(), StringId::YourCart, &[]));
+        let t = theme();
+        self.view.fg = t.box_fg;
+        self.view.bg = t.box_bg;
+        self.view.headings = cart_headings();
+        self.toast.fg = t.danger_fg;
+        self.toast.bg = t.danger;
+        let (left, right) = (StringId::MoreTickets, StringId::Checkout);
+        draw_captions(c, tr(left), tr(right));
+        self.draw_cart(c);
+        self.dirty = false;
+        self.toast.dismiss();
+        self.toast.invalidate();
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        let len = self.view.cart.len();
+        match ev {
+            InputEvent::Key(Corner::TopLeft) => {
+                self.view.selected = self.view.selected.map(|i| (i + 1) % len);
+            }
+            InputEvent::Key(Corner::TopRight) => {
+                if let Some(line) = self.view.selected {
+                    self.view.cart.remove(line);
+                    let len = self.view.cart.len();
+                    self.view.selected = match len {
+                        0 => None,
+                        _ => Some(usize::min(line, len - 1)),
+                    };
+                }
+            }
+            InputEvent::Key(Corner::BottomLeft) => {
+                return Some(Transition::To(ScreenId::Line(LINES - 1)))
+            }
+            InputEvent::Key(Corner::BottomRight) if len == 0 => {
+                self.toast.show(tr(StringId::CartEmpty), TOAST_FRAMES);
+            }
+            InputEvent::Key(Corner::BottomRight) => {
+                return Some(Transition::To(ScreenId::Confirm))
+            }
+            _ => return follow_lines(ev),
+        }
+        self.dirty = true;
+        None
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        if self.dirty {
+            self.draw_cart(c);
+            self.dirty = false;
+        }
+        self.toast.draw(c);
+        None
+    }
+
+    /// Blanks the captions, which would be wrong beside the keys of the
+    /// confirmation drawn over this screen.
+    fn on_exit(&mut self, c: &mut Cursor) {
+        clear_captions(c);
+    }
+}
+
+/// Closed, with an apology. Nothing a passenger does is answered; only
+/// `Screens::set_out_of_service` leaves it.
+pub struct OutOfServiceScreen {
+    /// Why, shown under the apology.
+    pub reason: Option<StringId>,
+}
+
+impl Screen for OutOfServiceScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let apology = tr_run(StringId::Apology1, StringId::Apology5);
+        let spec = translated(out_spec(), StringId::OutOfService, apology);
+        render_spec(c, &spec);
+        let (_, width) = c.size();
+        {
+            let mut c = c.styled(spec.header.style.bg, spec.bg);
+            let banner = tr(StringId::Sorry);
+            let col = width.saturating_sub(banner_width(banner)) / 2;
+            draw_banner(&mut c, OUT_BANNER_ROW, col, banner);
+        }
+        if let Some(reason) = self.reason {
+            let (label, reason) = (tr(StringId::Reason), tr(reason));
+            let w = usize::min(label.len() + reason.len(), width - 4);
+            c.goto(OUT_REASON_ROW, (width - w) / 2);
+            c.puts_truncated(label, w);
+            c.puts_truncated(reason, w.saturating_sub(label.len()));
+        }
+    }
+
+    fn on_event(&mut self, _ev: InputEvent) -> Option<Transition> {
+        None
+    }
+}
+
+/// The next departures, as far ahead as the board has rows.
+pub struct DeparturesScreen {
+    board: DepartureBoard,
+    pub schedule: Schedule,
+}
+
+impl DeparturesScreen {
+    pub fn new() -> Self {
+        DeparturesScreen {
+            board: DepartureBoard::new(DEPARTURES),
+            schedule: Schedule::new(),
+        }
+    }
+
+    /// Draws the screen from `spec`, with the title and headings of the
+    /// board, and leaves the board for the next tick.
+    fn draw(&mut self, c: &mut Cursor, spec: ScreenSpec<'static>) {
+        let mut spec = translated(spec, StringId::Departures, &[]);
+        spec.header.subtitle = Some(tr(StringId::LinesOneToThree));
+        render_spec(c, &spec);
+        // Over the columns of `DepartureBoard`'s rows.
+        let (row, col) = (DEPARTURES_HEADINGS_ROW, DEPARTURES.col);
+        c.puts_at(row, col, tr(StringId::LineHeading)).ok();
+        let destination = tr(StringId::DestinationHeading);
+        c.puts_at(row, col + LINE_W + 2, destination).ok();
+        let leaves_in = tr(StringId::LeavesInHeading);
+        c.puts_right(row, col + DEPARTURES.w - 2, leaves_in);
+        self.board.due = tr(StringId::Due);
+        self.board.invalidate();
+    }
+}
+
+impl Default for DeparturesScreen {
+    fn default() -> Self {
+        DeparturesScreen::new()
+    }
+}
+
+impl Screen for DeparturesScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        self.draw(c, departures_spec());
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        match ev {
+            InputEvent::Key(Corner::BottomLeft) => {
+                Some(Transition::To(ScreenId::Start))
+            }
+            _ => follow_lines(ev),
+        }
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        let departures = self.schedule.departures();
+        if self.board.update(c, departures) {
+            let text = if departures.is_empty() {
+                tr(StringId::NoDepartures)
+            } else {
+                b""
+            };
+            draw_centered(c, DEPARTURES_NONE_ROW, text);
+        }
+        None
+    }
+}
+
+/// The map of the network, with the keys choosing a line, and OK going to
+/// its tickets.
+pub struct MapScreen {
+    /// The map of `NETWORK`, but for the names of its lines and its colors,
+    /// which are put in each time it is drawn.
+    map: LineMap<'static>,
+    /// Whether the selection has changed since the map was drawn.
+    dirty: bool,
+}
+
+impl MapScreen {
+    pub fn new() -> Self {
+        MapScreen {
+            map: LineMap::new(&NETWORK, NETWORK_MAP),
+            dirty: false,
+        }
+    }
+
+    fn redraw(&self, c: &mut Cursor) {
+        let mut lines = NETWORK;
+        for (i, line) in lines.iter_mut().enumerate() {
+            line.name = line_title(i);
+        }
+        let t = theme();
+        LineMap {
+            lines: &lines,
+            fg: t.body_fg,
+            bg: t.body_bg,
+            ..self.map
+        }
+        .redraw(c);
+    }
+}
+
+impl Default for MapScreen {
+    fn default() -> Self {
+        MapScreen::new()
+    }
+}
+
+impl Screen for MapScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let mut spec = translated(choice_spec(), StringId::NetworkMap, &[]);
+        spec.header.subtitle = Some(tr(StringId::ChooseLine));
+        render_spec(c, &spec);
+        self.redraw(c);
+        self.dirty = false;
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        match ev {
+            InputEvent::Key(Corner::TopLeft) => self.map.step(false),
+            InputEvent::Key(Corner::TopRight) => self.map.step(true),
+            InputEvent::Key(Corner::BottomLeft) => {
+                return Some(Transition::To(ScreenId::Start))
+            }
+            InputEvent::Key(Corner::BottomRight) => {
+                return Some(Transition::To(ScreenId::Line(self.map.selected)))
+            }
+            _ => return follow_lines(ev),
+        }
+        self.dirty = true;
+        None
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        if self.dirty {
+            self.redraw(c);
+            self.dirty = false;
+        }
+        None
+    }
+}
+
+/// Pages of the attract loop, in the order they are shown.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum AttractPage {
+    Departures,
+    Map,
+    Promo,
+}
+
+impl AttractPage {
+    fn next(self) -> Self {
+        match self {
+            AttractPage::Departures => AttractPage::Map,
+            AttractPage::Map => AttractPage::Promo,
+            AttractPage::Promo => AttractPage::Departures,
+        }
+    }
+}
+
+/// Pages to catch the eye of passers-by, shown in turn for as long as
+/// nobody comes, under an invitation to start. Any button, or any change of
+/// the input lines, goes back to the start screen, to wait there as long
+/// again.
+pub struct AttractScreen {
+    /// The departures and the map, as on their own screens but without
+    /// their keys, which do nothing here.
+    departures: DeparturesScreen,
+    map: MapScreen,
+    page: AttractPage,
+    /// Frames the page has been up for.
+    shown: u32,
+    invite: Marquee<'static>,
+    /// Whether a page has been drawn since the last tick.
+    fresh: bool,
+}
+
+impl AttractScreen {
+    pub fn new() -> Self {
+        // Worded and colored on the way in, in the language and theme of
+        // the time.
+        AttractScreen {
+            departures: DeparturesScreen::new(),
+            map: MapScreen::new(),
+            page: AttractPage::Departures,
+            shown: 0,
+            invite: Marquee::new(b"", ATTRACT_INVITE, 8, 4, FPS),
+            fresh: false,
+        }
+    }
+
+    fn draw_page(&mut self, c: &mut Cursor) {
+        match self.page {
+            AttractPage::Departures => self.departures.draw(c, plain_spec()),
+            AttractPage::Map => {
+                let spec = translated(plain_spec(), StringId::NetworkMap, &[]);
+                render_spec(c, &spec);
+                // A different line picked out each time round.
+                self.map.map.step(true);
+                self.map.redraw(c);
+            }
+            AttractPage::Promo => {
+                let spec = ScreenSpec {
+                    message: Some(message_box(true)),
+                    ..plain_spec()
+                };
+                let promo = tr_run(StringId::Promo1, StringId::Promo3);
+                let spec = translated(spec, StringId::PromoTitle, promo);
+                render_spec(c, &spec);
+            }
+        }
+        self.shown = 0;
+        self.fresh = true;
+    }
+}
+
+impl Default for AttractScreen {
+    fn default() -> Self {
+        AttractScreen::new()
+    }
+}
+
+impl Screen for AttractScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let t = theme();
+        self.invite.text = tr(StringId::Invite);
+        self.invite.fg = t.accent;
+        self.invite.bg = t.body_bg;
+        self.invite.reset();
+        self.page = AttractPage::Departures;
+        self.draw_page(c);
+    }
+
+    fn on_event(&mut self, _ev: InputEvent) -> Option<Transition> {
+        Some(Transition::To(ScreenId::Start))
+    }
+
+    fn on_tick(&mut self, c: &mut Cursor, frame: u32) -> Option<Transition> {
+        self.shown += 1;
+        if self.shown >= ATTRACT_PAGE_FRAMES {
+            self.page = self.page.next();
+            self.draw_page(c);
+        }
+        if self.page == AttractPage::Departures {
+            self.departures.on_tick(c, frame);
+        }
+        let mut anims = Animations::new();
+        anims.push(&mut self.invite);
+        if self.fresh {
+            anims.invalidate();
+            self.fresh = false;
+        }
+        anims.tick_all(frame);
+        anims.draw_all(c);
+        None
+    }
+}
+
+/// The languages the kiosk speaks, stepped through with the top keys. OK
+/// switches every screen to the one chosen.
+pub struct LanguageScreen {
+    languages: RadioGroup<'static>,
+    /// Whether the choice has moved since it was drawn.
+    dirty: bool,
+}
+
+impl LanguageScreen {
+    pub fn new() -> Self {
+        // Colored on the way in, in the theme of the time.
+        let mut languages = RadioGroup::new(&LANGUAGE_NAMES, LANGUAGE_LIST, 0);
+        languages.focused = true;
+        LanguageScreen {
+            languages,
+            dirty: false,
+        }
+    }
+}
+
+impl Default for LanguageScreen {
+    fn default() -> Self {
+        LanguageScreen::new()
+    }
+}
+
+impl Screen for LanguageScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let spec = translated(choice_spec(), StringId::Language, &[]);
+        render_spec(c, &spec);
+        self.languages.fg = spec.fg;
+        self.languages.bg = spec.bg;
+        draw_centered(c, LANGUAGE_PROMPT_ROW, tr(StringId::ChooseLanguage));
+        self.languages.select(language() as usize);
+        self.languages.redraw(c);
+        self.dirty = false;
+    }
+
+    fn on_event(&mut self, ev: InputEvent) -> Option<Transition> {
+        let step = match ev {
+            InputEvent::Key(Corner::TopLeft) => MenuEvent::Up,
+            InputEvent::Key(Corner::TopRight) => MenuEvent::Down,
+            InputEvent::Key(Corner::BottomLeft) => {
+                return Some(Transition::To(ScreenId::Start))
+            }
+            InputEvent::Key(Corner::BottomRight) => {
+                set_language(Language::ALL[self.languages.selected()]);
+                return Some(Transition::To(ScreenId::Start));
+            }
+            _ => return follow_lines(ev),
+        };
+        // The choice follows the focus, as with the ways of paying.
+        self.languages.handle(step);
+        self.languages.handle(MenuEvent::Select);
+        self.dirty = true;
+        None
+    }
+
+    fn on_tick(
+        &mut self,
+        c: &mut Cursor,
+        _frame: u32,
+    ) -> Option<Transition> {
+        if self.dirty {
+            self.languages.redraw(c);
+            self.dirty = false;
+        }
+        None
+    }
+}
+
+/// Goodbye, with the ticket's code and the transaction's reference, and
+/// confetti falling around them.
+pub struct ThanksScreen {
+    pub reference: Option<Reference>,
+    confetti: Confetti<'static>,
+}
+
+impl ThanksScreen {
+    pub fn new() -> Self {
+        ThanksScreen {
+            reference: None,
+            confetti: Confetti::new(CONFETTI, CONFETTI_PIECES, CONFETTI_PERIOD),
+        }
+    }
+}
+
+impl Default for ThanksScreen {
+    fn default() -> Self {
+        ThanksScreen::new()
+    }
+}
+
+impl Screen for ThanksScreen {
+    fn on_enter(&mut self, c: &mut Cursor) {
+        let thanks = tr_run(StringId::Thanks1, StringId::Thanks3);
+        let spec = translated(thanks_spec(), StringId::ThankYou, thanks);
+        render_spec(c, &spec);
+        // Everything drawn from here on is kept clear of confetti.
+        self.confetti.bg = spec.bg;
+        self.confetti.start();
+        let (h, w) = c.size();
+        if let Some(rect) = spec.message_rect(h, w) {
+            self.confetti.keep_out(rect);
+        }
+        let banner = tr(StringId::ThankYouBanner);
+        let width = banner_width(banner);
+        let col = COLS.saturating_sub(width) / 2;
+        draw_banner(c, 7, col, banner);
+        self.confetti.keep_out(Rect::new(7, col, GLYPH_H, width));
+        let label = IconLabel {
+            icon: ICON_OK,
+            text: tr(StringId::TicketIssued),
+            icon_fg: theme().success,
+            fg: spec.fg,
+            bg: spec.bg,
+        };
+        let (_, width) = label.min_size();
+        let rect = Rect::new(24, (COLS - width) / 2, 1, width);
+        label.draw(c, rect);
+        self.confetti.keep_out(rect);
+
+        // ticket code, and the reference it is made from spelled out big
+        // enough to note down
+        let reference = match &self.reference {
+            Some(r) => r.as_bytes(),
+            None => return,
+        };
+        {
+            // Whatever the theme, as scanners expect.
+            let mut c = c.styled(BLACK, WHITE);
+            draw_code(&mut c, TICKET_CODE, reference);
+        }
+        self.confetti.keep_out(TICKET_CODE);
+        let caption = tr(StringId::YourReference);
+        draw_centered(c, REFERENCE_ROW - 2, caption);
+        let col = COLS.saturating_sub(caption.len()) / 2;
+        let rect = Rect::new(REFERENCE_ROW - 2, col, 1, caption.len());
+        self.confetti.keep_out(rect);
+        let width = banner_width(reference);
+        let col = COLS.saturating_sub(width) / 2;
+        let mut c = c.styled(theme().accent, spec.bg);
+        draw_banner(&mut c, REFERENCE_ROW, col, reference);
+        let rect = Rect::new(REFERENCE_ROW, col, GLYPH_H, width);
+        self.confetti.keep_out(rect);
+    }
+
+    fn on_tick(&mut self, c: &mut Cursor, frame: u32) -> Option<Transition> {
+        let mut anims = Animations::new();
+        anims.push(&mut self.confetti);
+        anims.tick_all(frame);
+        anims.draw_all(c);
+        None
+    }
+
+    fn on_exit(&mut self, c: &mut Cursor) {
+        self.confetti.stop(c);
+    }
+}
+
+/// Every screen, and which is current. Starts on the error screen, which
+/// the caller should already have drawn.
+pub struct Screens {
+    pub error: ErrorScreen,
+    pub start: StartScreen,
+    pub paying: PayingScreen,
+    pub confirm: ConfirmScreen,
+    pub line: LineScreen,
+    pub thanks: ThanksScreen,
+    pub quantity: QuantityScreen,
+    pub method: MethodScreen,
+    pub refunding: RefundingScreen,
+    pub out_of_service: OutOfServiceScreen,
+    pub cart: CartScreen,
+    pub departures: DeparturesScreen,
+    pub map: MapScreen,
+    pub language: LanguageScreen,
+    pub attract: AttractScreen,
+    pub transaction: Transaction,
+    /// Gives each transaction paid for its reference. Should be replaced
+    /// with one seeded differently each boot.
+    pub references: References,
+    current: ScreenId,
+    previous: ScreenId,
+    /// The screen the confirmation was asked from, drawn under it and gone
+    /// back to from it, however often the way of paying was cancelled.
+    confirm_from: ScreenId,
+    /// Ticks to go before a screen just entered takes input: the rest of
+    /// the frame it was entered on, and the whole of the next. Input until
+    /// then is dropped, so that the press that left one screen, or a bounce
+    /// of it, isn't taken as a choice on the next.
+    settling: u8,
+}
+
+/// `Screens::settling` for a screen just entered.
+const SETTLE_TICKS: u8 = 2;
+
+impl Screens {
+    pub fn new() -> Self {
+        Screens {
+            error: ErrorScreen,
+            start: StartScreen::new(),
+            paying: PayingScreen::new(),
+            confirm: ConfirmScreen::new(),
+            line: LineScreen {
+                line: 0,
+                cart: Cart::new(),
+            },
+            thanks: ThanksScreen::new(),
+            quantity: QuantityScreen::new(),
+            method: MethodScreen::new(),
+            refunding: RefundingScreen { amount: 0, done: 0 },
+            out_of_service: OutOfServiceScreen { reason: None },
+            cart: CartScreen::new(),
+            departures: DeparturesScreen::new(),
+            map: MapScreen::new(),
+            language: LanguageScreen::new(),
+            attract: AttractScreen::new(),
+            transaction: Transaction::new(),
+            references: References::new(0),
+            current: ScreenId::Error,
+            previous: ScreenId::Error,
+            confirm_from: ScreenId::Error,
+            settling: 0,
+        }
+    }
+
+    pub fn current(&self) -> ScreenId {
+        self.current
+    }
+
+    /// Returns whether input is being dropped, a screen having just been
+    /// entered.
+    pub fn settling(&self) -> bool {
+        self.settling > 0
+    }
+
+    /// Passes `ev` to the current screen, and goes where it says. Returns
+    /// whether a screen was entered. Input from the frame a screen was
+    /// entered on until it has ticked in the next is dropped, without the
+    /// screen seeing it.
+    pub fn handle(&mut self, c: &mut Cursor, ev: InputEvent) -> bool {
+        if self.settling() {
+            return false;
+        }
+        match self.screen(self.current).on_event(ev) {
+            Some(transition) => {
+                self.go(c, transition);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Leaves the current screen and enters the one `transition` names,
+    /// even if that's the same one.
+    pub fn go(&mut self, c: &mut Cursor, transition: Transition) {
+        let id = match transition {
+            Transition::To(id) => id,
+            Transition::Back if self.current == ScreenId::Confirm => {
+                self.confirm_from
+            }
+            Transition::Back => self.previous,
+        };
+        self.screen(self.current).on_exit(c);
+        // What the screen left changed of the transaction.
+        match self.current {
+            ScreenId::Quantity(_) => self.transaction.cart = self.quantity.cart,
+            ScreenId::Cart => self.transaction.cart = self.cart.cart(),
+            ScreenId::Method if id == ScreenId::Paying => {
+                self.transaction.method = self.method.method();
+            }
+            ScreenId::Paying => {
+                self.transaction.inserted = self.paying.inserted();
+            }
+            _ => {}
+        }
+        // A new customer starts with nothing chosen, and one cut off by the
+        // kiosk closing has their transaction cancelled.
+        if id == ScreenId::Start || id == ScreenId::OutOfService {
+            self.transaction = Transaction::new();
+        }
+        // What the screen entered needs of it.
+        match id {
+            ScreenId::Confirm => {
+                // Cancelling the way of paying asks again, over the screen
+                // it was first asked from.
+                if self.current == ScreenId::Method {
+                    self.screen(self.confirm_from).on_enter(c);
+                } else {
+                    self.confirm_from = self.current;
+                }
+                // Paying starts over with a full countdown after each
+                // confirmation.
+                self.paying.restart();
+                self.confirm.show(self.transaction.cart);
+            }
+            ScreenId::Paying => {
+                self.paying.method = self.transaction.method;
+                self.paying.due = self.transaction.cart.total(&CATALOG);
+            }
+            ScreenId::Refunding => {
+                self.refunding.amount = self.transaction.inserted;
+            }
+            ScreenId::Line(line) => {
+                self.line.line = line;
+                self.line.cart = self.transaction.cart;
+            }
+            ScreenId::Quantity(ticket) => {
+                // Going back to the quantity keeps it as it was.
+                if let Transition::To(_) = transition {
+                    self.quantity.choose(ticket);
+                }
+                self.quantity.cart = self.transaction.cart;
+            }
+            ScreenId::Cart => self.cart.set_cart(self.transaction.cart),
+            ScreenId::Thanks => {
+                // Paid for, so given its reference, once.
+                if self.transaction.reference.is_none() {
+                    let reference = self.references.issue();
+                    self.transaction.reference = Some(reference);
+                }
+                self.thanks.reference = self.transaction.reference;
+            }
+            _ => {}
+        }
+        self.previous = self.current;
+        self.current = id;
+        self.settling = SETTLE_TICKS;
+        self.screen(id).on_enter(c);
+    }
+
+    /// Takes the kiosk out of service if `out`, overriding whatever screen
+    /// is showing, or otherwise puts it back in service at the start screen,
+    /// if it isn't already as asked. Returns whether a screen was entered.
+    pub fn set_out_of_service(&mut self, c: &mut Cursor, out: bool) -> bool {
+        if out == (self.current == ScreenId::OutOfService) {
+            return false;
+        }
+        let id = if out {
+            ScreenId::OutOfService
+        } else {
+            ScreenId::Start
+        };
+        self.go(c, Transition::To(id));
+        true
+    }
+
+    /// Draws the current screen again, as after a change of theme. Screens
+    /// keep what was chosen on them, but start over anything under way, such
+    /// as a payment.
+    pub fn redraw(&mut self, c: &mut Cursor) {
+        // The question is drawn over the screen it is about.
+        if self.current == ScreenId::Confirm {
+            self.screen(self.confirm_from).on_enter(c);
+        }
+        self.screen(self.current).on_enter(c);
+    }
+
+    /// Shows `schedule` on every screen with departures. Call whenever it
+    /// changes, on any screen.
+    pub fn set_schedule(&mut self, schedule: &Schedule) {
+        self.start.schedule = *schedule;
+        self.departures.schedule = *schedule;
+        self.attract.departures.schedule = *schedule;
+    }
+
+    /// Ticks the current screen on to frame `frame`, and goes where it says
+    /// if it is done. Returns whether a screen was entered.
+    pub fn tick(&mut self, c: &mut Cursor, frame: u32) -> bool {
+        let entered = match self.screen(self.current).on_tick(c, frame) {
+            Some(transition) => {
+                self.go(c, transition);
+                true
+            }
+            None => false,
+        };
+        self.settling = self.settling.saturating_sub(1);
+        entered
+    }
+
+    fn screen(&mut self, id: ScreenId) -> &mut dyn Screen {
+        match id {
+            ScreenId::Error => &mut self.error,
+            ScreenId::Start => &mut self.start,
+            ScreenId::Paying => &mut self.paying,
+            ScreenId::Confirm => &mut self.confirm,
+            ScreenId::Line(_) => &mut self.line,
+            ScreenId::Thanks => &mut self.thanks,
+            ScreenId::Quantity(_) => &mut self.quantity,
+            ScreenId::Method => &mut self.method,
+            ScreenId::Refunding => &mut self.refunding,
+            ScreenId::OutOfService => &mut self.out_of_service,
+            ScreenId::Cart => &mut self.cart,
+            ScreenId::Departures => &mut self.departures,
+            ScreenId::Map => &mut self.map,
+            ScreenId::Language => &mut self.language,
+            ScreenId::Attract => &mut self.attract,
+        }
+    }
+}
+
+impl Default for Screens {
+    fn default() -> Self {
+        Screens::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payment::SIMULATED_SECONDS;
+    use crate::ROWS;
+    use m4vga::rast::text_10x16::AChar;
+    use textui::chrome::screen_area;
+
+    /// The screens on a text buffer of their own, driven a frame at a time
+    /// as the main loop does: input first, then a tick.
+    struct Kiosk {
+        buf: [AChar; COLS * ROWS],
+        screens: Screens,
+        frame: u32,
+    }
+
+    impl Kiosk {
+        /// Starts on the start screen.
+        fn new() -> Self {
+            let mut k = Kiosk {
+                buf: [AChar::from_ascii_char(b' '); COLS * ROWS],
+                screens: Screens::new(),
+                frame: 0,
+            };
+            k.go(ScreenId::Start);
+            k
+        }
+
+        fn go(&mut self, id: ScreenId) {
+            let mut c = Cursor::new(&mut self.buf, COLS);
+            let mut area = screen_area(&mut c);
+            self.screens.go(&mut area, Transition::To(id));
+        }
+
+        fn set_out_of_service(&mut self, out: bool) -> ScreenId {
+            let mut c = Cursor::new(&mut self.buf, COLS);
+            let mut area = screen_area(&mut c);
+            self.screens.set_out_of_service(&mut area, out);
+            self.screens.current()
+        }
+
+        /// Runs a frame with `evs`, in order, and returns the screen after
+        /// it.
+        fn frame(&mut self, evs: &[InputEvent]) -> ScreenId {
+            let mut c = Cursor::new(&mut self.buf, COLS);
+            let mut area = screen_area(&mut c);
+            for &ev in evs {
+                self.screens.handle(&mut area, ev);
+            }
+            self.screens.tick(&mut area, self.frame);
+            self.frame = self.frame.wrapping_add(1);
+            self.screens.current()
+        }
+
+        /// Runs `n` frames without input.
+        fn wait(&mut self, n: u32) -> ScreenId {
+            for _ in 0..n {
+                self.frame(&[]);
+            }
+            self.screens.current()
+        }
+
+        /// Runs frames without input until the screen takes input.
+        fn settle(&mut self) {
+            while self.screens.settling() {
+                self.frame(&[]);
+            }
+        }
+
+        /// Presses the button by `corner`, once the screen has settled.
+        fn press(&mut self, corner: Corner) -> ScreenId {
+            self.settle();
+            self.frame(&[InputEvent::Key(corner)])
+        }
+
+        fn lines(&mut self, s: u8) -> ScreenId {
+            self.settle();
+            self.frame(&[InputEvent::Lines(s)])
+        }
+
+        /// Adds `qty` of the first ticket on the line showing to the cart,
+        /// back on that line.
+        fn add_first(&mut self, qty: u32) {
+            let line = match self.screens.current() {
+                ScreenId::Line(line) => line,
+                id => panic!("adding on {:?}", id),
+            };
+            let ticket = line * TICKETS_PER_LINE;
+            assert_eq!(self.press(Corner::TopLeft), ScreenId::Quantity(ticket));
+            for _ in 1..qty {
+                self.press(Corner::TopRight);
+            }
+            assert_eq!(self.press(Corner::BottomRight), ScreenId::Line(line));
+        }
+
+        /// Goes on from the line showing to the cart, past the last line.
+        fn page_to_cart(&mut self) {
+            while let ScreenId::Line(_) = self.screens.current() {
+                self.press(Corner::BottomRight);
+            }
+            assert_eq!(self.screens.current(), ScreenId::Cart);
+        }
+    }
+
+    #[test]
+    fn paying_for_the_cart_ends_with_thanks() {
+        let mut k = Kiosk::new();
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Line(0));
+        k.add_first(2);
+        assert_eq!(k.screens.transaction.cart.qty(0), 2);
+        k.page_to_cart();
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Confirm);
+        assert_eq!(k.press(Corner::BottomLeft), ScreenId::Method);
+        assert_eq!(k.screens.method.method(), PayMethod::Contactless);
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Paying);
+        assert_eq!(k.screens.paying.due, 500);
+
+        // Through in the simulated time, then held for a moment.
+        let paying = SIMULATED_SECONDS * FPS + PAID_FRAMES;
+        assert_eq!(k.wait(paying - 1), ScreenId::Paying);
+        assert_eq!(k.wait(2), ScreenId::Thanks);
+        let transaction = k.screens.transaction;
+        assert_eq!(transaction.inserted, 500);
+        assert_eq!(transaction.method, PayMethod::Contactless);
+        assert!(transaction.reference.is_some());
+        assert_eq!(k.screens.thanks.reference, transaction.reference);
+        assert_eq!(k.screens.references.issued(), 1);
+
+        // A new customer starts with nothing.
+        assert_eq!(k.lines(0b001), ScreenId::Start);
+        assert!(k.screens.transaction.cart.is_empty());
+        assert!(k.screens.transaction.reference.is_none());
+    }
+
+    #[test]
+    fn cart_is_handed_between_screens() {
+        let mut k = Kiosk::new();
+        k.press(Corner::BottomRight);
+        k.add_first(1);
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Line(1));
+        k.add_first(3);
+        assert_eq!(k.screens.line.cart.len(), 2);
+        k.page_to_cart();
+        assert_eq!(k.screens.cart.cart().len(), 2);
+
+        // DEL takes out the line selected, the first.
+        k.press(Corner::TopRight);
+        assert_eq!(k.press(Corner::BottomLeft), ScreenId::Line(LINES - 1));
+        let cart = k.screens.transaction.cart;
+        assert_eq!((cart.qty(0), cart.qty(2)), (0, 3));
+        assert_eq!(k.screens.line.cart.qty(2), 3);
+
+        // Cancelling a quantity leaves the cart as it was.
+        k.press(Corner::TopLeft);
+        k.press(Corner::TopRight);
+        assert_eq!(k.press(Corner::BottomLeft), ScreenId::Line(LINES - 1));
+        assert_eq!(k.screens.transaction.cart.len(), 1);
+    }
+
+    #[test]
+    fn confirming_asks_over_the_cart_and_no_goes_back() {
+        let mut k = Kiosk::new();
+        k.press(Corner::BottomRight);
+        k.add_first(1);
+        k.page_to_cart();
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Confirm);
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Cart);
+        assert_eq!(k.screens.cart.cart().len(), 1);
+
+        // An empty cart can't be checked out.
+        k.press(Corner::TopRight);
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Cart);
+    }
+
+    #[test]
+    fn cancelling_a_payment_refunds_what_was_paid() {
+        let mut k = Kiosk::new();
+        k.press(Corner::BottomRight);
+        k.add_first(2);
+        k.page_to_cart();
+        k.press(Corner::BottomRight);
+        k.press(Corner::BottomLeft);
+        k.press(Corner::TopLeft);
+        k.press(Corner::TopLeft);
+        assert_eq!(k.screens.method.method(), PayMethod::Cash);
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Paying);
+        k.wait(SIMULATED_SECONDS * FPS / 2);
+
+        // Asked first, and NO carries on.
+        assert_eq!(k.press(Corner::BottomLeft), ScreenId::Paying);
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Paying);
+        k.press(Corner::BottomLeft);
+        assert_eq!(k.press(Corner::BottomLeft), ScreenId::Refunding);
+        let refund = k.screens.refunding.amount;
+        assert!(refund > 0 && refund < 500 && refund % 10 == 0, "{}", refund);
+        assert_eq!(k.screens.transaction.inserted, refund);
+
+        assert_eq!(k.wait(REFUND_FRAMES + 1), ScreenId::Start);
+        assert!(k.screens.transaction.cart.is_empty());
+    }
+
+    #[test]
+    fn out_of_service_overrides_and_reopens_at_start() {
+        let mut k = Kiosk::new();
+        k.press(Corner::BottomRight);
+        k.add_first(1);
+        assert_eq!(k.set_out_of_service(true), ScreenId::OutOfService);
+        assert!(k.screens.transaction.cart.is_empty());
+        for &corner in Corner::ALL.iter() {
+            assert_eq!(k.press(corner), ScreenId::OutOfService);
+        }
+        assert_eq!(k.lines(0b100), ScreenId::OutOfService);
+        assert_eq!(k.wait(ATTRACT_IDLE_FRAMES), ScreenId::OutOfService);
+        assert_eq!(k.set_out_of_service(false), ScreenId::Start);
+        assert_eq!(k.set_out_of_service(false), ScreenId::Start);
+    }
+
+    #[test]
+    fn idling_on_the_start_screen_alone_starts_the_attract_loop() {
+        let mut k = Kiosk::new();
+        assert_eq!(k.wait(ATTRACT_IDLE_FRAMES - 1), ScreenId::Start);
+        assert_eq!(k.wait(2), ScreenId::Attract);
+        assert_eq!(k.press(Corner::TopLeft), ScreenId::Start);
+
+        // Not in the middle of buying tickets.
+        k.press(Corner::BottomRight);
+        assert_eq!(k.wait(2 * ATTRACT_IDLE_FRAMES), ScreenId::Line(0));
+    }
+
+    #[test]
+    fn line_pages_step_both_ways_and_go_round() {
+        let mut k = Kiosk::new();
+        // The controller's NEXT, pressed and let go of.
+        assert_eq!(k.lines(LINES_TICKETS), ScreenId::Line(0));
+        for line in 1..LINES {
+            assert_eq!(k.lines(LINES_NEXT), ScreenId::Line(line));
+            assert_eq!(k.lines(LINES_TICKETS), ScreenId::Line(line));
+        }
+        assert_eq!(k.lines(LINES_NEXT), ScreenId::Line(0));
+
+        // PREV back from the last line, and QUIT at either end.
+        k.go(ScreenId::Line(LINES - 1));
+        for line in (0..LINES - 1).rev() {
+            assert_eq!(k.press(Corner::BottomLeft), ScreenId::Line(line));
+        }
+        assert_eq!(k.press(Corner::BottomLeft), ScreenId::Start);
+        k.go(ScreenId::Line(LINES - 1));
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Start);
+    }
+
+    #[test]
+    fn no_after_cancelling_the_way_of_paying_leaves_the_confirmation() {
+        let mut k = Kiosk::new();
+        k.press(Corner::BottomRight);
+        k.add_first(1);
+        k.page_to_cart();
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Confirm);
+        for _ in 0..2 {
+            assert_eq!(k.press(Corner::BottomLeft), ScreenId::Method);
+            assert_eq!(k.press(Corner::BottomLeft), ScreenId::Confirm);
+        }
+        assert_eq!(k.press(Corner::BottomRight), ScreenId::Cart);
+        assert_eq!(k.screens.cart.cart().len(), 1);
+    }
+
+    #[test]
+    fn input_waits_for_the_screen_entered_to_tick() {
+        let mut k = Kiosk::new();
+        k.settle();
+        // Into a line's tickets and, in the same frame and the next, a
+        // ticket chosen and the controller's NEXT.
+        let ticket = InputEvent::Key(Corner::TopLeft);
+        let next = InputEvent::Lines(LINES_NEXT);
+        let evs = [InputEvent::Lines(LINES_TICKETS), ticket];
+        assert_eq!(k.frame(&evs), ScreenId::Line(0));
+        assert_eq!(k.frame(&[ticket, next]), ScreenId::Line(0));
+        assert_eq!(k.frame(&[ticket]), ScreenId::Quantity(0));
+
+        // Likewise for a screen entered on a tick.
+        k.go(ScreenId::Start);
+        while k.frame(&[]) != ScreenId::Attract {}
+        assert_eq!(k.frame(&[ticket]), ScreenId::Attract);
+        assert_eq!(k.frame(&[ticket]), ScreenId::Start);
+    }
+
+    #[test]
+    fn anything_wakes_the_attract_loop_to_a_fresh_start() {
+        let events = [
+            InputEvent::Key(Corner::BottomRight),
+            InputEvent::Lines(LINES_TICKETS),
+            InputEvent::Lines(0b010),
+        ];
+        let mut k = Kiosk::new();
+        for &ev in events.iter() {
+            assert_eq!(k.wait(ATTRACT_IDLE_FRAMES + 1), ScreenId::Attract);
+            k.settle();
+            assert_eq!(k.frame(&[ev]), ScreenId::Start, "{:?}", ev);
+            assert_eq!(k.wait(ATTRACT_IDLE_FRAMES - 1), ScreenId::Start);
+        }
+    }
+
+    /// A payment stuck part of the way through, at its count out of 100,
+    /// as when the passenger walks off.
+    struct Stalled(u32);
+
+    impl PaymentProgress for Stalled {
+        fn begin(&mut self) {}
+        fn poll(&mut self) {}
+        fn cancel(&mut self) {}
+
+        fn done(&self) -> u32 {
+            self.0
+        }
+
+        fn total(&self) -> u32 {
+            100
+        }
+    }
+
+    /// Returns where a cash payment of 500p stuck at `done` goes, and after
+    /// how many frames.
+    fn run_out(done: u32) -> (Transition, u32) {
+        let mut buf = [AChar::from_ascii_char(b' '); COLS * ROWS];
+        let mut c = Cursor::new(&mut buf, COLS);
+        let mut area = screen_area(&mut c);
+        let mut paying = PayingScreen::with_payment(Stalled(done));
+        paying.method = PayMethod::Cash;
+        paying.due = 500;
+        paying.restart();
+        paying.on_enter(&mut area);
+        for frame in 0..2 * PAY_SECONDS * FPS {
+            if let Some(transition) = paying.on_tick(&mut area, frame) {
+                return (transition, frame);
+            }
+        }
+        panic!("still paying");
+    }
+
+    #[test]
+    fn running_out_of_time_refunds_or_starts_over() {
+        let (transition, frame) = run_out(40);
+        assert_eq!(transition, Transition::To(ScreenId::Refunding));
+        assert_eq!(frame, PAY_SECONDS * FPS - 1);
+        let (transition, _) = run_out(0);
+        assert_eq!(transition, Transition::To(ScreenId::Start));
+    }
+
+    /// Returns what the screen in `buf` says, a row at a time.
+    fn text(buf: &[AChar]) -> String {
+        buf.chunks(COLS)
+            .map(|row| row.iter().map(|a| a.ascii_char() as char).collect())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn keys_and_answers_are_in_the_language_chosen() {
+        let mut k = Kiosk::new();
+        k.press(Corner::BottomRight);
+        k.add_first(1);
+        set_language(Language::French);
+        k.go(ScreenId::Line(LINES - 1));
+        let line = text(&k.buf);
+        k.go(ScreenId::Confirm);
+        let confirm = text(&k.buf);
+        set_language(Language::English);
+
+        assert!(line.contains("PAN."), "{}", line);
+        assert!(line.contains("Page 3/3"), "{}", line);
+        assert!(!line.contains("NEXT") && !line.contains("CART"));
+        assert!(confirm.contains("OUI") && confirm.contains("NON"));
+        assert!(!confirm.contains("YES"));
+        assert!(confirm.contains("Billet"), "{}", confirm);
+    }
+}
@@ -0,0 +1,431 @@
+//! What the kiosk says to passengers, in each of the languages it speaks.
+//!
+//! Screens don't write out their text. They name it with a `StringId` and
+//! look it up with `tr`, which gives it in the language the passenger chose.
+//! Each language has a table with every string in the order of `StringId`,
+//! in CP437 as the screen shows it, so accented letters are written as
+//! escapes. A message over several lines is a run of strings, one a line,
+//! looked up with `tr_run`; a translation has to fit in as many lines, and
+//! leave a line blank if it needs fewer.
+//!
+//! Soft key labels are here too, and have to fit the four cells inside a
+//! key.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Languages the kiosk speaks.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Language {
+    English,
+    French,
+}
+
+/// Names of the languages in the order of `Language::ALL`, each in its own
+/// language, for choosing between them.
+pub static LANGUAGE_NAMES: [&[u8]; 2] = [b"English", b"Fran\x87ais"];
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::French];
+
+    fn table(self) -> &'static [&'static [u8]; STRINGS] {
+        match self {
+            Language::English => &ENGLISH,
+            Language::French => &FRENCH,
+        }
+    }
+}
+
+/// The language passengers are spoken to in, as an index in
+/// `Language::ALL`. Screens drawn after it changes use the new language.
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+pub fn language() -> Language {
+    let i = LANGUAGE.load(Ordering::Relaxed) as usize;
+    Language::ALL.get(i).cloned().unwrap_or(Language::English)
+}
+
+pub fn set_language(language: Language) {
+    LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// Names of the strings, in the order of each language's table.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum StringId {
+    // Start screen
+    Welcome,
+    StartPrompt1,
+    StartPrompt2,
+    StartPrompt3,
+    NetworkMap,
+    Departures,
+    Language,
+    // Departures screen
+    LinesOneToThree,
+    LineHeading,
+    DestinationHeading,
+    LeavesInHeading,
+    NoDepartures,
+    Due,
+    // Network map and ticket screens, by line
+    ChooseLine,
+    Line1,
+    Line2,
+    Line3,
+    ChooseTicket1,
+    ChooseTicket2,
+    ChooseTicket3,
+    InYourCart,
+    // Quantity screen
+    Quantity,
+    HowMany,
+    HowManyAfter,
+    Each,
+    Total,
+    Cancel,
+    AddToCart,
+    CartFull,
+    // Cart screen
+    YourCart,
+    MoreTickets,
+    Checkout,
+    CartEmpty,
+    Continue,
+    ItemHeading,
+    QtyHeading,
+    EachHeading,
+    // Payment method screen, with the methods in the order of `METHODS`
+    PaymentMethod,
+    HowToPay,
+    Pay,
+    Cash,
+    Card,
+    Contactless,
+    // Payment screen
+    Payment,
+    Please,
+    CashRules,
+    InsertCoins,
+    CardRules,
+    InsertCard,
+    ContactlessRules,
+    TapCard,
+    Processing,
+    CancelQuestion,
+    TotalToPay,
+    PayWithin,
+    PaymentReceived,
+    // Refund screen
+    Cancelled,
+    Cancelled1,
+    Cancelled2,
+    Refunding,
+    NoPayment,
+    // Out-of-service and error screens
+    OutOfService,
+    Sorry,
+    Apology1,
+    Apology2,
+    Apology3,
+    Apology4,
+    Apology5,
+    Reason,
+    Maintenance,
+    Error,
+    ErrorLabel,
+    // Thank-you screen
+    ThankYou,
+    ThankYouBanner,
+    Thanks1,
+    Thanks2,
+    Thanks3,
+    TicketIssued,
+    YourReference,
+    // Language screen
+    ChooseLanguage,
+    // Attract loop
+    PromoTitle,
+    Promo1,
+    Promo2,
+    Promo3,
+    Invite,
+    // Soft key labels, and the word before the page numbers
+    KeyYes,
+    KeyNo,
+    KeyOk,
+    KeyBack,
+    KeyUp,
+    KeyDown,
+    KeyNext,
+    KeyPrev,
+    KeyQuit,
+    KeyCart,
+    KeyDel,
+    KeyAdd,
+    KeyPay,
+    KeyMap,
+    KeyTime,
+    KeyLang,
+    Page,
+    // Announcements
+    News,
+}
+
+/// Number of strings in each table.
+const STRINGS: usize = StringId::News as usize + 1;
+
+static ENGLISH: [&[u8]; STRINGS] = [
+    // Start screen
+    b"Welcome",
+    b"Press any",
+    b"button",
+    b"to start!",
+    b"Network map",
+    b"Departures",
+    b"Language",
+    // Departures screen
+    b"Lines 1 to 3",
+    b"Line",
+    b"Destination",
+    b"Leaves in",
+    b"No departures coming up",
+    b"Due",
+    // Network map and ticket screens, by line
+    b"Choose a line for its tickets",
+    b"Line 1",
+    b"Line 2",
+    b"Line 3",
+    b"Choose a",
+    b"ticket to",
+    b"purchase",
+    b"In your cart:",
+    // Quantity screen
+    b"Quantity",
+    b"How many ",
+    b" tickets?",
+    b"Each",
+    b"Total",
+    b"CANCEL",
+    b"ADD TO CART",
+    b"Your cart is full",
+    // Cart screen
+    b"Your Cart",
+    b"MORE TICKETS",
+    b"CHECKOUT",
+    b"Your cart is empty",
+    b"Do you want to continue?",
+    b"Item",
+    b"Qty",
+    b"Each",
+    // Payment method screen, with the methods in the order of `METHODS`
+    b"Payment Method",
+    b"How would you like to pay?",
+    b"PAY",
+    b"Cash",
+    b"Card",
+    b"Contactless",
+    // Payment screen
+    b"Payment",
+    b"Please",
+    b"Coins only, no change given",
+    b"insert coins.",
+    b"Chip and PIN cards accepted",
+    b"insert your card.",
+    b"Card readers accepted: contactless only",
+    b"tap your card.",
+    b"Processing...",
+    b"Cancel transaction? Inserted amount will be refunded.",
+    b"Total to pay",
+    b"Complete payment within",
+    b"Payment received",
+    // Refund screen
+    b"Cancelled",
+    b"Transaction",
+    b"cancelled",
+    b"Refunding",
+    b"No payment was taken",
+    // Out-of-service and error screens
+    b"Out of Service",
+    b"SORRY",
+    b"We're sorry, this kiosk",
+    b"is out of service.",
+    b"",
+    b"Please use another kiosk",
+    b"or buy your ticket on board.",
+    b"Reason: ",
+    b"Maintenance in progress",
+    b"ERROR",
+    b"Out of service",
+    // Thank-you screen
+    b"Thank You",
+    b"THANK YOU",
+    b"Thanks for",
+    b"travelling",
+    b"with us!",
+    b"Ticket issued",
+    b"Your reference",
+    // Language screen
+    b"Choose your language",
+    // Attract loop
+    b"Day Tickets",
+    b"Unlimited travel",
+    b"on every line",
+    b"until midnight",
+    b"Press any button to buy tickets, see the departures or find your way \
+        on the network map.",
+    // Soft key labels, and the word before the page numbers
+    b"YES",
+    b"NO",
+    b"OK",
+    b"BACK",
+    b"UP",
+    b"DOWN",
+    b"NEXT",
+    b"PREV",
+    b"QUIT",
+    b"CART",
+    b"DEL",
+    b"ADD",
+    b"PAY",
+    b"MAP",
+    b"TIME",
+    b"LANG",
+    b"Page",
+    // Announcements
+    b"Service notice: trains on Line 2 are running every 15 minutes this \
+        weekend due to track work between Central and Riverside. Please \
+        allow extra time for your journey.",
+];
+
+static FRENCH: [&[u8]; STRINGS] = [
+    // Start screen
+    b"Bienvenue",
+    b"Appuyez sur",
+    b"une touche",
+    b"pour commencer",
+    b"Plan du r\x82seau",
+    b"D\x82parts",
+    b"Langue",
+    // Departures screen
+    b"Lignes 1 \x85 3",
+    b"N\xF8",
+    b"Destination",
+    b"D\x82part dans",
+    b"Aucun d\x82part pr\x82vu",
+    b"\x85 quai",
+    // Network map and ticket screens, by line
+    b"Choisissez une ligne pour ses billets",
+    b"Ligne 1",
+    b"Ligne 2",
+    b"Ligne 3",
+    b"Choisissez",
+    b"votre billet",
+    b"\x85 acheter",
+    b"Dans votre panier :",
+    // Quantity screen
+    b"Quantit\x82",
+    b"Combien de billets ",
+    b" ?",
+    b"Prix unitaire",
+    b"Total",
+    b"ANNULER",
+    b"AJOUTER AU PANIER",
+    b"Votre panier est plein",
+    // Cart screen
+    b"Votre panier",
+    b"AUTRES BILLETS",
+    b"PAYER",
+    b"Votre panier est vide",
+    b"Voulez-vous continuer ?",
+    b"Billet",
+    b"Qt\x82",
+    b"Prix",
+    // Payment method screen, with the methods in the order of `METHODS`
+    b"Moyen de paiement",
+    b"Comment souhaitez-vous payer ?",
+    b"PAYER",
+    b"Esp\x8Aces",
+    b"Carte",
+    b"Sans contact",
+    // Payment screen
+    b"Paiement",
+    b"Veuillez",
+    b"Pi\x8Aces uniquement, pas de monnaie rendue",
+    b"ins\x82rer des pi\x8Aces.",
+    b"Cartes \x85 puce et code accept\x82es",
+    b"ins\x82rer votre carte.",
+    b"Lecteurs de carte : sans contact uniquement",
+    b"approcher votre carte.",
+    b"Traitement...",
+    b"Annuler la transaction ? Le montant ins\x82r\x82 sera rembours\x82.",
+    b"Total \x85 payer",
+    b"Payez dans un d\x82lai de",
+    b"Paiement re\x87u",
+    // Refund screen
+    b"Annul\x82",
+    b"Transaction",
+    b"annul\x82e",
+    b"Remboursement",
+    b"Aucun paiement n'a \x82t\x82 pris",
+    // Out-of-service and error screens
+    b"Hors service",
+    b"DESOLE",
+    b"Nous sommes d\x82sol\x82s, cette",
+    b"borne est hors service.",
+    b"",
+    b"Utilisez une autre borne ou",
+    b"achetez votre billet \x85 bord.",
+    b"Raison : ",
+    b"Maintenance en cours",
+    b"ERREUR",
+    b"Hors service",
+    // Thank-you screen
+    b"Merci",
+    b"MERCI",
+    b"Merci d'avoir",
+    b"voyag\x82",
+    b"avec nous !",
+    b"Billet \x82mis",
+    b"Votre r\x82f\x82rence",
+    // Language screen
+    b"Choisissez votre langue",
+    // Attract loop
+    b"Billets Journ\x82e",
+    b"Voyagez sans limite",
+    b"sur toutes les lignes",
+    b"jusqu'\x85 minuit",
+    b"Appuyez sur une touche pour acheter des billets, voir les d\x82parts \
+        ou trouver votre chemin sur le plan du r\x82seau.",
+    // Soft key labels, and the word before the page numbers
+    b"OUI",
+    b"NON",
+    b"OK",
+    b"RET",
+    b"HAUT",
+    b"BAS",
+    b"SUIV",
+    b"PR\x90C",
+    b"FIN",
+    b"PAN.",
+    b"SUPP",
+    b"AJT",
+    b"PAYE",
+    b"PLAN",
+    b"HOR.",
+    b"LANG",
+    b"Page",
+    // Announcements
+    b"Info trafic : ce week-end, les trains de la ligne 2 passent toutes les \
+        15 minutes en raison de travaux entre Central et Riverside. \
+        Pr\x82voyez plus de temps pour votre trajet.",
+];
+
+/// Returns string `id` in the current language.
+pub fn tr(id: StringId) -> &'static [u8] {
+    language().table()[id as usize]
+}
+
+/// Returns the strings from `first` to `last`, both included, in the current
+/// language, such as the lines of a message or the items of a list.
+pub fn tr_run(first: StringId, last: StringId) -> &'static [&'static [u8]] {
+    &language().table()[first as usize..=last as usize]
+}
@@ -0,0 +1,135 @@
+//! The colors the kiosk is drawn in, chosen as a whole.
+//!
+//! Screens don't name colors. They ask `theme` for the one with the role
+//! they need, such as the background of a message box or the color of
+//! something gone wrong, so that a new look is a new `Theme` rather than an
+//! edit to every screen. Screens drawn after the theme changes use the new
+//! one; `Screens::redraw` draws the current screen again to catch up.
+//!
+//! A few colors stay out of it: each line's own color on the map, the
+//! departure boards, which are made to look like those on the platforms,
+//! and the black on white of the ticket's code, which scanners expect.
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use textui::overlay::{NO_STYLE, YES_STYLE};
+use textui::widget::ButtonStyle;
+use textui::BoxStyle;
+
+use crate::{BLACK, BLUE, DK_GRAY, RED, WHITE};
+
+/// Colors for each role on the screens.
+#[derive(Copy, Clone, Debug)]
+pub struct Theme {
+    /// The title bar across the top, and the line under the title.
+    pub title_fg: m4vga::Pixel,
+    pub title_bg: m4vga::Pixel,
+    pub subtitle_fg: m4vga::Pixel,
+    /// The body of a screen, inside its border, and the shading filling
+    /// the start screen.
+    pub body_fg: m4vga::Pixel,
+    pub body_bg: m4vga::Pixel,
+    pub shade_fg: m4vga::Pixel,
+    /// Boxes over the body: messages, questions and lists.
+    pub box_fg: m4vga::Pixel,
+    pub box_bg: m4vga::Pixel,
+    /// Picks out a word or an icon to catch the eye.
+    pub accent: m4vga::Pixel,
+    /// Something gone through, text on it, and a darker shade of it for
+    /// behind longer text.
+    pub success: m4vga::Pixel,
+    pub success_fg: m4vga::Pixel,
+    pub success_dim: m4vga::Pixel,
+    /// Something gone wrong, or about to, and text on it.
+    pub danger: m4vga::Pixel,
+    pub danger_fg: m4vga::Pixel,
+    /// The soft keys by the buttons, and those answering a question.
+    pub keys: ButtonStyle,
+    pub yes_keys: ButtonStyle,
+    pub no_keys: ButtonStyle,
+}
+
+/// White on blue and gray, as the kiosk has always looked.
+pub const STANDARD: Theme = Theme {
+    title_fg: WHITE,
+    title_bg: BLUE,
+    subtitle_fg: 0b10_10_10,
+    body_fg: WHITE,
+    body_bg: DK_GRAY,
+    shade_fg: BLACK,
+    box_fg: WHITE,
+    box_bg: BLUE,
+    accent: 0b00_11_11,  // Yellow
+    success: 0b00_11_00, // Green
+    success_fg: BLACK,
+    success_dim: 0b00_01_00, // Dark green
+    danger: RED,
+    danger_fg: WHITE,
+    keys: ButtonStyle {
+        normal: (WHITE, BLUE),
+        focused: (BLUE, WHITE),
+        pressed: (WHITE, RED),
+        frame: BoxStyle::SINGLE,
+    },
+    yes_keys: YES_STYLE,
+    no_keys: NO_STYLE,
+};
+
+/// Yellow and white on black, for passengers who find the standard theme
+/// hard to read.
+pub const HIGH_CONTRAST: Theme = Theme {
+    title_fg: BLACK,
+    title_bg: 0b00_11_11, // Yellow
+    subtitle_fg: BLACK,
+    body_fg: WHITE,
+    body_bg: BLACK,
+    shade_fg: DK_GRAY,
+    box_fg: 0b00_11_11,  // Yellow
+    box_bg: 0b01_00_00,  // Dark blue
+    accent: 0b11_11_00,  // Cyan
+    success: 0b00_11_00, // Green
+    success_fg: BLACK,
+    success_dim: 0b00_11_00,
+    danger: RED,
+    danger_fg: WHITE,
+    keys: ButtonStyle {
+        normal: (BLACK, 0b00_11_11),
+        focused: (0b00_11_11, BLACK),
+        pressed: (WHITE, RED),
+        frame: BoxStyle::DOUBLE,
+    },
+    yes_keys: ButtonStyle {
+        normal: (BLACK, 0b00_11_00), // Green
+        focused: (0b00_11_00, BLACK),
+        pressed: (WHITE, 0b00_10_00),
+        frame: BoxStyle::DOUBLE,
+    },
+    no_keys: ButtonStyle {
+        normal: (BLACK, 0b00_11_11), // Yellow
+        focused: (0b00_11_11, BLACK),
+        pressed: (WHITE, RED),
+        frame: BoxStyle::DOUBLE,
+    },
+};
+
+/// Themes the kiosk can be switched between.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ThemeId {
+    Standard,
+    HighContrast,
+}
+
+/// Every theme, in the order of `ThemeId`.
+static THEMES: [Theme; 2] = [STANDARD, HIGH_CONTRAST];
+
+/// The theme screens are drawn in, as an index in `THEMES`.
+static THEME: AtomicU8 = AtomicU8::new(0);
+
+pub fn theme() -> &'static Theme {
+    let i = THEME.load(Ordering::Relaxed) as usize;
+    THEMES.get(i).unwrap_or(&THEMES[0])
+}
+
+pub fn set_theme(id: ThemeId) {
+    THEME.store(id as u8, Ordering::Relaxed);
+}
@@ -0,0 +1,346 @@
+//! Attributed text display.
+//!
+//! This shows a mostly static screen of colored text, with some dynamic
+//! elements for fun.
+//!
+//! # Theory of operation
+//!
+//! We allocate a static buffer, `TEXT_BUF`, to hold attributed text. Our
+//! rasterizer expects that buffer to contain values of type `AChar`, for
+//! *attributed char*.
+//!
+//! Because we want to update the text from the application loop, but read it
+//! during scanout, we enclose the buffer in a `SpinLock`. Before updating the
+//! text in the application loop, we `sync_to_vblank` to ensure that we're not
+//! racing scanout.
+//!
+//! At startup, at the top of `main`, the demo fills the text buffer with text.
+//! It then activates the display driver, giving it a raster callback and a main
+//! loop.
+//!
+//! The raster callback uses `m4vga::rast::text_10x16` to draw the top 592 lines
+//! of the display, using the standard font, and then `m4vga::rast::solid_color`
+//! to draw the partial last line. During the rendering of the text part of the
+//! display, it locks `TEXT_BUF` on every horizontal retrace -- this is pretty
+//! cheap, and won't race the application loop because we only call the raster
+//! callback outside of the vertical blanking interval.
+//!
+//! The application loop calls `sync_to_vblank` every iteration and then writes
+//! an updated frame number into the `TEXT_BUF`. Note that we can use `write!`
+//! here despite `no_std`; we don't have to write our own numeric formatting
+//! code, which is great.
+
+#![no_std]
+#![no_main]
+
+#[cfg(feature = "panic-halt")]
+extern crate panic_halt;
+#[cfg(feature = "panic-itm")]
+extern crate panic_itm;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use stm32f4;
+use stm32f4::stm32f407::interrupt;
+use stm32f4::stm32f407 as device;
+
+use font_10x16;
+use m4vga::rast::text_10x16::{self, AChar};
+use m4vga::util::spin_lock::SpinLock;
+
+use textui::anim::Animations;
+use textui::chrome::{
+    draw_status_row, screen_area, status_value, Corner, STATUS_BG, STATUS_FG,
+};
+use textui::clock::{Clock, Elapsed, SecondsCounter, TimeSource};
+use textui::reference::References;
+use textui::widget::Marquee;
+use textui::{Cursor, Rect};
+
+use kiosk::schedule::{self, Schedule};
+use kiosk::screens::{ErrorScreen, InputEvent, Screen, ScreenId, Screens};
+use kiosk::strings::{tr, StringId};
+use kiosk::theme::{set_theme, theme, ThemeId};
+use kiosk::{BLACK, COLS, FPS, ROWS, WHITE};
+
+/// Area of the announcement scrolling along the bottom of every screen.
+const NEWS_BAR: Rect = Rect::new(34, 1, 1, COLS - 2);
+/// Area of the clock in the middle of the status row.
+const CLOCK: Rect = Rect::new(ROWS - 1, (COLS - 8) / 2, 1, 8);
+
+/// Set while the kiosk is out of service. The maintenance switch sets and
+/// clears it when moved, and anything else with a say, such as a handler for
+/// commands from the operator, may do so in between; the main loop shows the
+/// out-of-service screen for as long as it is set.
+static OUT_OF_SERVICE: AtomicBool = AtomicBool::new(false);
+
+static TEXT_BUF: SpinLock<[AChar; COLS * ROWS]> =
+    SpinLock::new([AChar::from_ascii_char(0); COLS * ROWS]);
+
+/// Demo entry point. Responsible for starting up the display driver and
+/// providing callbacks.
+#[allow(unused_parens)] // TODO bug in cortex_m_rt
+#[cortex_m_rt::entry]
+fn main() -> ! {
+    {
+        // Type some stuff into the buffer.
+        let mut c = TEXT_BUF.try_lock().unwrap();
+        let mut c = Cursor::new_with(&mut *c, COLS, WHITE, BLACK);
+        ErrorScreen.on_enter(&mut screen_area(&mut c));
+        draw_status_row(&mut c, b"", b"---");
+        draw_state_label(&mut c, ScreenId::Error);
+    }
+
+    let mut cp = cortex_m::peripheral::Peripherals::take().unwrap();
+    let p = device::Peripherals::take().unwrap();
+
+    // allow clock to access gpioc, and the random number generator, which
+    // runs once m4vga::init has started the 48 MHz clock
+    p.RCC.ahb1enr.modify(|_, w| {w.gpiocen().enabled()});
+    p.RCC.ahb2enr.modify(|_, w| {w.rngen().enabled()});
+    // turn on gpioc input for pins 0 to 3 for the buttons by the screen
+    // corners, in the order of their `Corner` bits, 7 8 9, 10 for the coin
+    // acceptor's fault line, 11 for the maintenance switch, and 12 for the
+    // high-contrast switch
+    p.GPIOC.moder.modify(|_, w| {
+        w.moder0().input().moder1().input();
+        w.moder2().input().moder3().input();
+        w.moder7().input().moder8().input().moder9().input();
+        w.moder10().input().moder11().input().moder12().input()
+    });
+    // simplify input as idr
+    let input = &p.GPIOC.idr;
+    // taken out of p before m4vga::init takes its share, for the main loop
+    let rng = p.RNG;
+
+    let mut s0: u8 = 0;
+    let mut buttons0: u8 = 0;
+    let mut maintenance0 = false;
+    let mut high_contrast0 = false;
+
+    // Give the driver its hardware resources...
+    m4vga::init(
+        cp.NVIC,
+        &mut cp.SCB,
+        p.FLASH,
+        &p.DBG,
+        p.RCC,
+        p.GPIOB,
+        p.GPIOE,
+        p.TIM1,
+        p.TIM3,
+        p.TIM4,
+        p.DMA2,
+        )
+        // ...select a display timing...
+        .configure_timing(&m4vga::timing::SVGA_800_600)
+        // ... and provide a raster callback.
+        .with_raster(
+            // The raster callback is invoked on every horizontal retrace to
+            // provide new pixels.
+            |ln, tgt, ctx, _| {
+                if ln < 592 {
+                    text_10x16::unpack(
+                        &*TEXT_BUF.try_lock().expect("rast buf access"),
+                        font_10x16::FONT.as_glyph_slices(),
+                        &mut **tgt,
+                        ln,
+                        COLS,
+                    );
+                    ctx.target_range = 0..COLS * text_10x16::GLYPH_COLS;
+                } else {
+                    // There's a partial 38th line visible on the display.
+                    // Trying to display it will panic by going out of range on
+                    // the 80x37 buffer. Instead, we'll just black it out:
+                    m4vga::rast::solid_color_fill(tgt, ctx, 800, 0);
+                    // Save some CPU while we're at it by not invoking the
+                    // callback again this frame.
+                    ctx.repeat_lines = 600 - 592;
+                }
+            },
+            // This closure contains the main loop of the program.
+            |vga| {
+                // Enable outputs. The driver doesn't do this for you in case
+                // you want to set up some graphics before doing so.
+
+                vga.video_on();
+                let mut frame_no: u32 = 0;
+                let news_text = tr(StringId::News);
+                let mut news = Marquee::new(news_text, NEWS_BAR, 8, 6, 120);
+                // Time since power-on until there's a real time clock to ask.
+                let mut time = SecondsCounter::new(0, FPS);
+                let mut clock = Clock::new(CLOCK);
+                clock.fg = STATUS_FG;
+                clock.bg = STATUS_BG;
+                // How long the kiosk has been on its current screen.
+                let mut elapsed =
+                    Elapsed::new(state_time(ScreenId::Error), FPS);
+                elapsed.fg = STATUS_FG;
+                elapsed.bg = STATUS_BG;
+                let mut screens = Screens::new();
+                screens.references = References::new(boot_nonce(&rng));
+                // Until there is a feed to fill it, the next departures come
+                // from the demo timetable.
+                let mut schedule = Schedule::new();
+                schedule::fill(&mut schedule, 0);
+                screens.set_schedule(&schedule);
+                // Spin forever!
+                loop {
+                    vga.sync_to_vblank();
+                    let mut buf = TEXT_BUF.try_lock().expect("app buf access");
+                    let mut c =
+                        Cursor::new_with(&mut *buf, COLS, WHITE, BLACK);
+                    let s = ((input.read().idr7().bit() as u8) << 0) + ((input.read().idr8().bit() as u8) << 1) + ((input.read().idr9().bit() as u8) << 2);
+
+                    let maintenance = input.read().idr11().bit();
+                    if maintenance != maintenance0 {
+                        maintenance0 = maintenance;
+                        OUT_OF_SERVICE.store(maintenance, Ordering::Relaxed);
+                        screens.out_of_service.reason = if maintenance {
+                            Some(StringId::Maintenance)
+                        } else {
+                            None
+                        };
+                    }
+
+                    // A new theme takes over the screen showing, as well as
+                    // those to come.
+                    let high_contrast = input.read().idr12().bit();
+                    let mut redrawn = false;
+                    if high_contrast != high_contrast0 {
+                        high_contrast0 = high_contrast;
+                        set_theme(if high_contrast {
+                            ThemeId::HighContrast
+                        } else {
+                            ThemeId::Standard
+                        });
+                        screens.redraw(&mut screen_area(&mut c));
+                        redrawn = true;
+                    }
+
+                    // Screens can't reach the status row through this.
+                    let mut entered = screens.set_out_of_service(
+                        &mut screen_area(&mut c),
+                        OUT_OF_SERVICE.load(Ordering::Relaxed),
+                    );
+                    // The controller holds the lines at what it asks for, so a
+                    // change of them can wait for the screen to settle.
+                    if s0 != s && !screens.settling() {
+                        entered |= screens.handle(
+                            &mut screen_area(&mut c),
+                            InputEvent::Lines(s),
+                        );
+                        s0 = s;
+                    }
+                    // A button counts once, as it goes down, however long
+                    // it is held.
+                    let buttons = (input.read().bits() & 0b1111) as u8;
+                    if let Some(corner) = Corner::from_bits(buttons & !buttons0)
+                    {
+                        entered |= screens.handle(
+                            &mut screen_area(&mut c),
+                            InputEvent::Key(corner),
+                        );
+                    }
+                    buttons0 = buttons;
+                    entered |=
+                        screens.tick(&mut screen_area(&mut c), frame_no);
+                    if entered {
+                        draw_state_label(&mut c, screens.current());
+                        elapsed.rect = state_time(screens.current());
+                        elapsed.reset(frame_no);
+                    }
+                    // No cash while the coin acceptor is out of order.
+                    screens.method.set_coin_fault(input.read().idr10().bit());
+
+                    // The announcement runs along the bottom of every screen,
+                    // starting over when a screen in another language is.
+                    if entered && news.text != tr(StringId::News) {
+                        news.text = tr(StringId::News);
+                        news.offset = 0;
+                    }
+                    news.fg = theme().body_fg;
+                    news.bg = theme().body_bg;
+                    let mut anims = Animations::new();
+                    anims.push(&mut news);
+                    if entered || redrawn {
+                        anims.invalidate();
+                    }
+                    anims.tick_all(frame_no);
+                    anims.draw_all(&mut c);
+
+                    frame_no = frame_no.wrapping_add(1);
+                    if time.tick() && time.seconds() % 60 == 0 {
+                        schedule.count_down();
+                        let minute = (time.seconds() / 60) as u32;
+                        schedule::top_up(&mut schedule, minute);
+                        screens.set_schedule(&schedule);
+                    }
+                    clock.update(&mut c, &time);
+                    elapsed.update(&mut c, frame_no);
+
+                    use core::fmt::Write;
+                    // Truncated rather than unwrapped: a value too long for
+                    // the status row mustn't take the kiosk down.
+                    write!(
+                        status_value(&mut c).truncating(usize::MAX),
+                        "{:03b} #{}",
+                        s,
+                        frame_no
+                    )
+                    .ok();
+                }
+            },
+        )
+}
+
+/// Wires up the PendSV handler expected by the driver.
+#[cortex_m_rt::exception]
+#[link_section = ".ramcode"]
+fn PendSV() {
+    m4vga::pendsv_raster_isr()
+}
+
+/// Wires up the TIM3 handler expected by the driver.
+#[interrupt]
+#[link_section = ".ramcode"]
+fn TIM3() {
+    m4vga::tim3_shock_isr()
+}
+
+/// Wires up the TIM4 handler expected by the driver.
+#[interrupt]
+#[link_section = ".ramcode"]
+fn TIM4() {
+    m4vga::tim4_horiz_isr()
+}
+
+/// Returns a number from the hardware random number generator, different
+/// each boot, to keep references from repeating from one session to the
+/// next.
+fn boot_nonce(rng: &device::RNG) -> u32 {
+    rng.cr.modify(|_, w| w.rngen().set_bit());
+    while rng.sr.read().drdy().bit_is_clear() {}
+    rng.dr.read().bits()
+}
+
+const STATE_PREFIX: &[u8] = b"in state ";
+const STATE_SUFFIX: &[u8] = b" for ";
+
+/// Writes `in state <name> for` at the left of the status row, leaving the
+/// time spent on screen `id` to `state_time`.
+fn draw_state_label(c: &mut Cursor, id: ScreenId) {
+    let mut c = c.styled(STATUS_FG, STATUS_BG);
+    c.fill_rect(ROWS - 1, 1, 1, CLOCK.col - 1, b' ');
+    c.goto(ROWS - 1, 1);
+    c.puts(STATE_PREFIX);
+    c.puts(id.name());
+    c.puts(STATE_SUFFIX);
+}
+
+/// Area of the status row for the time spent on screen `id`, after the label
+/// drawn by `draw_state_label`. Long enough for `H:MM:SS`.
+fn state_time(id: ScreenId) -> Rect {
+    let col = 1 + STATE_PREFIX.len() + id.name().len() + STATE_SUFFIX.len();
+    Rect::new(ROWS - 1, col, 1, 7)
+}
@@ -0,0 +1,208 @@
+//! Widgets that change from frame to frame, and a registry to drive them.
+//!
+//! Each animated widget keeps no time of its own: it is ticked once a frame
+//! by the main loop and redrawn when the tick says it changed. Rather than
+//! the main loop remembering which widgets the current screen has, the
+//! screen's widgets are put in an `Animations`, and the loop makes one call
+//! to tick them all and one to draw those that need it.
+
+use crate::clock::Countdown;
+use crate::confetti::Confetti;
+use crate::cursor::Cursor;
+use crate::widget::{Blink, Marquee, Spinner};
+
+/// Something drawn differently as frames go by.
+pub trait Animate {
+    /// Moves on to frame `frame` of the main loop, returning whether that
+    /// changed how it looks and so needs a `draw`.
+    fn tick(&mut self, frame: u32) -> bool;
+
+    /// Draws it where it belongs.
+    fn draw(&self, c: &mut Cursor);
+}
+
+impl<'t> Animate for Spinner<'t> {
+    fn tick(&mut self, frame: u32) -> bool {
+        Spinner::tick(self, frame)
+    }
+
+    fn draw(&self, c: &mut Cursor) {
+        self.redraw(c)
+    }
+}
+
+/// A marquee steps once per tick, whatever the frame number.
+impl<'t> Animate for Marquee<'t> {
+    fn tick(&mut self, _frame: u32) -> bool {
+        Marquee::tick(self)
+    }
+
+    fn draw(&self, c: &mut Cursor) {
+        self.redraw(c)
+    }
+}
+
+impl<'w> Animate for Blink<'w> {
+    fn tick(&mut self, frame: u32) -> bool {
+        Blink::tick(self, frame)
+    }
+
+    fn draw(&self, c: &mut Cursor) {
+        self.redraw(c)
+    }
+}
+
+/// A countdown counts down one frame per tick, whatever the frame number.
+impl Animate for Countdown {
+    fn tick(&mut self, _frame: u32) -> bool {
+        Countdown::tick(self)
+    }
+
+    fn draw(&self, c: &mut Cursor) {
+        self.redraw(c)
+    }
+}
+
+impl<'t> Animate for Confetti<'t> {
+    fn tick(&mut self, frame: u32) -> bool {
+        Confetti::tick(self, frame)
+    }
+
+    fn draw(&self, c: &mut Cursor) {
+        self.redraw(c)
+    }
+}
+
+/// Most animations an `Animations` can hold.
+pub const ANIM_CAP: usize = 8;
+
+/// The animated widgets on the current screen.
+///
+/// Widgets are held by reference, so the registry is usually made afresh
+/// each frame, with the widgets the screen showing has; nothing from the
+/// last screen can linger in it. Call `invalidate` on the frame a screen is
+/// drawn, so that every animation is drawn over it whether or not it moved.
+pub struct Animations<'a> {
+    anims: [Option<&'a mut dyn Animate>; ANIM_CAP],
+    len: usize,
+    /// Bit `i` set if animation `i` needs drawing.
+    dirty: u32,
+}
+
+impl<'a> Animations<'a> {
+    pub fn new() -> Self {
+        Animations {
+            anims: Default::default(),
+            len: 0,
+            dirty: 0,
+        }
+    }
+
+    /// Adds `anim` after the others. Returns false, and leaves it out, if
+    /// there are already `ANIM_CAP` of them.
+    pub fn push(&mut self, anim: &'a mut dyn Animate) -> bool {
+        if self.len == ANIM_CAP {
+            return false;
+        }
+        self.anims[self.len] = Some(anim);
+        self.len += 1;
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Takes all the animations out.
+    pub fn clear(&mut self) {
+        for anim in self.anims.iter_mut() {
+            *anim = None;
+        }
+        self.len = 0;
+        self.dirty = 0;
+    }
+
+    /// Marks every animation as needing drawing, such as after the screen
+    /// under them has been drawn.
+    pub fn invalidate(&mut self) {
+        self.dirty = !0;
+    }
+
+    /// Ticks every animation on to frame `frame`, noting those that changed.
+    pub fn tick_all(&mut self, frame: u32) {
+        for (i, anim) in self.anims.iter_mut().enumerate() {
+            if let Some(anim) = anim {
+                if anim.tick(frame) {
+                    self.dirty |= 1 << i;
+                }
+            }
+        }
+    }
+
+    /// Draws the animations that changed since they were last drawn.
+    pub fn draw_all(&mut self, c: &mut Cursor) {
+        for (i, anim) in self.anims.iter().enumerate() {
+            if let Some(anim) = anim {
+                if self.dirty & 1 << i != 0 {
+                    anim.draw(c);
+                }
+            }
+        }
+        self.dirty = 0;
+    }
+}
+
+impl<'a> Default for Animations<'a> {
+    fn default() -> Self {
+        Animations::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::Rect;
+    use crate::widget::SPINNER_ASCII;
+    use m4vga::rast::text_10x16::AChar;
+
+    #[test]
+    fn draws_only_what_changed() {
+        const COLS: usize = 4;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let mut fast =
+            Spinner::new(Rect::new(0, 0, 1, 1), &SPINNER_ASCII, 1, b"");
+        let mut slow =
+            Spinner::new(Rect::new(0, 2, 1, 1), &SPINNER_ASCII, 4, b"");
+        {
+            let mut anims = Animations::new();
+            assert!(anims.push(&mut fast));
+            assert!(anims.push(&mut slow));
+            anims.invalidate();
+            anims.tick_all(0);
+            anims.draw_all(&mut Cursor::new(&mut buf, COLS));
+        }
+        let row = |buf: &[AChar]| -> String {
+            buf.iter().map(|a| a.char()).collect()
+        };
+        assert_eq!(row(&buf), "|.|.");
+
+        // A frame later, only the fast spinner has moved.
+        buf[2] = AChar::from_ascii_char(b'x');
+        let mut anims = Animations::new();
+        anims.push(&mut fast);
+        anims.push(&mut slow);
+        anims.tick_all(1);
+        anims.draw_all(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(row(&buf), "/.x.");
+
+        anims.clear();
+        assert!(anims.is_empty());
+        anims.tick_all(2);
+        anims.draw_all(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(row(&buf), "/.x.");
+    }
+}
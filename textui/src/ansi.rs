@@ -0,0 +1,375 @@
+//! Interpretation of ANSI/VT100 escape sequences.
+//!
+//! This lets text written for a serial terminal be displayed through a
+//! `Cursor`. Only the commonly used subset of CSI sequences is understood:
+//!
+//! - `ESC[row;colH` and `ESC[row;colf`: cursor positioning (1-based).
+//! - `ESC[nA`, `B`, `C`, `D`: relative cursor movement.
+//! - `ESC[...m`: colors (SGR) 30-37, 40-47, their bright variants 90-97 and
+//!   100-107, the defaults 39 and 49, and reset 0.
+//! - `ESC[nK`: erase in line.
+//! - `ESC[nJ`: erase in display.
+//!
+//! Anything else is parsed far enough to find its end and then ignored.
+
+use core::fmt;
+
+use crate::cp437;
+use crate::cursor::Cursor;
+
+const ESC: u8 = 0x1B;
+
+/// Maximum number of numeric parameters kept per sequence. Extra parameters
+/// are ignored.
+const MAX_PARAMS: usize = 8;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum State {
+    /// Passing characters through.
+    Ground,
+    /// Seen `ESC`.
+    Escape,
+    /// Inside a `ESC[` control sequence.
+    Csi,
+}
+
+/// Wraps a `Cursor`, interpreting escape sequences in the text written through
+/// it with `write!` or `putc`.
+///
+/// The parser keeps its state between calls, so sequences may be split across
+/// several writes, as `write!` is prone to do.
+pub struct AnsiWriter<'c, 'a> {
+    cursor: &'c mut Cursor<'a>,
+    state: State,
+    params: [u16; MAX_PARAMS],
+    param_count: usize,
+    /// Set if the sequence has a private-mode marker (e.g. `ESC[?25l`); such
+    /// sequences are ignored.
+    private: bool,
+    /// Colors to return to on SGR reset.
+    default_fg: m4vga::Pixel,
+    default_bg: m4vga::Pixel,
+}
+
+impl<'c, 'a> AnsiWriter<'c, 'a> {
+    /// Wraps `cursor`. The cursor's current colors become the defaults that
+    /// SGR 0, 39, and 49 return to.
+    pub fn new(cursor: &'c mut Cursor<'a>) -> Self {
+        let (default_fg, default_bg) = (cursor.fg, cursor.bg);
+        AnsiWriter {
+            cursor,
+            state: State::Ground,
+            params: [0; MAX_PARAMS],
+            param_count: 0,
+            private: false,
+            default_fg,
+            default_bg,
+        }
+    }
+
+    /// Feeds a single byte through the parser.
+    pub fn putc(&mut self, c: u8) -> fmt::Result {
+        match self.state {
+            State::Ground => {
+                if c == ESC {
+                    self.state = State::Escape;
+                } else {
+                    self.cursor.try_putc(c).map_err(|_| fmt::Error)?;
+                }
+            }
+            State::Escape => {
+                if c == b'[' {
+                    self.params = [0; MAX_PARAMS];
+                    self.param_count = 0;
+                    self.private = false;
+                    self.state = State::Csi;
+                } else {
+                    // Not a sequence we understand; drop it.
+                    self.state = State::Ground;
+                }
+            }
+            State::Csi => match c {
+                b'0'..=b'9' => {
+                    if self.param_count == 0 {
+                        self.param_count = 1;
+                    }
+                    if let Some(p) = self.params.get_mut(self.param_count - 1) {
+                        *p = p
+                            .saturating_mul(10)
+                            .saturating_add(u16::from(c - b'0'));
+                    }
+                }
+                b';' => {
+                    // An empty leading parameter still counts.
+                    self.param_count = usize::max(self.param_count, 1) + 1;
+                }
+                0x3C..=0x3F => self.private = true,
+                0x40..=0x7E => {
+                    self.state = State::Ground;
+                    if !self.private {
+                        self.dispatch(c);
+                    }
+                }
+                ESC => self.state = State::Escape,
+                // Intermediate bytes and stray controls are ignored.
+                _ => (),
+            },
+        }
+        Ok(())
+    }
+
+    /// Returns parameter `i`, or `default` if it was omitted or zero.
+    fn param(&self, i: usize, default: u16) -> u16 {
+        match self.params.get(i) {
+            Some(&p) if i < self.param_count && p != 0 => p,
+            _ => default,
+        }
+    }
+
+    /// Carries out a complete control sequence ending in `command`.
+    fn dispatch(&mut self, command: u8) {
+        let (rows, cols) = self.cursor.size();
+        if rows == 0 || cols == 0 {
+            return;
+        }
+        let (row, col) = self.cursor.position();
+        let row = usize::min(row, rows - 1);
+        let col = usize::min(col, cols - 1);
+        match command {
+            b'H' | b'f' => {
+                let r = usize::from(self.param(0, 1)) - 1;
+                let c = usize::from(self.param(1, 1)) - 1;
+                self.cursor
+                    .goto(usize::min(r, rows - 1), usize::min(c, cols - 1));
+            }
+            b'A' => {
+                let n = usize::from(self.param(0, 1));
+                self.cursor.goto(row.saturating_sub(n), col);
+            }
+            b'B' => {
+                let n = usize::from(self.param(0, 1));
+                self.cursor.goto(usize::min(row + n, rows - 1), col);
+            }
+            b'C' => {
+                let n = usize::from(self.param(0, 1));
+                self.cursor.goto(row, usize::min(col + n, cols - 1));
+            }
+            b'D' => {
+                let n = usize::from(self.param(0, 1));
+                self.cursor.goto(row, col.saturating_sub(n));
+            }
+            b'K' => match self.param(0, 0) {
+                0 => self.cursor.fill_rect(row, col, 1, cols - col, b' '),
+                1 => self.cursor.fill_rect(row, 0, 1, col + 1, b' '),
+                2 => self.cursor.fill_rect(row, 0, 1, cols, b' '),
+                _ => (),
+            },
+            b'J' => match self.param(0, 0) {
+                0 => {
+                    self.cursor.fill_rect(row, col, 1, cols - col, b' ');
+                    self.cursor.fill_rect(row + 1, 0, rows, cols, b' ');
+                }
+                1 => {
+                    self.cursor.fill_rect(0, 0, row, cols, b' ');
+                    self.cursor.fill_rect(row, 0, 1, col + 1, b' ');
+                }
+                2 => self.cursor.fill_rect(0, 0, rows, cols, b' '),
+                _ => (),
+            },
+            b'm' => self.select_graphic_rendition(),
+            _ => (),
+        }
+    }
+
+    /// Handles the SGR (`m`) command, which may carry several parameters.
+    fn select_graphic_rendition(&mut self) {
+        if self.param_count == 0 {
+            self.param_count = 1;
+        }
+        for i in 0..usize::min(self.param_count, MAX_PARAMS) {
+            match self.params[i] {
+                0 => {
+                    self.cursor.fg = self.default_fg;
+                    self.cursor.bg = self.default_bg;
+                }
+                n @ 30..=37 => self.cursor.fg = ansi_color(n - 30, false),
+                39 => self.cursor.fg = self.default_fg,
+                n @ 40..=47 => self.cursor.bg = ansi_color(n - 40, false),
+                49 => self.cursor.bg = self.default_bg,
+                n @ 90..=97 => self.cursor.fg = ansi_color(n - 90, true),
+                n @ 100..=107 => self.cursor.bg = ansi_color(n - 100, true),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<'c, 'a> fmt::Write for AnsiWriter<'c, 'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.putc(cp437::encode(c))?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps one of the eight ANSI color numbers onto an m4vga pixel.
+///
+/// The ANSI number's bits select red (1), green (2), and blue (4). Normal
+/// colors use the second-brightest level of each channel, and bright colors the
+/// brightest. Bright black comes out as dark gray.
+fn ansi_color(n: u16, bright: bool) -> m4vga::Pixel {
+    if n == 0 {
+        return if bright { 0b01_01_01 } else { 0 };
+    }
+    let level = if bright { 0b11 } else { 0b10 };
+    let n = n as u8;
+    let r = (n & 1) * level;
+    let g = ((n >> 1) & 1) * level;
+    let b = ((n >> 2) & 1) * level;
+    r | (g << 2) | (b << 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 10;
+    const ROWS: usize = 4;
+
+    /// Colors the cursor starts with, which SGR 0, 39 and 49 go back to.
+    const FG: m4vga::Pixel = 0b01_00_00;
+    const BG: m4vga::Pixel = 0b00_01_00;
+
+    fn filled() -> [AChar; COLS * ROWS] {
+        [AChar::from_ascii_char(b'x'); COLS * ROWS]
+    }
+
+    fn row_text(buf: &[AChar], row: usize) -> String {
+        buf[row * COLS..(row + 1) * COLS]
+            .iter()
+            .map(|c| c.char())
+            .collect()
+    }
+
+    fn colors(a: AChar) -> (m4vga::Pixel, m4vga::Pixel) {
+        (a.foreground(), a.background())
+    }
+
+    /// Writes `text` through an `AnsiWriter` on `buf`, and returns where the
+    /// cursor ends up.
+    fn write(buf: &mut [AChar], text: &str) -> (usize, usize) {
+        let mut c = Cursor::new_with(buf, COLS, FG, BG);
+        let mut w = AnsiWriter::new(&mut c);
+        w.write_str(text).unwrap();
+        w.cursor.position()
+    }
+
+    #[test]
+    fn cursor_position_clamps_to_window() {
+        let mut buf = filled();
+        assert_eq!(write(&mut buf, "\x1b[2;3Ha"), (1, 3));
+        assert_eq!(write(&mut buf, "\x1b[4;5fb"), (3, 5));
+        assert_eq!(write(&mut buf, "\x1b[99;99H"), (ROWS - 1, COLS - 1));
+        // Missing and zero parameters count as one.
+        assert_eq!(write(&mut buf, "\x1b[3;5H\x1b[H"), (0, 0));
+        assert_eq!(write(&mut buf, "\x1b[0;4Hc"), (0, 4));
+        assert_eq!(write(&mut buf, "\x1b[;7f"), (0, 6));
+        assert_eq!(write(&mut buf, "\x1b[3;3H\x1b[2A\x1b[9C"), (0, 9));
+        assert_eq!(write(&mut buf, "\x1b[B\x1b[9D"), (1, 0));
+        assert_eq!(row_text(&buf, 0), "xxxcxxxxxx");
+        assert_eq!(row_text(&buf, 1), "xxaxxxxxxx");
+        assert_eq!(row_text(&buf, 3), "xxxxbxxxxx");
+    }
+
+    #[test]
+    fn sgr_sets_colors() {
+        let mut buf = filled();
+        write(
+            &mut buf,
+            "\x1b[31;44ma\x1b[92mb\x1b[103mc\x1b[39md\x1b[49me\
+             \x1b[90;47mf\x1b[0mg\x1b[35;100mh\x1b[mi",
+        );
+        assert_eq!(row_text(&buf, 0), "abcdefghix");
+        let cells: Vec<_> = buf[..9].iter().map(|&a| colors(a)).collect();
+        assert_eq!(
+            cells,
+            [
+                (0b00_00_10, 0b10_00_00),
+                (0b00_11_00, 0b10_00_00),
+                (0b00_11_00, 0b00_11_11),
+                (FG, 0b00_11_11),
+                (FG, BG),
+                (0b01_01_01, 0b10_10_10),
+                (FG, BG),
+                (0b10_00_10, 0b01_01_01),
+                (FG, BG),
+            ]
+        );
+    }
+
+    #[test]
+    fn erase_in_line() {
+        let mut buf = filled();
+        assert_eq!(write(&mut buf, "\x1b[1;5H\x1b[K"), (0, 4));
+        assert_eq!(write(&mut buf, "\x1b[2;5H\x1b[1K"), (1, 4));
+        assert_eq!(write(&mut buf, "\x1b[3;5H\x1b[2K"), (2, 4));
+        assert_eq!(write(&mut buf, "\x1b[4;5H\x1b[0K"), (3, 4));
+        assert_eq!(row_text(&buf, 0), "xxxx      ");
+        assert_eq!(row_text(&buf, 1), "     xxxxx");
+        assert_eq!(row_text(&buf, 2), "          ");
+        assert_eq!(row_text(&buf, 3), "xxxx      ");
+        assert_eq!(colors(buf[COLS]), (FG, BG));
+    }
+
+    #[test]
+    fn erase_in_display() {
+        let mut buf = filled();
+        assert_eq!(write(&mut buf, "\x1b[2;5H\x1b[J"), (1, 4));
+        assert_eq!(row_text(&buf, 0), "xxxxxxxxxx");
+        assert_eq!(row_text(&buf, 1), "xxxx      ");
+        assert_eq!(row_text(&buf, 2), "          ");
+        assert_eq!(row_text(&buf, 3), "          ");
+
+        let mut buf = filled();
+        write(&mut buf, "\x1b[2;5H\x1b[1J");
+        assert_eq!(row_text(&buf, 0), "          ");
+        assert_eq!(row_text(&buf, 1), "     xxxxx");
+        assert_eq!(row_text(&buf, 2), "xxxxxxxxxx");
+
+        let mut buf = filled();
+        write(&mut buf, "\x1b[2;5H\x1b[2J");
+        assert!((0..ROWS).all(|r| row_text(&buf, r) == "          "));
+    }
+
+    #[test]
+    fn private_and_unknown_sequences_are_ignored() {
+        let mut buf = filled();
+        let text = "\x1b[?25la\x1b[?1049h\x1b[5nb\x1bcd\x1b[31";
+        assert_eq!(write(&mut buf, text), (0, 3));
+        assert_eq!(row_text(&buf, 0), "abdxxxxxxx");
+        assert!(buf[..3].iter().all(|&a| colors(a) == (FG, BG)));
+        // A private marker doesn't carry over to the next sequence.
+        assert_eq!(write(&mut buf, "\x1b[?7l\x1b[2;2H"), (1, 1));
+    }
+
+    #[test]
+    fn sequences_split_across_writes() {
+        let mut buf = filled();
+        {
+            let mut c = Cursor::new_with(&mut buf, COLS, FG, BG);
+            let mut w = AnsiWriter::new(&mut c);
+            for part in &["\x1b[", "3", ";4", "H", "\x1b", "[3", "2mq"] {
+                w.write_str(part).unwrap();
+            }
+            write!(w, "\x1b[{};{}Hr", 1, 2).unwrap();
+            assert_eq!(w.cursor.position(), (0, 2));
+        }
+        assert_eq!(row_text(&buf, 2), "xxxqxxxxxx");
+        assert_eq!(colors(buf[2 * COLS + 3]), (0b00_10_00, BG));
+        assert_eq!(row_text(&buf, 0), "xrxxxxxxxx");
+        assert_eq!(colors(buf[1]), (0b00_10_00, BG));
+    }
+}
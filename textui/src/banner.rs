@@ -0,0 +1,165 @@
+//! Large block-letter text, built from character cells.
+//!
+//! Each glyph is a 5x5 bitmap, and each set bit is drawn as a solid block
+//! (`FULL_BLOCK`) in the cursor's foreground color. Unset bits are left alone,
+//! so a banner can be drawn over a backdrop without boxing it in.
+
+use crate::cursor::Cursor;
+
+/// Height of a banner, in rows.
+pub const GLYPH_H: usize = 5;
+/// Width of one banner letter, in columns, not counting the gap after it.
+pub const GLYPH_W: usize = 5;
+/// Columns left blank between letters.
+const GAP: usize = 1;
+
+/// CP437 glyph used for the set bits of a letter.
+pub const FULL_BLOCK: u8 = 0xDB;
+
+/// Letters `A` through `Z`. Each glyph is packed into the low 25 bits of a
+/// `u32`, five bits per row with the top row most significant; within a row,
+/// the leftmost cell is the most significant bit. The underscores separate
+/// rows.
+static LETTERS: [u32; 26] = [
+    0b01110_10001_11111_10001_10001, // A
+    0b11110_10001_11110_10001_11110, // B
+    0b01111_10000_10000_10000_01111, // C
+    0b11110_10001_10001_10001_11110, // D
+    0b11111_10000_11110_10000_11111, // E
+    0b11111_10000_11110_10000_10000, // F
+    0b01111_10000_10011_10001_01111, // G
+    0b10001_10001_11111_10001_10001, // H
+    0b11111_00100_00100_00100_11111, // I
+    0b00111_00010_00010_10010_01100, // J
+    0b10001_10010_11100_10010_10001, // K
+    0b10000_10000_10000_10000_11111, // L
+    0b10001_11011_10101_10001_10001, // M
+    0b10001_11001_10101_10011_10001, // N
+    0b01110_10001_10001_10001_01110, // O
+    0b11110_10001_11110_10000_10000, // P
+    0b01110_10001_10101_10010_01101, // Q
+    0b11110_10001_11110_10010_10001, // R
+    0b01111_10000_01110_00001_11110, // S
+    0b11111_00100_00100_00100_00100, // T
+    0b10001_10001_10001_10001_01110, // U
+    0b10001_10001_10001_01010_00100, // V
+    0b10001_10001_10101_11011_10001, // W
+    0b10001_01010_00100_01010_10001, // X
+    0b10001_01010_00100_00100_00100, // Y
+    0b11111_00010_00100_01000_11111, // Z
+];
+
+/// Digits `0` through `9`, packed like `LETTERS`.
+static DIGITS: [u32; 10] = [
+    0b01110_10011_10101_11001_01110, // 0
+    0b00100_01100_00100_00100_01110, // 1
+    0b11110_00001_01110_10000_11111, // 2
+    0b11110_00001_00110_00001_11110, // 3
+    0b10010_10010_11111_00010_00010, // 4
+    0b11111_10000_11110_00001_11110, // 5
+    0b01110_10000_11110_10001_01110, // 6
+    0b11111_00001_00010_00100_00100, // 7
+    0b01110_10001_01110_10001_01110, // 8
+    0b01110_10001_01111_00001_01110, // 9
+];
+
+/// Looks up the bitmap for `c`. Lowercase letters get the uppercase glyph;
+/// anything without a glyph, including space, is blank.
+fn glyph(c: u8) -> u32 {
+    match c {
+        b'A'..=b'Z' => LETTERS[usize::from(c - b'A')],
+        b'a'..=b'z' => LETTERS[usize::from(c - b'a')],
+        b'0'..=b'9' => DIGITS[usize::from(c - b'0')],
+        _ => 0,
+    }
+}
+
+/// Returns the number of columns `draw_banner` uses for `text`.
+pub fn banner_width(text: &[u8]) -> usize {
+    (text.len() * (GLYPH_W + GAP)).saturating_sub(GAP)
+}
+
+/// Draws `text` in block letters `GLYPH_H` rows tall, with the top-left corner
+/// of the first letter at `row`, `col`.
+///
+/// Letters are `GLYPH_W` columns wide with one blank column between them;
+/// see `banner_width` for centering. Anything past the edges of the window is
+/// clipped. The cursor is not moved.
+pub fn draw_banner(c: &mut Cursor, row: usize, col: usize, text: &[u8]) {
+    let (_, width) = c.size();
+    for (i, &ch) in text.iter().enumerate() {
+        let left = col + i * (GLYPH_W + GAP);
+        if left >= width {
+            break;
+        }
+        let bits = glyph(ch);
+        for r in 0..GLYPH_H {
+            for k in 0..GLYPH_W {
+                let shift = (GLYPH_H - 1 - r) * GLYPH_W + (GLYPH_W - 1 - k);
+                if bits >> shift & 1 != 0 {
+                    c.hline(row + r, left + k, 1, FULL_BLOCK);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    fn render(
+        cols: usize,
+        rows: usize,
+        col: usize,
+        text: &[u8],
+    ) -> Vec<String> {
+        let mut buf = vec![AChar::from_ascii_char(b'.'); cols * rows];
+        draw_banner(&mut Cursor::new(&mut buf, cols), 0, col, text);
+        buf.chunks(cols)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        FULL_BLOCK => '#',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn letters_are_spaced_and_blank_bits_untouched() {
+        assert_eq!(
+            render(13, 5, 1, b"Hi"),
+            [
+                ".#...#.#####.",
+                ".#...#...#...",
+                ".#####...#...",
+                ".#...#...#...",
+                ".#...#.#####.",
+            ]
+        );
+        assert_eq!(banner_width(b"Hi"), 11);
+        assert_eq!(banner_width(b""), 0);
+    }
+
+    #[test]
+    fn clipped_at_right_and_bottom() {
+        assert_eq!(
+            render(8, 3, 0, b"L7 X"),
+            ["#.....##", "#.......", "#......."]
+        );
+    }
+
+    #[test]
+    fn every_glyph_fits_in_25_bits() {
+        for &g in LETTERS.iter().chain(DIGITS.iter()) {
+            assert_eq!(g >> (GLYPH_W * GLYPH_H), 0);
+            assert_ne!(g, 0);
+        }
+        assert_eq!(glyph(b' '), 0);
+        assert_eq!(glyph(b'?'), 0);
+    }
+}
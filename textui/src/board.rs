@@ -0,0 +1,249 @@
+//! Timetable of the next departures.
+//!
+//! A `Departure` owns its text in fixed-size arrays rather than borrowing
+//! it, so that records can be filled in place as they arrive, say from a
+//! serial feed, as well as written out in static tables.
+
+use crate::cursor::{Cursor, Rect};
+use crate::widget::Widget;
+
+/// Widths of a `Departure`'s line and destination.
+pub const LINE_W: usize = 3;
+pub const DEST_W: usize = 20;
+
+/// Most rows a `DepartureBoard` shows.
+pub const BOARD_CAP: usize = 8;
+
+/// Width of the minutes column, enough for `99 min`.
+const MINUTES_W: usize = 6;
+
+/// One train or bus leaving soon.
+///
+/// Text shorter than its field is padded with blanks or zeros, which aren't
+/// drawn.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Departure {
+    pub line: [u8; LINE_W],
+    pub destination: [u8; DEST_W],
+    /// Minutes until it leaves; 0 shows as due.
+    pub minutes: u16,
+}
+
+impl Departure {
+    /// Record with no line or destination, leaving now.
+    pub const EMPTY: Departure = Departure {
+        line: [0; LINE_W],
+        destination: [0; DEST_W],
+        minutes: 0,
+    };
+
+    /// Makes a record from text of any length, cutting it to fit.
+    pub fn new(line: &[u8], destination: &[u8], minutes: u16) -> Self {
+        let mut d = Departure::EMPTY;
+        d.set_line(line);
+        d.set_destination(destination);
+        d.minutes = minutes;
+        d
+    }
+
+    pub fn set_line(&mut self, line: &[u8]) {
+        copy_field(&mut self.line, line);
+    }
+
+    pub fn set_destination(&mut self, destination: &[u8]) {
+        copy_field(&mut self.destination, destination);
+    }
+}
+
+/// Copies as much of `text` as fits into `field`, zeroing the rest.
+fn copy_field(field: &mut [u8], text: &[u8]) {
+    for (i, cell) in field.iter_mut().enumerate() {
+        *cell = text.get(i).cloned().unwrap_or(0);
+    }
+}
+
+/// Returns `field` without its padding.
+fn trimmed(field: &[u8]) -> &[u8] {
+    let end = field
+        .iter()
+        .rposition(|&b| b != 0 && b != b' ')
+        .map_or(0, |i| i + 1);
+    &field[..end]
+}
+
+/// Departures one to a row, with the line, the destination, and the minutes
+/// to go right-aligned, on backgrounds alternating between `bg` and `alt_bg`.
+/// Departures that are due say so in `due_fg`.
+///
+/// Like the `Clock`, the board is given its data with `update`, which only
+/// redraws rows that changed, so it can be called every frame with the
+/// latest records. Rows without a departure are left blank.
+#[derive(Copy, Clone, Debug)]
+pub struct DepartureBoard {
+    /// Area of the board, one row per departure, up to `BOARD_CAP`.
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub alt_bg: m4vga::Pixel,
+    pub due_fg: m4vga::Pixel,
+    /// Shown in place of the minutes for a departure that is due.
+    pub due: &'static [u8],
+    /// Departure on each row, or `None` for a blank row.
+    shown: [Option<Departure>; BOARD_CAP],
+    /// Whether `shown` is what is on screen.
+    valid: bool,
+}
+
+impl DepartureBoard {
+    /// Makes a board in white on dark blue stripes, with due departures in
+    /// yellow, that hasn't been drawn yet.
+    pub const fn new(rect: Rect) -> Self {
+        DepartureBoard {
+            rect,
+            fg: 0b11_11_11,
+            bg: 0b01_00_00,
+            alt_bg: 0b10_00_00,
+            due_fg: 0b00_11_11,
+            due: b"Due",
+            shown: [None; BOARD_CAP],
+            valid: false,
+        }
+    }
+
+    /// Returns the number of rows, which is at most `BOARD_CAP`.
+    pub fn rows(&self) -> usize {
+        usize::min(self.rect.h, BOARD_CAP)
+    }
+
+    /// Shows the first of `departures`, as many as there are rows, redrawing
+    /// only the rows that changed. Returns whether anything was drawn.
+    pub fn update(&mut self, c: &mut Cursor, departures: &[Departure]) -> bool {
+        let rows = self.rows();
+        let mut w = c.window(self.rect.row, self.rect.col, rows, self.rect.w);
+        let mut drawn = false;
+        for row in 0..rows {
+            let departure = departures.get(row).cloned();
+            if self.valid && self.shown[row] == departure {
+                continue;
+            }
+            self.shown[row] = departure;
+            self.draw_row(&mut w, row, departure.as_ref());
+            drawn = true;
+        }
+        self.valid = true;
+        drawn
+    }
+
+    /// Forgets what is on screen, so that the next `update` draws every row.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Draws `departure` on `row` of the window `w`, or blanks the row.
+    fn draw_row(
+        &self,
+        w: &mut Cursor,
+        row: usize,
+        departure: Option<&Departure>,
+    ) {
+        let (_, width) = w.size();
+        let mut line = w.window(row, 0, 1, width);
+        line.fg = self.fg;
+        line.bg = if row % 2 == 1 { self.alt_bg } else { self.bg };
+        line.set_scroll(false);
+        line.clear();
+        let d = match departure {
+            Some(d) => d,
+            None => return,
+        };
+        line.puts_at(0, 1, trimmed(&d.line)).ok();
+        let dest_col = LINE_W + 2;
+        let dest_w = width.saturating_sub(dest_col + MINUTES_W + 2);
+        line.goto(0, dest_col);
+        line.puts_truncated(trimmed(&d.destination), dest_w);
+        let right_col = width.saturating_sub(2);
+        if d.minutes == 0 {
+            let mut line = line.styled(self.due_fg, line.bg);
+            line.puts_right(0, right_col, self.due);
+        } else {
+            write_right!(line, 0, right_col, "{} min", d.minutes).ok();
+        }
+    }
+}
+
+impl Widget for DepartureBoard {
+    /// Draws the departures last passed to `update`.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let rows = usize::min(area.h, BOARD_CAP);
+        let mut w = c.window(area.row, area.col, rows, area.w);
+        for row in 0..rows {
+            self.draw_row(&mut w, row, self.shown[row].as_ref());
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, LINE_W + MINUTES_W + 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::TRUNCATION_MARK;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 24;
+    const ROWS: usize = 3;
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        TRUNCATION_MARK => '~',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rows_stripe_highlight_due_and_fill_blanks() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let mut board = DepartureBoard::new(Rect::new(0, 0, ROWS, COLS));
+        let mut departures = [
+            Departure::new(b"1", b"Central", 0),
+            Departure::new(b"12", b"Riverside Park and Ride", 14),
+        ];
+        assert!(board.update(&mut Cursor::new(&mut buf, COLS), &departures));
+        assert_eq!(
+            text(&buf),
+            [
+                " 1   Central        Due ",
+                " 12  Riverside ~ 14 min ",
+                "                        ",
+            ]
+        );
+        assert_eq!(buf[20].foreground(), board.due_fg);
+        assert_eq!(buf[0].background(), board.bg);
+        assert_eq!(buf[COLS].background(), board.alt_bg);
+
+        // Only the row that changed is drawn again.
+        buf[0] = AChar::from_ascii_char(b'x');
+        assert!(!board.update(&mut Cursor::new(&mut buf, COLS), &departures));
+        departures[1].minutes = 13;
+        assert!(board.update(&mut Cursor::new(&mut buf, COLS), &departures));
+        let rows = text(&buf);
+        assert_eq!(rows[0], "x1   Central        Due ");
+        assert_eq!(rows[1], " 12  Riverside ~ 13 min ");
+
+        assert!(board.update(&mut Cursor::new(&mut buf, COLS), &[]));
+        assert_eq!(text(&buf)[1], " ".repeat(COLS));
+
+        board.due = b"Now";
+        board.invalidate();
+        board.update(&mut Cursor::new(&mut buf, COLS), &departures);
+        assert_eq!(text(&buf)[0], " 1   Central        Now ");
+    }
+}
@@ -0,0 +1,517 @@
+//! What is being bought, and a view of it for the confirmation screen.
+//!
+//! A `Cart` only holds item ids and quantities; the names and prices come
+//! from a `Catalog`, indexed by id, when the cart is drawn or totalled.
+
+use core::fmt;
+
+use crate::cursor::{Cursor, Rect};
+use crate::price::{format_price, POUND};
+use crate::table::{Align, Column, Table, HEADER_ROWS};
+use crate::widget::Widget;
+
+/// Most distinct items a `Cart` holds.
+pub const CART_CAP: usize = 8;
+
+/// Names and unit prices, in cents, of the items for sale, by id.
+#[derive(Copy, Clone, Debug)]
+pub struct Catalog<'t> {
+    pub names: &'t [&'t [u8]],
+    pub prices: &'t [i32],
+}
+
+impl<'t> Catalog<'t> {
+    /// Returns the name of item `id`, or `?` if there is no such item.
+    pub fn name(&self, id: usize) -> &'t [u8] {
+        self.names.get(id).cloned().unwrap_or(b"?")
+    }
+
+    /// Returns the unit price of item `id`, or 0 if there is no such item.
+    pub fn price(&self, id: usize) -> i32 {
+        self.prices.get(id).cloned().unwrap_or(0)
+    }
+}
+
+/// Errors reported when changing a `Cart`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CartError {
+    /// The cart already holds `CART_CAP` distinct items.
+    Full,
+}
+
+/// One line of a `Cart`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct CartItem {
+    /// Index of the item in the `Catalog`.
+    pub id: usize,
+    pub qty: u32,
+}
+
+/// Up to `CART_CAP` distinct items with their quantities, in the order they
+/// were first added.
+#[derive(Copy, Clone, Debug)]
+pub struct Cart {
+    items: [CartItem; CART_CAP],
+    len: usize,
+}
+
+impl Cart {
+    pub const fn new() -> Self {
+        Cart {
+            items: [CartItem { id: 0, qty: 0 }; CART_CAP],
+            len: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[CartItem] {
+        &self.items[..self.len]
+    }
+
+    /// Returns the number of distinct items.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns how many of item `id` are in the cart.
+    pub fn qty(&self, id: usize) -> u32 {
+        self.position(id).map_or(0, |i| self.items[i].qty)
+    }
+
+    /// Sets how many of item `id` are in the cart, adding it at the end or
+    /// taking it out, for a `qty` of 0, as needed. Returns the line the item
+    /// is on, or was on, or `None` if nothing changed, including when a new
+    /// item doesn't fit.
+    pub fn set(&mut self, id: usize, qty: u32) -> Option<usize> {
+        match self.position(id) {
+            Some(i) if self.items[i].qty == qty => None,
+            Some(i) if qty == 0 => {
+                for j in i + 1..self.len {
+                    self.items[j - 1] = self.items[j];
+                }
+                self.len -= 1;
+                Some(i)
+            }
+            Some(i) => {
+                self.items[i].qty = qty;
+                Some(i)
+            }
+            None if qty == 0 || self.len == CART_CAP => None,
+            None => {
+                self.items[self.len] = CartItem { id, qty };
+                self.len += 1;
+                Some(self.len - 1)
+            }
+        }
+    }
+
+    /// Adds `qty` more of item `id`, on its line if it has one or on a new
+    /// line at the end. A new item that doesn't fit is left out, and
+    /// `CartError::Full` returned.
+    pub fn add(&mut self, id: usize, qty: u32) -> Result<(), CartError> {
+        let have = self.qty(id);
+        if have == 0 && qty > 0 && self.len == CART_CAP {
+            return Err(CartError::Full);
+        }
+        self.set(id, have.saturating_add(qty));
+        Ok(())
+    }
+
+    /// Takes out cart line `line`, moving the lines after it up. Returns
+    /// what was on it, or `None` if there is no such line.
+    pub fn remove(&mut self, line: usize) -> Option<CartItem> {
+        let item = self.items().get(line).cloned()?;
+        self.set(item.id, 0);
+        Some(item)
+    }
+
+    /// Takes everything out of the cart.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the cost of everything in the cart, in cents, saturating
+    /// rather than overflowing.
+    pub fn total(&self, catalog: &Catalog) -> i32 {
+        self.items().iter().fold(0, |sum: i32, item| {
+            let line = catalog.price(item.id).saturating_mul(item.qty as i32);
+            sum.saturating_add(line)
+        })
+    }
+
+    fn position(&self, id: usize) -> Option<usize> {
+        self.items().iter().position(|item| item.id == id)
+    }
+}
+
+impl Default for Cart {
+    fn default() -> Self {
+        Cart::new()
+    }
+}
+
+/// Widths of the quantity, unit price and line total columns of a
+/// `CartView`. The name column gets the rest.
+const QTY_W: usize = 3;
+const EACH_W: usize = 8;
+const TOTAL_W: usize = 9;
+
+/// Rows of a `CartView` that aren't items: the header and its rule, and the
+/// separator and grand total at the bottom.
+pub const CART_CHROME_ROWS: usize = HEADER_ROWS + 2;
+
+/// Text of one table cell, formatted in place.
+struct CellBuf {
+    buf: [u8; TOTAL_W + 1],
+    len: usize,
+}
+
+impl CellBuf {
+    fn new() -> Self {
+        CellBuf {
+            buf: [0; TOTAL_W + 1],
+            len: 0,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl fmt::Write for CellBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.len == self.buf.len() {
+                return Err(fmt::Error);
+            }
+            self.buf[self.len] = crate::cp437::encode(c);
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `cents` in pounds into a new cell. A price too wide for the cell
+/// is cut short, and marked so when drawn.
+fn price_cell(cents: i32) -> CellBuf {
+    let mut cell = CellBuf::new();
+    cell.buf[..POUND.len()].copy_from_slice(POUND);
+    cell.len = POUND.len();
+    format_price(cents, &mut cell).ok();
+    cell
+}
+
+/// Table of what is in a `Cart`: one row per item with its name, quantity,
+/// unit price and line total, then a grand total under a separator.
+///
+/// Items that don't fit are split into pages, like a `PagedList`, with the
+/// page showing noted at the right end of the separator. Change quantities
+/// through `set_qty`, which redraws only the rows that changed.
+#[derive(Copy, Clone, Debug)]
+pub struct CartView<'t> {
+    pub cart: Cart,
+    pub catalog: Catalog<'t>,
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Cart line drawn with the colors swapped, such as one to be removed.
+    pub selected: Option<usize>,
+    /// Column headings, item to total. The last also labels the total row.
+    pub headings: [&'t [u8]; 4],
+    /// Index of the page showing, counting from 0.
+    page: usize,
+}
+
+impl<'t> CartView<'t> {
+    /// Makes a view of an empty cart, in white on black.
+    pub const fn new(catalog: Catalog<'t>, rect: Rect) -> Self {
+        CartView {
+            cart: Cart::new(),
+            catalog,
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            selected: None,
+            headings: [b"Item", b"Qty", b"Each", b"Total"],
+            page: 0,
+        }
+    }
+
+    /// Returns the number of item rows on a page. Zero counts as one.
+    pub fn page_size(&self) -> usize {
+        usize::max(self.rect.h.saturating_sub(CART_CHROME_ROWS), 1)
+    }
+
+    /// Returns the number of pages, which is at least one.
+    pub fn pages(&self) -> usize {
+        self.cart.len().saturating_sub(1) / self.page_size() + 1
+    }
+
+    /// Returns the index of the page showing.
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Shows page `page`, or the last page if there aren't that many.
+    /// Returns whether the page changed and so needs a `redraw`.
+    pub fn set_page(&mut self, page: usize) -> bool {
+        let before = self.page;
+        self.page = usize::min(page, self.pages() - 1);
+        self.page != before
+    }
+
+    /// Sets how many of item `id` are in the cart, like `Cart::set`, and
+    /// redraws what that changed: the item's row, or from there down if it
+    /// came or went, and the grand total. If the item isn't on the page
+    /// showing, its page is drawn instead. Returns whether anything changed.
+    pub fn set_qty(&mut self, c: &mut Cursor, id: usize, qty: u32) -> bool {
+        let before = self.cart.len();
+        let line = match self.cart.set(id, qty) {
+            Some(line) => line,
+            None => return false,
+        };
+        // Taking out the last item on the last page takes the page with it.
+        let page = usize::min(line / self.page_size(), self.pages() - 1);
+        if page != self.page {
+            self.page = page;
+            self.redraw(c);
+            return true;
+        }
+        let mut w = self.window(c);
+        let columns = self.columns();
+        let table = Table::new(&columns, 0, 0);
+        let last = if self.cart.len() == before {
+            line
+        } else {
+            usize::max(self.cart.len(), before) - 1
+        };
+        for i in line..=last {
+            self.draw_line(&mut w, &table, i);
+        }
+        self.draw_total(&mut w, &table);
+        true
+    }
+
+    /// Draws the view at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+
+    /// Returns a window over the view's `rect`, in its colors.
+    fn window<'c>(&self, c: &'c mut Cursor) -> Cursor<'c> {
+        let r = self.rect;
+        let mut w = c.window(r.row, r.col, r.h, r.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w
+    }
+
+    /// Returns the columns, with the name column taking whatever width the
+    /// others leave.
+    fn columns(&self) -> [Column<'t>; 4] {
+        let fixed = QTY_W + EACH_W + TOTAL_W + 3;
+        let name_w = self.rect.w.saturating_sub(fixed);
+        let [item, qty, each, total] = self.headings;
+        [
+            Column::new(item, name_w, Align::Left),
+            Column::new(qty, QTY_W, Align::Right),
+            Column::new(each, EACH_W, Align::Right),
+            Column::new(total, TOTAL_W, Align::Right),
+        ]
+    }
+
+    /// Draws cart line `line` if it is on the page showing, or blanks its row
+    /// if the cart has no such line.
+    fn draw_line(&self, c: &mut Cursor, table: &Table, line: usize) {
+        let first = self.page * self.page_size();
+        if line < first || line >= first + self.page_size() {
+            return;
+        }
+        let item = match self.cart.items().get(line) {
+            Some(item) => *item,
+            None => return table.draw_row(c, line - first, &[]),
+        };
+        let (fg, bg) = if self.selected == Some(line) {
+            (c.bg, c.fg)
+        } else {
+            (c.fg, c.bg)
+        };
+        let mut c = c.styled(fg, bg);
+        let price = self.catalog.price(item.id);
+        let mut qty = CellBuf::new();
+        {
+            use core::fmt::Write;
+            write!(qty, "{}", item.qty).ok();
+        }
+        let each = price_cell(price);
+        let total = price_cell(price.saturating_mul(item.qty as i32));
+        let cells = [
+            self.catalog.name(item.id),
+            qty.as_bytes(),
+            each.as_bytes(),
+            total.as_bytes(),
+        ];
+        table.draw_row(&mut c, line - first, &cells);
+    }
+
+    /// Draws the separator, with the page indicator if there is more than
+    /// one page, and the grand total under it.
+    fn draw_total(&self, c: &mut Cursor, table: &Table) {
+        let rule = self.page_size();
+        table.draw_rule(c, rule);
+        if self.pages() > 1 {
+            let (page, pages) = (self.page + 1, self.pages());
+            let (row, right_col) = (HEADER_ROWS + rule, table.width() - 1);
+            write_right!(c, row, right_col, " {}/{}", page, pages).ok();
+        }
+        let total = price_cell(self.cart.total(&self.catalog));
+        let label = self.headings[3];
+        table.draw_row(c, rule + 1, &[label, b"", b"", total.as_bytes()]);
+    }
+}
+
+impl<'t> Widget for CartView<'t> {
+    /// Draws the page showing. `area` should be the same size as `rect`,
+    /// which sets the page size.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        let columns = self.columns();
+        let table = Table::new(&columns, 0, 0);
+        table.draw_header(&mut w);
+        let first = self.page * self.page_size();
+        for line in first..first + self.page_size() {
+            self.draw_line(&mut w, &table, line);
+        }
+        self.draw_total(&mut w, &table);
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (CART_CHROME_ROWS + 1, QTY_W + EACH_W + TOTAL_W + 3 + 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::TRUNCATION_MARK;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 30;
+    const ROWS: usize = 6;
+
+    static CATALOG: Catalog = Catalog {
+        names: &[b"Adult", b"Child", b"Senior"],
+        prices: &[250, 125, 200],
+    };
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        0x9C => 'L',
+                        0xC4 => '-',
+                        TRUNCATION_MARK => '~',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cart_adds_updates_and_removes() {
+        let mut cart = Cart::new();
+        assert_eq!(cart.set(2, 1), Some(0));
+        assert_eq!(cart.set(0, 3), Some(1));
+        assert_eq!(cart.set(0, 3), None);
+        assert_eq!(cart.set(1, 0), None);
+        assert_eq!(cart.total(&CATALOG), 200 + 3 * 250);
+        assert_eq!(cart.set(2, 0), Some(0));
+        assert_eq!(cart.items(), [CartItem { id: 0, qty: 3 }]);
+        for id in 0..CART_CAP + 1 {
+            cart.set(id, 1);
+        }
+        assert_eq!(cart.len(), CART_CAP);
+        assert_eq!(cart.qty(CART_CAP), 0);
+    }
+
+    #[test]
+    fn cart_add_tops_up_and_reports_full() {
+        let mut cart = Cart::new();
+        assert_eq!(cart.add(1, 2), Ok(()));
+        assert_eq!(cart.add(1, 3), Ok(()));
+        assert_eq!(cart.items(), [CartItem { id: 1, qty: 5 }]);
+        for id in 2..CART_CAP + 1 {
+            assert_eq!(cart.add(id, 1), Ok(()));
+        }
+        assert_eq!(cart.add(0, 1), Err(CartError::Full));
+        assert_eq!(cart.add(1, 1), Ok(()));
+        assert_eq!(cart.qty(1), 6);
+
+        assert_eq!(cart.remove(0), Some(CartItem { id: 1, qty: 6 }));
+        assert_eq!(cart.items()[0].id, 2);
+        assert_eq!(cart.remove(CART_CAP), None);
+        assert_eq!(cart.add(0, 1), Ok(()));
+        assert_eq!(cart.len(), CART_CAP);
+    }
+
+    #[test]
+    fn view_pages_and_redraws_only_changed_rows() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let mut view = CartView::new(CATALOG, Rect::new(0, 0, ROWS, 29));
+        view.cart.set(0, 2);
+        view.cart.set(1, 1);
+        view.cart.set(2, 1);
+        assert_eq!(view.pages(), 2);
+        view.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(
+            text(&buf),
+            [
+                "Item   Qty     Each     Total.",
+                "-----------------------------.",
+                "Adult    2    L2.50     L5.00.",
+                "Child    1    L1.25     L1.25.",
+                "------------------------- 1/2.",
+                "Total                   L8.25.",
+            ]
+        );
+
+        view.selected = Some(1);
+        view.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(buf[3 * COLS].background(), view.fg);
+        assert_eq!(buf[2 * COLS].background(), view.bg);
+
+        // Scribble on the other item row: changing Adult mustn't touch it.
+        buf[3 * COLS] = AChar::from_ascii_char(b'x');
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            assert!(view.set_qty(&mut c, 0, 4));
+            assert!(!view.set_qty(&mut c, 0, 4));
+        }
+        let rows = text(&buf);
+        assert_eq!(rows[2], "Adult    4    L2.50    L10.00.");
+        assert_eq!(rows[3], "xhild    1    L1.25     L1.25.");
+        assert_eq!(rows[5], "Total                  L13.25.");
+
+        // Taking Adult out moves the rest up, and the pages down to one.
+        assert!(view.set_qty(&mut Cursor::new(&mut buf, COLS), 0, 0));
+        let rows = text(&buf);
+        assert_eq!(rows[2], "Child    1    L1.25     L1.25.");
+        assert_eq!(rows[3], "Senior   1    L2.00     L2.00.");
+        assert_eq!(rows[4], "-----------------------------.");
+
+        view.headings = [b"Billet", b"Qt.", b"Prix", b"Somme"];
+        view.redraw(&mut Cursor::new(&mut buf, COLS));
+        let rows = text(&buf);
+        assert_eq!(rows[0], "Billet Qt.     Prix     Somme.");
+        assert_eq!(rows[5], "Somme                   L3.25.");
+    }
+}
@@ -0,0 +1,308 @@
+//! Simple charts built from character cells.
+//!
+//! A bar is a run of cells split into a filled part, proportional to the value
+//! being shown, and an empty part making up the rest of the bar's length. Both
+//! parts are drawn in the cursor's current colors.
+//!
+//! A sparkline squeezes a whole series of samples into one row instead, with
+//! each cell's glyph showing roughly how large its sample is.
+
+use crate::banner::FULL_BLOCK;
+use crate::cursor::Cursor;
+
+/// CP437 glyph used for the empty part of a bar (`░`).
+pub const LIGHT_SHADE: u8 = 0xB0;
+
+/// Returns how many of `len` cells represent `value` out of `max`, rounded to
+/// the nearest cell. Values above `max` fill the whole bar, and a `max` of
+/// zero gives an empty bar.
+pub fn bar_fill(len: usize, value: u32, max: u32) -> usize {
+    if max == 0 {
+        return 0;
+    }
+    let value = u64::from(u32::min(value, max));
+    let max = u64::from(max);
+    ((value * len as u64 + max / 2) / max) as usize
+}
+
+/// Draws a horizontal bar `width` cells long, starting at `row`, `col` and
+/// growing to the right, showing `value` out of `max`. See `bar_fill`.
+///
+/// Anything outside the window is clipped. The cursor is not moved.
+pub fn draw_bar(
+    c: &mut Cursor,
+    row: usize,
+    col: usize,
+    width: usize,
+    value: u32,
+    max: u32,
+) {
+    let filled = bar_fill(width, value, max);
+    c.hline(row, col, filled, FULL_BLOCK);
+    c.hline(row, col + filled, width - filled, LIGHT_SHADE);
+}
+
+/// Draws a bar like `draw_bar`, but using the end of the `width` cells to
+/// print `value` itself, after a blank cell. The bar gets whatever space the
+/// number leaves, which may be none.
+pub fn draw_bar_with_value(
+    c: &mut Cursor,
+    row: usize,
+    col: usize,
+    width: usize,
+    value: u32,
+    max: u32,
+) {
+    if width == 0 {
+        return;
+    }
+    let bar = width.saturating_sub(digits(value) + 1);
+    draw_bar(c, row, col, bar, value, max);
+    c.fill_rect(row, col + bar, 1, width - bar, b' ');
+    // In a window of its own so that a number too long to fit is cut off on
+    // the left, rather than spilling over whatever is left of the bar.
+    let mut number = c.window(row, col + bar, 1, width - bar);
+    let right_col = width - bar - 1;
+    number
+        .write_right(0, right_col, format_args!("{}", value))
+        .ok();
+}
+
+/// Draws a vertical bar `height` cells tall, with its bottom cell at `row`,
+/// `col` and growing upward, showing `value` out of `max`. See `bar_fill`.
+///
+/// Anything outside the window is clipped, including the part of a bar that
+/// would reach above row 0. The cursor is not moved.
+pub fn draw_column(
+    c: &mut Cursor,
+    row: usize,
+    col: usize,
+    height: usize,
+    value: u32,
+    max: u32,
+) {
+    let filled = bar_fill(height, value, max);
+    for i in 0..height {
+        if let Some(r) = row.checked_sub(i) {
+            let ch = if i < filled { FULL_BLOCK } else { LIGHT_SHADE };
+            c.vline(r, col, 1, ch);
+        }
+    }
+}
+
+/// Glyphs of increasing height used by `draw_sparkline`: underscore, dash,
+/// upper half block and full block.
+pub const SPARK_LEVELS: [u8; 4] = [b'_', b'-', 0xDF, FULL_BLOCK];
+
+/// Draws `samples` as a sparkline `width` cells long, starting at `row`, `col`,
+/// with the first sample on the left. Each cell shows its sample scaled from
+/// the range 0-255 onto `SPARK_LEVELS`.
+///
+/// If there are more samples than cells, each cell shows the largest of the
+/// samples that fall in it, so short spikes aren't lost. If there are fewer,
+/// they are drawn at the right end, so the newest sample of a `SampleRing`
+/// stays in the same place, and the cells to their left are blanked.
+///
+/// Anything outside the window is clipped. The cursor is not moved.
+pub fn draw_sparkline(
+    c: &mut Cursor,
+    row: usize,
+    col: usize,
+    samples: &[u8],
+    width: usize,
+) {
+    let n = samples.len();
+    let blank = width.saturating_sub(n);
+    c.hline(row, col, blank, b' ');
+    let cells = width - blank;
+    for i in 0..cells {
+        let bucket = &samples[i * n / cells..(i + 1) * n / cells];
+        let peak = bucket.iter().cloned().max().unwrap_or(0);
+        let level = usize::from(peak) * SPARK_LEVELS.len() / 256;
+        c.hline(row, col + blank + i, 1, SPARK_LEVELS[level]);
+    }
+}
+
+/// Number of samples kept by a `SampleRing`.
+pub const RING_LEN: usize = 128;
+
+/// The last `RING_LEN` samples pushed, for feeding `draw_sparkline`.
+///
+/// Each sample is stored twice, `RING_LEN` bytes apart, so that the samples
+/// can always be read back oldest-first as one slice without copying.
+pub struct SampleRing {
+    buf: [u8; 2 * RING_LEN],
+    /// Index in `0..RING_LEN` where the next sample goes.
+    next: usize,
+    /// Number of valid samples, up to `RING_LEN`.
+    len: usize,
+}
+
+impl SampleRing {
+    pub const fn new() -> Self {
+        SampleRing {
+            buf: [0; 2 * RING_LEN],
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Adds a sample, dropping the oldest one if the ring is full.
+    pub fn push(&mut self, sample: u8) {
+        self.buf[self.next] = sample;
+        self.buf[self.next + RING_LEN] = sample;
+        self.next = (self.next + 1) % RING_LEN;
+        self.len = usize::min(self.len + 1, RING_LEN);
+    }
+
+    /// Returns the samples, oldest first.
+    pub fn as_slice(&self) -> &[u8] {
+        let start = (self.next + RING_LEN - self.len) % RING_LEN;
+        &self.buf[start..start + self.len]
+    }
+
+    /// Returns the newest `n` samples, or all of them if there are fewer,
+    /// oldest first.
+    pub fn last(&self, n: usize) -> &[u8] {
+        let all = self.as_slice();
+        &all[all.len().saturating_sub(n)..]
+    }
+}
+
+impl Default for SampleRing {
+    fn default() -> Self {
+        SampleRing::new()
+    }
+}
+
+/// Returns the number of decimal digits in `value`.
+fn digits(mut value: u32) -> usize {
+    let mut n = 1;
+    while value >= 10 {
+        value /= 10;
+        n += 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 12;
+    const ROWS: usize = 4;
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        FULL_BLOCK => '#',
+                        LIGHT_SHADE => '~',
+                        0xDF => '^',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fill_rounds_and_clamps() {
+        assert_eq!(bar_fill(10, 0, 100), 0);
+        assert_eq!(bar_fill(10, 4, 100), 0);
+        assert_eq!(bar_fill(10, 5, 100), 1);
+        assert_eq!(bar_fill(10, 50, 100), 5);
+        assert_eq!(bar_fill(10, 100, 100), 10);
+        assert_eq!(bar_fill(10, 250, 100), 10);
+        assert_eq!(bar_fill(10, 7, 0), 0);
+        // Large enough to overflow a u32 product.
+        assert_eq!(bar_fill(80, 4_000_000_000, 4_000_000_000), 80);
+    }
+
+    #[test]
+    fn horizontal_bars() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            draw_bar(&mut c, 0, 1, 10, 3, 10);
+            draw_bar(&mut c, 1, 6, 10, 1, 1);
+            draw_bar_with_value(&mut c, 2, 0, COLS, 42, 100);
+            draw_bar_with_value(&mut c, 3, 0, 4, 12345, 100);
+            assert_eq!(c.position(), (0, 0));
+        }
+        assert_eq!(
+            text(&buf),
+            [
+                ".###~~~~~~~.",
+                "......######",
+                "####~~~~~ 42",
+                "2345........",
+            ]
+        );
+    }
+
+    #[test]
+    fn sparkline_levels_and_blanks() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let ramp = [0, 63, 64, 127, 128, 191, 192, 255];
+            draw_sparkline(&mut c, 0, 0, &ramp, 10);
+            // Twice as many samples as cells: each cell shows the larger.
+            draw_sparkline(&mut c, 1, 1, &[0, 0, 0, 200, 100, 10, 255, 0], 4);
+            draw_sparkline(&mut c, 2, 2, &[255; 3], 5);
+            draw_sparkline(&mut c, 3, 0, &[], 3);
+        }
+        assert_eq!(
+            text(&buf),
+            [
+                "  __--^^##..",
+                "._#-#.......",
+                "..  ###.....",
+                "   .........",
+            ]
+        );
+    }
+
+    #[test]
+    fn ring_keeps_newest_samples_in_order() {
+        let mut ring = SampleRing::new();
+        assert_eq!(ring.as_slice(), &[] as &[u8]);
+        for i in 0..5 {
+            ring.push(i);
+        }
+        assert_eq!(ring.as_slice(), &[0, 1, 2, 3, 4]);
+        assert_eq!(ring.last(2), &[3, 4]);
+        for i in 5..(RING_LEN as u8 + 7) {
+            ring.push(i);
+        }
+        let all = ring.as_slice();
+        assert_eq!(all.len(), RING_LEN);
+        assert_eq!(all[0], 7);
+        assert_eq!(all[RING_LEN - 1], RING_LEN as u8 + 6);
+        assert!(all.windows(2).all(|w| w[1] == w[0] + 1));
+        assert_eq!(ring.last(RING_LEN + 1).len(), RING_LEN);
+    }
+
+    #[test]
+    fn vertical_bars() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            draw_column(&mut c, 3, 0, 4, 2, 4);
+            draw_column(&mut c, 3, 1, 4, 9, 4);
+            draw_column(&mut c, 1, 2, 4, 4, 4);
+        }
+        assert_eq!(
+            text(&buf),
+            [
+                "~##.........",
+                "~##.........",
+                "##..........",
+                "##..........",
+            ]
+        );
+    }
+}
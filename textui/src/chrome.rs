@@ -0,0 +1,526 @@
+//! Screen furniture shared by full-screen layouts.
+//!
+//! These helpers draw the parts of a screen that stay in the same place from
+//! one screen to the next, so that every screen draws them the same way.
+
+use crate::color::fill_gradient;
+use crate::cursor::{BoxStyle, Cursor, Rect};
+use crate::widget::{Button, ButtonStyle, Header, MessageBox, Widget};
+
+/// Number of rows at the top of the window taken by `draw_title_bar`.
+pub const TITLE_ROWS: usize = 3;
+
+/// Number of rows at the top of the window taken by a `widget::Header` with
+/// a subtitle, the last of them being where the screen border's divider
+/// goes. Nothing else on a screen should start above this row.
+pub const HEADER_ROWS: usize = TITLE_ROWS + 1;
+
+/// Colors of the status row drawn by `draw_status_row`.
+pub const STATUS_FG: m4vga::Pixel = 0b11_11_11;
+pub const STATUS_BG: m4vga::Pixel = 0b00_00_11;
+
+/// Number of cells at the right end of the status row reserved for the value
+/// drawn by `draw_status_value`, including a cell of margin.
+pub const STATUS_VALUE_W: usize = 16;
+
+/// Returns a window covering everything but the status row at the bottom.
+///
+/// Screens should draw through this window rather than the whole buffer, so
+/// that the status row survives screen changes and only needs to be drawn
+/// once.
+pub fn screen_area<'c>(c: &'c mut Cursor) -> Cursor<'c> {
+    let (height, width) = c.size();
+    c.window(0, 0, height.saturating_sub(1), width)
+}
+
+/// Draws a title bar across the top `TITLE_ROWS` rows of the window, with
+/// `title` centered on the middle row in `fg`. The background fades from `bg`
+/// at the left edge to black at the right; see `color::fill_gradient`.
+///
+/// The cursor's colors are left as they were.
+pub fn draw_title_bar(
+    c: &mut Cursor,
+    title: &[u8],
+    bg: m4vga::Pixel,
+    fg: m4vga::Pixel,
+) {
+    let (_, width) = c.size();
+    let mut c = c.styled(fg, bg);
+    fill_gradient(&mut c, Rect::new(0, 0, TITLE_ROWS, width), bg, 0);
+    let transparent = c.bg_transparent;
+    c.bg_transparent = true;
+    c.puts_centered(TITLE_ROWS / 2, title);
+    c.bg_transparent = transparent;
+}
+
+/// Frames the whole window using the glyphs from `style`, with a divider
+/// under the title bar drawn by `draw_title_bar`.
+///
+/// Pass the window from `screen_area` so the frame stops above the status
+/// row. Only the frame's own cells are written, in the current foreground
+/// color; each keeps the background already there, so the frame can be drawn
+/// after the title bar and any backdrop without clashing with them.
+pub fn draw_screen_border(c: &mut Cursor, style: &BoxStyle) {
+    draw_screen_border_under(c, style, TITLE_ROWS)
+}
+
+/// Frames the whole window like `draw_screen_border`, but with the divider
+/// on the last of the top `header_rows` rows, to go under a header of that
+/// height; see `widget::Header::rows`.
+pub fn draw_screen_border_under(
+    c: &mut Cursor,
+    style: &BoxStyle,
+    header_rows: usize,
+) {
+    let (height, width) = c.size();
+    let transparent = c.bg_transparent;
+    c.bg_transparent = true;
+    c.draw_box(0, 0, height, width, style);
+    // Skip the divider if it would land on the bottom edge.
+    if header_rows > 0 && height > header_rows {
+        let row = header_rows - 1;
+        c.hline(row, 0, 1, style.tee_left);
+        c.hline(row, 1, width.saturating_sub(2), style.top);
+        c.hline(row, width.saturating_sub(1), 1, style.tee_right);
+    }
+    c.bg_transparent = transparent;
+}
+
+/// Draws the status row at the bottom of the window, with `left` as a label
+/// at the left end and `right` as a value at the right end.
+///
+/// The cursor's colors are left as they were.
+pub fn draw_status_row(c: &mut Cursor, left: &[u8], right: &[u8]) {
+    let (height, width) = c.size();
+    let row = height.saturating_sub(1);
+    {
+        let mut c = c.styled(STATUS_FG, STATUS_BG);
+        c.fill_row(row, b' ');
+        c.goto(row, 1);
+        c.puts_truncated(left, width.saturating_sub(STATUS_VALUE_W + 1));
+    }
+    draw_status_value(c, right);
+}
+
+/// Replaces the value at the right end of the status row, leaving the label
+/// alone. Values longer than `STATUS_VALUE_W - 1` are cut off on the left.
+///
+/// This is cheap enough to call every frame. The cursor's colors are left as
+/// they were.
+pub fn draw_status_value(c: &mut Cursor, right: &[u8]) {
+    let mut value = status_value(c);
+    let right_col = value.size().1.saturating_sub(1);
+    value.puts_right(0, right_col, right);
+}
+
+/// Clears the value at the right end of the status row and returns a window
+/// over it, in the status colors, for formatting a value with `write!`.
+///
+/// Write through `Cursor::truncating` so that a value that turns out longer
+/// than the window is cut short instead of failing:
+///
+/// ```ignore
+/// write!(status_value(&mut c).truncating(usize::MAX), "{:03b}", bits).ok();
+/// ```
+pub fn status_value<'c>(c: &'c mut Cursor) -> Cursor<'c> {
+    let (height, width) = c.size();
+    let row = height.saturating_sub(1);
+    let col = width.saturating_sub(STATUS_VALUE_W);
+    c.styled(STATUS_FG, STATUS_BG)
+        .fill_rect(row, col, 1, STATUS_VALUE_W, b' ');
+    // Keep the margin cell at the right end out of reach.
+    let mut value = c.window(row, col, 1, STATUS_VALUE_W.saturating_sub(1));
+    value.fg = STATUS_FG;
+    value.bg = STATUS_BG;
+    value
+}
+
+/// One of the four screen corners, each with a physical button next to it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    /// All the corners, in reading order, which is also the order of their
+    /// bits.
+    pub const ALL: [Corner; 4] = [
+        Corner::TopLeft,
+        Corner::TopRight,
+        Corner::BottomLeft,
+        Corner::BottomRight,
+    ];
+
+    /// Returns the bit that this corner's button sets in the word read from
+    /// the button inputs, GPIO bit 0 being the top left button.
+    pub const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Returns the corner whose button sets the lowest bit set in `bits`, if
+    /// any of them is.
+    pub fn from_bits(bits: u8) -> Option<Corner> {
+        Corner::ALL.iter().cloned().find(|c| bits & c.bit() != 0)
+    }
+}
+
+/// Size of each of the `SoftKeys` buttons.
+pub const SOFT_KEY_H: usize = 3;
+pub const SOFT_KEY_W: usize = 6;
+
+/// Rows of the top and bottom pairs of `SoftKeys` on a full-height screen,
+/// level with the physical buttons. Both are clear of the `HEADER_ROWS` at
+/// the top and the status row at the bottom.
+pub const SOFT_KEY_ROWS: [usize; 2] = [10, 25];
+
+/// Labels for the physical buttons by the screen corners, drawn as buttons
+/// right next to them.
+///
+/// Every screen draws its soft keys in the same places and the same style;
+/// it only chooses the labels. Corners with no label are blanked, so nothing
+/// from the screen before suggests a button does something it doesn't.
+#[derive(Copy, Clone, Debug)]
+pub struct SoftKeys<'t> {
+    /// Labels in the order of `Corner::ALL`.
+    pub labels: [Option<&'t [u8]>; 4],
+    pub style: &'t ButtonStyle,
+    /// Looks of particular keys that stand out from the rest, in the order
+    /// of `Corner::ALL`; the others use `style`.
+    pub styles: [Option<&'t ButtonStyle>; 4],
+    /// Rows of the top and bottom pairs of keys.
+    pub rows: [usize; 2],
+}
+
+impl<'t> SoftKeys<'t> {
+    /// Makes a set of keys with no labels, at `SOFT_KEY_ROWS`.
+    pub const fn new(style: &'t ButtonStyle) -> Self {
+        SoftKeys {
+            labels: [None; 4],
+            style,
+            styles: [None; 4],
+            rows: SOFT_KEY_ROWS,
+        }
+    }
+
+    /// Returns the keys with `labels` assigned, in the order of
+    /// `Corner::ALL`.
+    pub const fn with_labels(self, labels: [Option<&'t [u8]>; 4]) -> Self {
+        SoftKeys { labels, ..self }
+    }
+
+    /// Assigns `label` to the key at `corner`, or unassigns it.
+    pub fn set(&mut self, corner: Corner, label: Option<&'t [u8]>) {
+        self.labels[corner as usize] = label;
+    }
+
+    pub fn label(&self, corner: Corner) -> Option<&'t [u8]> {
+        self.labels[corner as usize]
+    }
+
+    /// Gives the key at `corner` a look of its own, or `None` for `style`.
+    pub fn set_style(
+        &mut self,
+        corner: Corner,
+        style: Option<&'t ButtonStyle>,
+    ) {
+        self.styles[corner as usize] = style;
+    }
+
+    /// Returns where the key at `corner` goes in a window `width` columns
+    /// wide, one column in from the side to clear the screen border.
+    pub fn rect(&self, corner: Corner, width: usize) -> Rect {
+        let row = self.rows[corner as usize / 2];
+        let band = Rect::new(row, 0, SOFT_KEY_H, width).inset(1);
+        let (left, _) = band.split_h(SOFT_KEY_W);
+        let (_, right) = band.split_h(band.w.saturating_sub(SOFT_KEY_W));
+        let key = match corner {
+            Corner::TopLeft | Corner::BottomLeft => left,
+            _ => right,
+        };
+        Rect::new(row, key.col, SOFT_KEY_H, key.w)
+    }
+
+    /// Draws the keys with labels, and blanks the others in the current
+    /// colors. Pass the window from `screen_area`.
+    pub fn draw(&self, c: &mut Cursor) {
+        let (_, width) = c.size();
+        for &corner in Corner::ALL.iter() {
+            let rect = self.rect(corner, width);
+            let style = self.styles[corner as usize].unwrap_or(self.style);
+            match self.label(corner) {
+                Some(label) => Button::new(label, rect, style).redraw(c),
+                None => c.fill_rect(rect.row, rect.col, rect.h, rect.w, b' '),
+            }
+        }
+    }
+}
+
+/// Blank cells between the text of a `ScreenSpec`'s message and the edge of
+/// its box, all round.
+pub const MESSAGE_PAD: usize = 1;
+
+/// Everything on a simple screen, to be drawn by `render_spec`: a header, a
+/// message in a box in the middle, and keys by any of the buttons.
+///
+/// A spec can be written out as a `static`, so a screen made of only these
+/// needs no code of its own. Screens with more on them draw the rest after
+/// `render_spec`.
+#[derive(Copy, Clone, Debug)]
+pub struct ScreenSpec<'t> {
+    pub header: Header<'t>,
+    /// Frame round the screen, with its divider under the header.
+    pub border: BoxStyle,
+    /// Colors of the border, the blank corners, and anything drawn over the
+    /// screen afterwards.
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Glyph the screen is filled with before anything else is drawn, and
+    /// its foreground. Usually a blank.
+    pub fill: u8,
+    pub fill_fg: m4vga::Pixel,
+    /// Message centered inside the border, under the header, in a box
+    /// `MESSAGE_PAD` cells bigger than its text.
+    pub message: Option<MessageBox<'t>>,
+    /// Keys by the buttons, or `None` to leave the corners filled like the
+    /// rest of the screen.
+    pub keys: Option<SoftKeys<'t>>,
+}
+
+impl<'t> ScreenSpec<'t> {
+    /// Returns where the message box goes on a screen `height` by `width`,
+    /// if there is a message.
+    pub fn message_rect(&self, height: usize, width: usize) -> Option<Rect> {
+        let (h, w) = self.message?.min_size();
+        let rows = self.header.rows();
+        let inside = Rect::new(
+            rows,
+            1,
+            height.saturating_sub(rows + 1),
+            width.saturating_sub(2),
+        );
+        let pad = 2 * MESSAGE_PAD;
+        Some(Rect::center_within(&inside, w + pad, h + pad))
+    }
+}
+
+/// Draws the screen `spec` describes over the whole window, leaving the
+/// cursor in the spec's colors. Pass the window from `screen_area`.
+pub fn render_spec(c: &mut Cursor, spec: &ScreenSpec) {
+    let (height, width) = c.size();
+    c.fg = spec.fill_fg;
+    c.bg = spec.bg;
+    c.fill_rect(0, 0, height, width, spec.fill);
+    c.fg = spec.fg;
+    let rows = spec.header.rows();
+    spec.header.draw(c, Rect::new(0, 0, rows, width));
+    draw_screen_border_under(c, &spec.border, rows);
+    if let (Some(message), Some(rect)) =
+        (spec.message, spec.message_rect(height, width))
+    {
+        message.draw(c, rect);
+    }
+    if let Some(keys) = spec.keys {
+        keys.draw(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    #[test]
+    fn title_bar_restores_colors() {
+        let mut buf = [AChar::from_ascii_char(b'.'); 8 * 4];
+        {
+            let mut c = Cursor::new(&mut buf, 8);
+            c.fg = 1;
+            c.bg = 2;
+            draw_title_bar(&mut c, b"Hi", 3, 4);
+            assert_eq!((c.fg, c.bg), (1, 2));
+        }
+        let text: String = buf.iter().map(|a| a.char()).collect();
+        assert_eq!(&text[8..16], "   Hi   ");
+        assert_eq!(&text[16..], "        ........");
+        let fade = [3, 3, 2, 2, 1, 1, 0, 0];
+        for (i, a) in buf[..24].iter().enumerate() {
+            assert_eq!(a.background(), fade[i % 8]);
+        }
+        assert_eq!(buf[11].foreground(), 4);
+    }
+
+    #[test]
+    fn screen_border_keeps_backgrounds() {
+        let mut buf = [AChar::from_ascii_char(b'.'); 6 * 6];
+        {
+            let mut c = Cursor::new(&mut buf, 6);
+            draw_status_row(&mut c, b"", b"");
+            let mut screen = screen_area(&mut c);
+            screen.bg = 1;
+            screen.clear();
+            draw_title_bar(&mut screen, b"T", 2, 3);
+            draw_screen_border(&mut screen, &BoxStyle::ASCII);
+            assert!(!screen.bg_transparent);
+        }
+        let text: String = buf.iter().map(|a| a.char()).collect();
+        assert_eq!(&text[..30], "+----+| T  |+----+|    |+----+");
+        let fade = [2, 2, 1, 1, 0, 0];
+        for (i, a) in buf[..18].iter().enumerate() {
+            assert_eq!(a.background(), fade[i % 6]);
+        }
+        assert!(buf[18..30].iter().all(|a| a.background() == 1));
+        assert!(buf[30..].iter().all(|a| a.background() == STATUS_BG));
+    }
+
+    fn row_text(buf: &[AChar], row: usize) -> String {
+        buf[row * 24..(row + 1) * 24]
+            .iter()
+            .map(|a| a.char())
+            .collect()
+    }
+
+    #[test]
+    fn status_row_survives_screen_area() {
+        let mut buf = [AChar::from_ascii_char(b'.'); 24 * 3];
+        {
+            let mut c = Cursor::new(&mut buf, 24);
+            draw_status_row(&mut c, b"Label", b"12345");
+            let mut screen = screen_area(&mut c);
+            assert_eq!(screen.size(), (2, 24));
+            screen.clear();
+            screen.fill_rows(0, 10, b'#');
+        }
+        assert_eq!(row_text(&buf, 1), "########################");
+        assert_eq!(row_text(&buf, 2), " Label            12345 ");
+        assert!(buf[48..].iter().all(|a| a.background() == STATUS_BG));
+    }
+
+    #[test]
+    fn status_value_replaces_only_value() {
+        let mut buf = [AChar::from_ascii_char(b'.'); 24 * 3];
+        {
+            let mut c = Cursor::new(&mut buf, 24);
+            draw_status_row(&mut c, b"A long label text", b"12345");
+            draw_status_value(&mut c, b"9");
+        }
+        let mark = crate::cursor::TRUNCATION_MARK as char;
+        let expected = format!(" A long{}{:>16}", mark, "9 ");
+        assert_eq!(row_text(&buf, 2), expected);
+    }
+
+    #[test]
+    fn formatted_status_value_is_cut_short() {
+        use core::fmt::Write;
+
+        let mut buf = [AChar::from_ascii_char(b'.'); 24 * 3];
+        {
+            let mut c = Cursor::new(&mut buf, 24);
+            draw_status_row(&mut c, b"Label", b"12345");
+            let mut value = status_value(&mut c);
+            let r = write!(value.truncating(usize::MAX), "{}", 1u64 << 63);
+            assert!(r.is_ok());
+        }
+        assert_eq!(row_text(&buf, 2), " Label  922337203685477 ");
+        assert!(buf[48..].iter().all(|a| a.background() == STATUS_BG));
+    }
+
+    #[test]
+    fn soft_keys_match_corner_bits() {
+        static STYLE: ButtonStyle = ButtonStyle {
+            normal: (1, 2),
+            focused: (2, 1),
+            pressed: (1, 3),
+            frame: BoxStyle::ASCII,
+        };
+        let mut keys = SoftKeys::new(&STYLE).with_labels([
+            Some(b"A"),
+            None,
+            Some(b"LONG"),
+            Some(b"B"),
+        ]);
+        keys.rows = [0, 3];
+        keys.set(Corner::BottomRight, None);
+        assert_eq!(keys.label(Corner::BottomLeft), Some(&b"LONG"[..]));
+        assert_eq!(Corner::from_bits(0b1100), Some(Corner::BottomLeft));
+        assert_eq!(Corner::from_bits(0), None);
+        assert_eq!(Corner::BottomRight.bit(), 0b1000);
+
+        let mut buf = [AChar::from_ascii_char(b'.'); 16 * 6];
+        keys.draw(&mut Cursor::new(&mut buf, 16));
+        let rows: Vec<String> = buf
+            .chunks(16)
+            .map(|row| row.iter().map(|a| a.char()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ".+----+..      .",
+                ".| A  |..      .",
+                ".+----+..      .",
+                ".+----+..      .",
+                ".|LONG|..      .",
+                ".+----+..      .",
+            ]
+        );
+        assert_eq!(buf[17].background(), 2);
+    }
+
+    #[test]
+    fn spec_fills_frames_and_centers_message() {
+        use crate::widget::HeaderStyle;
+        static SPEC: ScreenSpec = ScreenSpec {
+            header: Header {
+                title: b"Hi",
+                subtitle: None,
+                style: HeaderStyle {
+                    fg: 1,
+                    bg: 2,
+                    subtitle_fg: 1,
+                },
+            },
+            border: BoxStyle::ASCII,
+            fg: 1,
+            bg: 2,
+            fill: b':',
+            fill_fg: 3,
+            message: Some(MessageBox {
+                lines: &[b"ok"],
+                fg: 4,
+                bg: 5,
+                shadow: false,
+            }),
+            keys: None,
+        };
+        const COLS: usize = 16;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * 8];
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            render_spec(&mut c, &SPEC);
+            assert_eq!((c.fg, c.bg), (1, 2));
+        }
+        let rows: Vec<String> = buf
+            .chunks(COLS)
+            .map(|row| row.iter().map(|a| a.char()).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "+--------------+",
+                "|      Hi      |",
+                "+--------------+",
+                "|:::::    :::::|",
+                "|::::: ok :::::|",
+                "|:::::    :::::|",
+                "|::::::::::::::|",
+                "+--------------+",
+            ]
+        );
+        assert_eq!(SPEC.message_rect(8, COLS), Some(Rect::new(3, 6, 3, 4)));
+        assert_eq!(buf[6 * COLS + 1].foreground(), 3);
+        assert_eq!(buf[6 * COLS].foreground(), 1);
+        assert_eq!(buf[4 * COLS + 7].background(), 5);
+    }
+}
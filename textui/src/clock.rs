@@ -0,0 +1,507 @@
+//! Widgets that show the time.
+//!
+//! Nothing here reads a clock. The time comes from a `TimeSource`, which for
+//! now is a `SecondsCounter` that the main loop advances once a frame; a real
+//! time clock can stand in for it later without the widgets changing.
+
+use crate::cursor::{Cursor, Rect};
+use crate::widget::Widget;
+
+/// Number of seconds in a day.
+pub const DAY_SECONDS: u32 = 24 * 60 * 60;
+
+/// Something that knows the time of day.
+pub trait TimeSource {
+    /// Returns the number of seconds since midnight. Values of a day or more
+    /// are taken modulo `DAY_SECONDS`.
+    fn seconds(&self) -> u32;
+}
+
+/// A plain number of seconds is its own time source.
+impl TimeSource for u32 {
+    fn seconds(&self) -> u32 {
+        *self
+    }
+}
+
+/// Time kept by counting frames, `fps` to the second.
+#[derive(Copy, Clone, Debug)]
+pub struct SecondsCounter {
+    seconds: u32,
+    frames: u32,
+    fps: u32,
+}
+
+impl SecondsCounter {
+    /// Makes a counter starting at `seconds` past midnight, to be ticked
+    /// `fps` times a second.
+    pub const fn new(seconds: u32, fps: u32) -> Self {
+        SecondsCounter {
+            seconds,
+            frames: 0,
+            fps,
+        }
+    }
+
+    /// Counts one frame, returning whether that started a new second.
+    pub fn tick(&mut self) -> bool {
+        self.frames += 1;
+        if self.frames < self.fps {
+            return false;
+        }
+        self.frames = 0;
+        self.seconds = (self.seconds + 1) % DAY_SECONDS;
+        true
+    }
+}
+
+impl TimeSource for SecondsCounter {
+    fn seconds(&self) -> u32 {
+        self.seconds
+    }
+}
+
+/// Longest text `format_time` produces, `12:00:00 AM`.
+pub const TIME_W: usize = 11;
+
+/// Writes `seconds` past midnight into `buf` as `HH:MM:SS`, or with
+/// `twelve_hour` as `HH:MM:SS AM`, returning the part used.
+pub fn format_time(
+    seconds: u32,
+    twelve_hour: bool,
+    buf: &mut [u8; TIME_W],
+) -> &[u8] {
+    let seconds = seconds % DAY_SECONDS;
+    let mut hours = seconds / 3600;
+    if twelve_hour {
+        buf[8..].copy_from_slice(if hours < 12 { b" AM" } else { b" PM" });
+        hours %= 12;
+        if hours == 0 {
+            hours = 12;
+        }
+    }
+    let fields = [hours, seconds / 60 % 60, seconds % 60];
+    for (i, &field) in fields.iter().enumerate() {
+        buf[i * 3] = b'0' + (field / 10) as u8;
+        buf[i * 3 + 1] = b'0' + (field % 10) as u8;
+        if i < 2 {
+            buf[i * 3 + 2] = b':';
+        }
+    }
+    if twelve_hour {
+        &buf[..]
+    } else {
+        &buf[..8]
+    }
+}
+
+/// Time of day drawn as `HH:MM:SS`, left-aligned in its area.
+///
+/// Call `update` every frame: it only draws when the time shown would
+/// change, so most frames it writes nothing at all.
+#[derive(Copy, Clone, Debug)]
+pub struct Clock {
+    pub rect: Rect,
+    /// Whether to show the time as `HH:MM:SS AM` rather than 24-hour time.
+    pub twelve_hour: bool,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Seconds past midnight currently on screen, if anything is.
+    shown: Option<u32>,
+}
+
+impl Clock {
+    /// Makes a 24-hour clock in white on black that hasn't been drawn yet.
+    pub const fn new(rect: Rect) -> Self {
+        Clock {
+            rect,
+            twelve_hour: false,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            shown: None,
+        }
+    }
+
+    /// Draws the time from `source` at the clock's `rect`, unless it is
+    /// already on screen. Returns whether anything was drawn.
+    pub fn update(&mut self, c: &mut Cursor, source: &dyn TimeSource) -> bool {
+        let seconds = source.seconds() % DAY_SECONDS;
+        if self.shown == Some(seconds) {
+            return false;
+        }
+        self.shown = Some(seconds);
+        self.draw(c, self.rect);
+        true
+    }
+
+    /// Forgets what is on screen, so that the next `update` draws. Use this
+    /// after clearing the clock's cells, or after changing how it looks.
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+}
+
+impl Widget for Clock {
+    /// Draws the time last passed to `update`, or dashes if there hasn't
+    /// been one.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut buf = [b'-'; TIME_W];
+        let text: &[u8] = match self.shown {
+            Some(seconds) => format_time(seconds, self.twelve_hour, &mut buf),
+            None if self.twelve_hour => b"--:--:--   ",
+            None => b"--:--:--",
+        };
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        w.puts_at(0, 0, text).ok();
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, if self.twelve_hour { TIME_W } else { 8 })
+    }
+}
+
+/// Color a `Countdown` turns when it gets down to its last seconds.
+pub const WARN_FG: m4vga::Pixel = 0b00_00_11;
+
+/// Seconds left at which a `Countdown` turns `warn_fg`.
+pub const WARN_SECONDS: u32 = 10;
+
+/// Time left to do something, drawn as `M:SS`, left-aligned in its area.
+///
+/// The countdown is kept in frames and driven by the main loop, which calls
+/// `tick` once a frame and `redraw`s when it says to. Seconds are rounded up,
+/// so `0:00` only shows once time is up.
+#[derive(Copy, Clone, Debug)]
+pub struct Countdown {
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Foreground used instead of `fg` under `WARN_SECONDS` seconds.
+    pub warn_fg: m4vga::Pixel,
+    /// Frames to count down from, as restored by `reset`.
+    duration: u32,
+    /// Frames left.
+    remaining: u32,
+    fps: u32,
+    paused: bool,
+}
+
+impl Countdown {
+    /// Makes a running countdown of `frames` frames, `fps` to the second, in
+    /// white on black.
+    pub const fn new(rect: Rect, frames: u32, fps: u32) -> Self {
+        Countdown {
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            warn_fg: WARN_FG,
+            duration: frames,
+            remaining: frames,
+            fps,
+            paused: false,
+        }
+    }
+
+    /// Makes a running countdown of `seconds` seconds, `fps` frames to the
+    /// second.
+    pub const fn from_seconds(rect: Rect, seconds: u32, fps: u32) -> Self {
+        Countdown::new(rect, seconds * fps, fps)
+    }
+
+    /// Counts down one frame, unless paused or expired. Returns whether the
+    /// time shown changed and so needs a `redraw`.
+    pub fn tick(&mut self) -> bool {
+        if self.paused || self.remaining == 0 {
+            return false;
+        }
+        let before = self.seconds_left();
+        self.remaining -= 1;
+        self.seconds_left() != before
+    }
+
+    /// Returns the number of whole or partial seconds left.
+    pub fn seconds_left(&self) -> u32 {
+        let fps = u32::max(self.fps, 1);
+        let whole = self.remaining / fps;
+        if whole * fps < self.remaining {
+            whole + 1
+        } else {
+            whole
+        }
+    }
+
+    /// Returns whether the countdown has run out.
+    pub fn expired(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Stops `tick` from counting down until `resume`.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Puts the full duration back on the countdown. Whether it is paused
+    /// doesn't change.
+    pub fn reset(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    /// Draws the countdown at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl Widget for Countdown {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let seconds = self.seconds_left();
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = if seconds < WARN_SECONDS {
+            self.warn_fg
+        } else {
+            self.fg
+        };
+        w.bg = self.bg;
+        w.clear();
+        w.goto(0, 0);
+        w.set_scroll(false);
+        use core::fmt::Write;
+        write!(w.truncating(area.w), "{}:{:02}", seconds / 60, seconds % 60)
+            .ok();
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, 4)
+    }
+}
+
+/// Time since something happened, drawn as `MM:SS`, or `H:MM:SS` from an
+/// hour on, left-aligned in its area.
+///
+/// Like the `Clock`, call `update` every frame with the main loop's frame
+/// count: it only draws when the time shown would change. The frames are
+/// added up in 64 bits as they go by, so the frame count wrapping around
+/// doesn't upset it, as long as it isn't left for a whole wrap between
+/// updates.
+#[derive(Copy, Clone, Debug)]
+pub struct Elapsed {
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    fps: u32,
+    /// Frame count at the last `reset` or `update`.
+    last_frame: u32,
+    /// Frames counted since the last `reset`.
+    frames: u64,
+    /// Seconds currently on screen, if anything is.
+    shown: Option<u64>,
+}
+
+impl Elapsed {
+    /// Makes a stopwatch counting from frame 0, `fps` frames to the second,
+    /// in white on black.
+    pub const fn new(rect: Rect, fps: u32) -> Self {
+        Elapsed {
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            fps,
+            last_frame: 0,
+            frames: 0,
+            shown: None,
+        }
+    }
+
+    /// Starts counting again from frame `frame_no`. The next `update` draws.
+    pub fn reset(&mut self, frame_no: u32) {
+        self.last_frame = frame_no;
+        self.frames = 0;
+        self.shown = None;
+    }
+
+    /// Returns the number of whole seconds counted up to the last `reset` or
+    /// `update`.
+    pub fn seconds(&self) -> u64 {
+        self.frames / u64::from(u32::max(self.fps, 1))
+    }
+
+    /// Counts the frames up to `frame_no` and draws the time at the
+    /// stopwatch's `rect`, unless it is already on screen. Returns whether
+    /// anything was drawn.
+    pub fn update(&mut self, c: &mut Cursor, frame_no: u32) -> bool {
+        self.frames += u64::from(frame_no.wrapping_sub(self.last_frame));
+        self.last_frame = frame_no;
+        let seconds = self.seconds();
+        if self.shown == Some(seconds) {
+            return false;
+        }
+        self.shown = Some(seconds);
+        self.draw(c, self.rect);
+        true
+    }
+
+    /// Forgets what is on screen, so that the next `update` draws.
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+}
+
+impl Widget for Elapsed {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let seconds = self.seconds();
+        let (minutes, seconds) = (seconds / 60, seconds % 60);
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        w.goto(0, 0);
+        w.set_scroll(false);
+        use core::fmt::Write;
+        let mut out = w.truncating(area.w);
+        if minutes < 60 {
+            write!(out, "{:02}:{:02}", minutes, seconds).ok();
+        } else {
+            let (hours, minutes) = (minutes / 60, minutes % 60);
+            write!(out, "{}:{:02}:{:02}", hours, minutes, seconds).ok();
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, 5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    #[test]
+    fn formats_both_modes() {
+        let mut buf = [0; TIME_W];
+        let cases: [(u32, &str, &str); 5] = [
+            (0, "00:00:00", "12:00:00 AM"),
+            (59, "00:00:59", "12:00:59 AM"),
+            (12 * 3600 + 61, "12:01:01", "12:01:01 PM"),
+            (DAY_SECONDS - 1, "23:59:59", "11:59:59 PM"),
+            (DAY_SECONDS + 9 * 3600, "09:00:00", "09:00:00 AM"),
+        ];
+        for &(seconds, h24, h12) in cases.iter() {
+            assert_eq!(format_time(seconds, false, &mut buf), h24.as_bytes());
+            assert_eq!(format_time(seconds, true, &mut buf), h12.as_bytes());
+        }
+    }
+
+    #[test]
+    fn counter_rolls_over_seconds_and_days() {
+        let mut counter = SecondsCounter::new(DAY_SECONDS - 1, 3);
+        assert!(!counter.tick());
+        assert!(!counter.tick());
+        assert!(counter.tick());
+        assert_eq!(counter.seconds(), 0);
+    }
+
+    #[test]
+    fn clock_only_draws_on_change() {
+        const COLS: usize = 10;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let mut clock = Clock::new(Rect::new(0, 1, 1, 9));
+        let row = |buf: &[AChar]| -> String {
+            buf.iter().map(|a| a.char()).collect()
+        };
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            assert!(clock.update(&mut c, &3723));
+            // Scribble over it: an unchanged time mustn't redraw.
+            c.puts_at(0, 1, b"x").ok();
+            assert!(!clock.update(&mut c, &3723));
+        }
+        assert_eq!(row(&buf), ".x1:02:03 ");
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            clock.invalidate();
+            assert!(clock.update(&mut c, &3723));
+        }
+        assert_eq!(row(&buf), ".01:02:03 ");
+    }
+
+    #[test]
+    fn countdown_rounds_up_and_warns() {
+        const COLS: usize = 6;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let mut timer = Countdown::from_seconds(Rect::new(0, 1, 1, 5), 10, 2);
+        timer.fg = 1;
+        let mut shown = |timer: &Countdown| -> (String, u8) {
+            timer.redraw(&mut Cursor::new(&mut buf, COLS));
+            (buf.iter().map(|a| a.char()).collect(), buf[1].foreground())
+        };
+        assert_eq!(shown(&timer), (".0:10 ".to_string(), 1));
+        assert!(!timer.tick());
+        assert_eq!(shown(&timer), (".0:10 ".to_string(), 1));
+        assert!(timer.tick());
+        assert_eq!(shown(&timer), (".0:09 ".to_string(), WARN_FG));
+
+        timer.pause();
+        assert!(!timer.tick());
+        assert_eq!(timer.seconds_left(), 9);
+        timer.resume();
+        while !timer.expired() {
+            timer.tick();
+        }
+        assert!(!timer.tick());
+        assert_eq!(shown(&timer).0, ".0:00 ");
+
+        timer.reset();
+        assert!(!timer.expired());
+        assert_eq!(
+            Countdown::from_seconds(timer.rect, 75, 1).seconds_left(),
+            75
+        );
+        assert_eq!(
+            shown(&Countdown::from_seconds(timer.rect, 600, 1)).0,
+            ".10:00"
+        );
+    }
+
+    #[test]
+    fn elapsed_survives_frame_count_wrap() {
+        const COLS: usize = 9;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let row = |buf: &[AChar]| -> String {
+            buf.iter().map(|a| a.char()).collect()
+        };
+        let mut watch = Elapsed::new(Rect::new(0, 1, 1, 8), 2);
+        let start = u32::MAX - 2;
+        watch.reset(start);
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            assert!(watch.update(&mut c, start));
+            assert!(!watch.update(&mut c, start.wrapping_add(1)));
+            assert!(watch.update(&mut c, start.wrapping_add(2)));
+            assert_eq!(watch.seconds(), 1);
+            let hour = 2 * 3600;
+            let frames = (0..=hour + 170).step_by(1000).chain(Some(hour + 170));
+            for frame in frames {
+                watch.update(&mut c, start.wrapping_add(2 + frame));
+            }
+        }
+        assert_eq!(row(&buf), ".1:01:26 ");
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            watch.reset(7);
+            assert!(watch.update(&mut c, 7 + 42 * 2));
+        }
+        assert_eq!(row(&buf), ".00:42   ");
+    }
+}
@@ -0,0 +1,138 @@
+//! Block-mosaic codes that stand in for a scannable ticket code.
+//!
+//! This isn't a real QR code and nothing can decode it. The data is hashed
+//! into a seed for a small pseudo-random generator that fills the grid, so
+//! the same data always gives the same pattern, while data differing in a
+//! single byte gives a pattern unrelated to the first.
+
+use crate::banner::FULL_BLOCK;
+use crate::cursor::{Cursor, Rect};
+
+/// Most modules along a side of a code.
+pub const CODE_MAX: usize = 21;
+
+/// Columns per module, making modules about square in 10x16 glyphs.
+pub const MODULE_W: usize = 2;
+
+/// Modules of blank margin around a code.
+pub const QUIET: usize = 1;
+
+/// Side of the solid squares marking three corners of a code, like the
+/// finder patterns of a QR code, and the smallest code that has them.
+const FINDER: usize = 3;
+const FINDER_MIN: usize = 2 * (FINDER + 1) + 1;
+
+/// Returns the number of modules along a side of the code `draw_code` fits
+/// in `rect`, not counting the quiet zone.
+pub fn code_modules(rect: Rect) -> usize {
+    let side = usize::min(rect.h, rect.w / MODULE_W);
+    usize::min(side.saturating_sub(2 * QUIET), CODE_MAX)
+}
+
+/// FNV-1a hash of `data`.
+fn hash(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |h: u32, &b| {
+        (h ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Returns whether the module at `row`, `col` of an `n` by `n` code is part
+/// of a finder square or the blank separating it from the data, and if so,
+/// whether it is on.
+fn finder(n: usize, row: usize, col: usize) -> Option<bool> {
+    if n < FINDER_MIN {
+        return None;
+    }
+    let far = n - FINDER - 1;
+    let r = if row <= FINDER {
+        row
+    } else if row >= far {
+        n - 1 - row
+    } else {
+        return None;
+    };
+    let c = if col <= FINDER {
+        col
+    } else if col >= far {
+        n - 1 - col
+    } else {
+        return None;
+    };
+    // No finder in the bottom right corner.
+    if row >= far && col >= far {
+        return None;
+    }
+    Some(r < FINDER && c < FINDER)
+}
+
+/// Draws a code for `data` in the top left of `rect`: the largest square of
+/// modules that fits, up to `CODE_MAX`, inside a quiet zone of `QUIET`
+/// blank modules. On modules are full blocks in the current foreground and
+/// off modules blanks in the current background, so draw dark on light, as
+/// printed codes are. The cursor is not moved.
+pub fn draw_code(c: &mut Cursor, rect: Rect, data: &[u8]) {
+    let n = code_modules(rect);
+    let side = n + 2 * QUIET;
+    c.fill_rect(rect.row, rect.col, side, side * MODULE_W, b' ');
+    // Xorshift, which must not start at zero.
+    let mut state = hash(data) | 1;
+    for row in 0..n {
+        for col in 0..n {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            let on = finder(n, row, col).unwrap_or(state & 0x100 != 0);
+            if on {
+                let r = rect.row + QUIET + row;
+                let x = rect.col + (QUIET + col) * MODULE_W;
+                c.hline(r, x, MODULE_W, FULL_BLOCK);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 26;
+    const ROWS: usize = 13;
+
+    fn modules(data: &[u8]) -> Vec<bool> {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let rect = Rect::new(0, 0, ROWS, COLS);
+        draw_code(&mut Cursor::new(&mut buf, COLS), rect, data);
+        let n = code_modules(rect);
+        assert_eq!(n, 11);
+        // The quiet zone is blank all round.
+        for i in 0..COLS {
+            assert_eq!(buf[i].ascii_char(), b' ');
+            assert_eq!(buf[(ROWS - 1) * COLS + i].ascii_char(), b' ');
+        }
+        let mut out = Vec::new();
+        for row in 0..n {
+            for col in 0..n {
+                let i = (row + QUIET) * COLS + (col + QUIET) * MODULE_W;
+                let on = buf[i].ascii_char() == FULL_BLOCK;
+                assert_eq!(buf[i + 1].ascii_char(), buf[i].ascii_char());
+                out.push(on);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn same_data_same_code_and_small_changes_show() {
+        let a = modules(b"TKT-0042-7781");
+        assert_eq!(a, modules(b"TKT-0042-7781"));
+        // Finder squares in three corners, and not the fourth.
+        assert!(a[0] && a[10] && a[10 * 11]);
+        assert!(!a[3] && !a[3 * 11]);
+        let b = modules(b"TKT-0042-7782");
+        let differ = a.iter().zip(&b).filter(|(a, b)| a != b).count();
+        assert!(differ > 20, "only {} modules differ", differ);
+        let on = a.iter().filter(|&&on| on).count();
+        assert!(on > 30 && on < 90, "{} modules on", on);
+    }
+}
@@ -0,0 +1,235 @@
+//! Arithmetic on the 6-bit `0bBB_GG_RR` pixel colors used for text
+//! attributes.
+//!
+//! Each channel is two bits wide and is always handled separately, so no
+//! operation here can bleed one channel into its neighbor. That includes the
+//! gradient fills, which step each channel through its four levels on its own.
+
+use m4vga::rast::text_10x16::AChar;
+use m4vga::Pixel;
+
+use crate::cursor::{Cursor, Rect};
+
+/// Mask selecting the low bit of each channel.
+const CHANNEL_LSBS: Pixel = 0b01_01_01;
+
+/// Halves the brightness of each channel of `p`, rounding down. This is the
+/// darkening used for drop shadows.
+pub fn halved(p: Pixel) -> Pixel {
+    (p >> 1) & CHANNEL_LSBS
+}
+
+/// Lowers each channel of `p` by one step, stopping at zero.
+pub fn dimmed(p: Pixel) -> Pixel {
+    map_channels(p, |c| c.saturating_sub(1))
+}
+
+/// Raises each channel of `p` by one step, stopping at full brightness.
+pub fn brightened(p: Pixel) -> Pixel {
+    map_channels(p, |c| u8::min(c + 1, 0b11))
+}
+
+/// Applies `f` to each 2-bit channel of `p` separately. Bits above the
+/// three channels are discarded.
+fn map_channels(p: Pixel, f: impl Fn(u8) -> u8) -> Pixel {
+    let mut out = 0;
+    for shift in &[0, 2, 4] {
+        out |= (f((p >> shift) & 0b11) & 0b11) << shift;
+    }
+    out
+}
+
+/// Returns the color of cell `i` of `n` in a gradient from `from` at cell 0 to
+/// `to` at cell `n - 1`.
+///
+/// With only four levels per channel, each channel's run of `n` cells is split
+/// into equal bands, one per level it passes through, with the remainder of
+/// the division going to the bands nearer `to`. A channel that doesn't change
+/// stays put, and channels step independently of each other. If `n` is
+/// smaller than the number of levels, some levels are skipped and `to` may not
+/// be reached.
+pub fn gradient(from: Pixel, to: Pixel, i: usize, n: usize) -> Pixel {
+    let mut out = 0;
+    for shift in &[0, 2, 4] {
+        let a = (from >> shift) & 0b11;
+        let b = (to >> shift) & 0b11;
+        let (low, high) = (u8::min(a, b), u8::max(a, b));
+        let steps = usize::from(high - low);
+        let band = usize::min(i * (steps + 1) / usize::max(n, 1), steps) as u8;
+        let c = if a < b { a + band } else { a - band };
+        out |= c << shift;
+    }
+    out
+}
+
+/// Fills `rect` with spaces whose background fades from `from` in the
+/// leftmost column to `to` in the rightmost, following `gradient`.
+///
+/// The foreground color is the cursor's. Any part of `rect` outside the
+/// window is clipped, without changing the colors of the rest. The cursor is
+/// not moved.
+pub fn fill_gradient(c: &mut Cursor, rect: Rect, from: Pixel, to: Pixel) {
+    let fg = c.fg;
+    for i in 0..rect.w {
+        let bg = gradient(from, to, i, rect.w);
+        c.styled(fg, bg)
+            .fill_rect(rect.row, rect.col + i, rect.h, 1, b' ');
+    }
+}
+
+/// Fills `rect` like `fill_gradient`, but fading from `from` in the top row
+/// to `to` in the bottom row.
+pub fn fill_gradient_vertical(
+    c: &mut Cursor,
+    rect: Rect,
+    from: Pixel,
+    to: Pixel,
+) {
+    let fg = c.fg;
+    for i in 0..rect.h {
+        let bg = gradient(from, to, i, rect.h);
+        c.styled(fg, bg)
+            .fill_rect(rect.row + i, rect.col, 1, rect.w, b' ');
+    }
+}
+
+/// Attribute helpers for `AChar`, for deriving one cell's look from another.
+pub trait ACharExt {
+    /// Exchanges the foreground and background colors.
+    fn swapped_colors(self) -> Self;
+    /// Replaces both colors at once.
+    fn with_colors(self, fg: Pixel, bg: Pixel) -> Self;
+    /// Applies `dimmed` to both colors.
+    fn dimmed(self) -> Self;
+    /// Applies `brightened` to both colors.
+    fn brightened(self) -> Self;
+}
+
+impl ACharExt for AChar {
+    fn swapped_colors(self) -> Self {
+        self.with_colors(self.background(), self.foreground())
+    }
+
+    fn with_colors(self, fg: Pixel, bg: Pixel) -> Self {
+        self.with_foreground(fg).with_background(bg)
+    }
+
+    fn dimmed(self) -> Self {
+        self.with_colors(dimmed(self.foreground()), dimmed(self.background()))
+    }
+
+    fn brightened(self) -> Self {
+        self.with_colors(
+            brightened(self.foreground()),
+            brightened(self.background()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halved_per_channel() {
+        assert_eq!(halved(0b11_11_11), 0b01_01_01);
+        assert_eq!(halved(0b10_01_00), 0b01_00_00);
+        assert_eq!(halved(0b00_00_11), 0b00_00_01);
+        assert_eq!(halved(0), 0);
+    }
+
+    #[test]
+    fn dimmed_saturates_per_channel() {
+        assert_eq!(dimmed(0b11_10_01), 0b10_01_00);
+        assert_eq!(dimmed(0b00_11_00), 0b00_10_00);
+        assert_eq!(dimmed(0), 0);
+    }
+
+    #[test]
+    fn brightened_saturates_per_channel() {
+        assert_eq!(brightened(0b00_01_10), 0b01_10_11);
+        assert_eq!(brightened(0b11_00_11), 0b11_01_11);
+        assert_eq!(brightened(0b11_11_11), 0b11_11_11);
+    }
+
+    #[test]
+    fn achar_helpers_keep_character() {
+        let a =
+            AChar::from_ascii_char(b'q').with_colors(0b11_00_00, 0b00_01_00);
+        let s = a.swapped_colors();
+        assert_eq!(
+            (s.char(), s.foreground(), s.background()),
+            ('q', 0b00_01_00, 0b11_00_00)
+        );
+        let d = a.dimmed();
+        assert_eq!(
+            (d.char(), d.foreground(), d.background()),
+            ('q', 0b10_00_00, 0)
+        );
+        let b = a.brightened();
+        assert_eq!(
+            (b.char(), b.foreground(), b.background()),
+            ('q', 0b11_01_01, 0b01_10_01)
+        );
+    }
+
+    #[test]
+    fn gradient_steps_in_equal_bands() {
+        let levels: Vec<Pixel> = (0..8).map(|i| gradient(0, 3, i, 8)).collect();
+        assert_eq!(levels, [0, 0, 1, 1, 2, 2, 3, 3]);
+        // Blue to black across a title bar: four bands of 20 columns.
+        let steps: Vec<usize> = (1..80)
+            .filter(|&i| {
+                gradient(0b11_00_00, 0, i, 80)
+                    != gradient(0b11_00_00, 0, i - 1, 80)
+            })
+            .collect();
+        assert_eq!(steps, [20, 40, 60]);
+        // Leftover cells go to the bands nearer `to`, whichever way it runs.
+        let up: Vec<Pixel> = (0..6).map(|i| gradient(0, 3, i, 6)).collect();
+        assert_eq!(up, [0, 0, 1, 2, 2, 3]);
+        let down: Vec<Pixel> = (0..6).map(|i| gradient(3, 0, i, 6)).collect();
+        assert_eq!(down, [3, 3, 2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn gradient_channels_are_independent() {
+        // Red falls while green rises and blue holds.
+        let from = 0b10_00_11;
+        let to = 0b10_11_00;
+        let cells: Vec<Pixel> =
+            (0..4).map(|i| gradient(from, to, i, 4)).collect();
+        assert_eq!(cells, [0b10_00_11, 0b10_01_10, 0b10_10_01, 0b10_11_00]);
+        assert_eq!(gradient(from, to, 0, 1), from);
+        assert_eq!(gradient(from, to, 0, 0), from);
+    }
+
+    #[test]
+    fn fill_gradient_clips_and_keeps_fg() {
+        let mut buf =
+            [AChar::from_ascii_char(b'.').with_background(0b10); 6 * 3];
+        {
+            let mut c = Cursor::new_with(&mut buf, 6, 0b01, 0b11);
+            fill_gradient(&mut c, Rect::new(0, 2, 2, 8), 0, 3);
+            fill_gradient_vertical(
+                &mut c,
+                Rect::new(1, 0, 2, 2),
+                0b11_00_00,
+                0,
+            );
+            assert_eq!(c.bg, 0b11);
+        }
+        let bgs: Vec<Pixel> = buf.iter().map(|a| a.background()).collect();
+        assert_eq!(
+            bgs,
+            [
+                0b10, 0b10, 0, 0, 1, 1, //
+                0b11_00_00, 0b11_00_00, 0, 0, 1, 1, //
+                0b01_00_00, 0b01_00_00, 0b10, 0b10, 0b10, 0b10,
+            ]
+        );
+        assert!(buf[2..6]
+            .iter()
+            .all(|a| a.char() == ' ' && a.foreground() == 0b01));
+    }
+}
@@ -0,0 +1,292 @@
+//! Confetti falling down the screen, to celebrate.
+//!
+//! Each piece is one cell colored in from its background, so it needs no
+//! glyph from the font. Pieces fall a row at a time, each at its own moment,
+//! and go back to the top in a new place and color when they reach the
+//! bottom. Where they come from is up to a xorshift generator seeded from
+//! the frame count, which is random enough for confetti.
+
+use crate::cursor::{Cursor, Rect};
+
+/// Most pieces of confetti, and most areas kept clear of them.
+pub const CONFETTI_CAP: usize = 32;
+pub const KEEP_OUT_CAP: usize = 8;
+
+/// Bright colors for confetti: red, green, yellow, cyan, magenta and white.
+pub const CONFETTI_COLORS: [m4vga::Pixel; 6] = [
+    0b00_00_11, 0b00_11_00, 0b00_11_11, 0b11_11_00, 0b11_00_11, 0b11_11_11,
+];
+
+/// One piece, at `row`, `col` of the area.
+#[derive(Copy, Clone, Debug)]
+struct Piece {
+    row: usize,
+    col: usize,
+    color: m4vga::Pixel,
+    /// Ticks left before it falls a row.
+    wait: u32,
+    /// Where it left on the last tick, to be blanked.
+    left: Option<(usize, usize)>,
+}
+
+/// Steps the xorshift generator in `state`, which must not be zero, and
+/// returns its next number.
+fn xorshift(state: &mut u32) -> u32 {
+    let mut x = *state;
+    x ^= x << 13;
+    x ^= x >> 17;
+    x ^= x << 5;
+    *state = x;
+    x
+}
+
+/// Confetti falling down an area, behind anything it is kept out of.
+///
+/// The confetti is driven by the main loop like the `Spinner`: call `tick`
+/// once a frame and `redraw` when it says to. Nothing falls until `start`,
+/// and `stop` takes every piece off the screen again. A cell a piece leaves
+/// is blanked in `bg`, so the area should be blank in that color, apart
+/// from what is kept out with `keep_out`: pieces pass behind that rather
+/// than drawing over it.
+#[derive(Copy, Clone, Debug)]
+pub struct Confetti<'t> {
+    pub rect: Rect,
+    /// Colors pieces are picked from.
+    pub colors: &'t [m4vga::Pixel],
+    /// Pieces to drop, up to `CONFETTI_CAP`.
+    pub count: usize,
+    /// Ticks each piece takes to fall a row. Zero counts as one.
+    pub period: u32,
+    /// Background of the area, which cells are blanked in.
+    pub bg: m4vga::Pixel,
+    pieces: [Piece; CONFETTI_CAP],
+    /// Number of `pieces` falling, which is none until the first tick after
+    /// `start`.
+    len: usize,
+    keep_out: [Rect; KEEP_OUT_CAP],
+    keep_outs: usize,
+    running: bool,
+    rng: u32,
+}
+
+impl<'t> Confetti<'t> {
+    /// Makes confetti of `CONFETTI_COLORS` over a black background, not yet
+    /// started.
+    pub const fn new(rect: Rect, count: usize, period: u32) -> Self {
+        Confetti {
+            rect,
+            colors: &CONFETTI_COLORS,
+            count,
+            period,
+            bg: crate::cursor::DEFAULT_BG,
+            pieces: [Piece {
+                row: 0,
+                col: 0,
+                color: 0,
+                wait: 0,
+                left: None,
+            }; CONFETTI_CAP],
+            len: 0,
+            keep_out: [Rect::new(0, 0, 0, 0); KEEP_OUT_CAP],
+            keep_outs: 0,
+            running: false,
+            rng: 1,
+        }
+    }
+
+    /// Starts the confetti falling afresh, on the next tick, with nothing
+    /// kept out of it yet.
+    pub fn start(&mut self) {
+        self.len = 0;
+        self.keep_outs = 0;
+        self.running = true;
+    }
+
+    /// Keeps pieces from being drawn over `rect`, given in the same window
+    /// as the confetti's own. Returns false, and leaves it out, if there
+    /// are already `KEEP_OUT_CAP` areas kept out.
+    pub fn keep_out(&mut self, rect: Rect) -> bool {
+        if self.keep_outs == KEEP_OUT_CAP {
+            return false;
+        }
+        self.keep_out[self.keep_outs] = rect;
+        self.keep_outs += 1;
+        true
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Stops the confetti, blanking every piece on show, including any the
+    /// last tick moved that haven't been drawn since.
+    pub fn stop(&mut self, c: &mut Cursor) {
+        let mut c = c.styled(c.fg, self.bg);
+        for p in self.pieces[..self.len].iter() {
+            if let Some((row, col)) = p.left {
+                self.fill(&mut c, row, col);
+            }
+            self.fill(&mut c, p.row, p.col);
+        }
+        self.len = 0;
+        self.running = false;
+    }
+
+    /// Returns a number from the generator below `n`, which must not be
+    /// zero.
+    fn below(&mut self, n: usize) -> usize {
+        xorshift(&mut self.rng) as usize % n
+    }
+
+    /// Puts piece `i` on `row` in a new column and color.
+    fn scatter(&mut self, i: usize, row: usize) {
+        let col = self.below(self.rect.w);
+        let color = self.colors[self.below(self.colors.len())];
+        let p = &mut self.pieces[i];
+        p.row = row;
+        p.col = col;
+        p.color = color;
+    }
+
+    /// Moves the confetti on a tick, returning whether any piece moved and
+    /// so needs a `redraw`. The first tick after `start` seeds the generator
+    /// from `frame_no` and scatters the pieces over the whole area.
+    pub fn tick(&mut self, frame_no: u32) -> bool {
+        if !self.running || self.rect.h == 0 || self.rect.w == 0 {
+            return false;
+        }
+        if self.colors.is_empty() {
+            return false;
+        }
+        if self.len == 0 {
+            // Any seed but zero, which xorshift never leaves.
+            self.rng = frame_no.wrapping_mul(0x9E37_79B9) | 1;
+            self.len = usize::min(self.count, CONFETTI_CAP);
+            let period = u32::max(self.period, 1) as usize;
+            for i in 0..self.len {
+                let row = self.below(self.rect.h);
+                self.scatter(i, row);
+                // Falling out of step with each other.
+                self.pieces[i].wait = 1 + self.below(period) as u32;
+                self.pieces[i].left = None;
+            }
+            return self.len > 0;
+        }
+        let mut moved = false;
+        for i in 0..self.len {
+            let p = &mut self.pieces[i];
+            p.left = None;
+            p.wait -= 1;
+            if p.wait > 0 {
+                continue;
+            }
+            p.left = Some((p.row, p.col));
+            p.wait = u32::max(self.period, 1);
+            if p.row + 1 < self.rect.h {
+                p.row += 1;
+            } else {
+                self.scatter(i, 0);
+            }
+            moved = true;
+        }
+        moved
+    }
+
+    /// Returns whether a piece at `row`, `col` of the area can be seen.
+    fn shows(&self, row: usize, col: usize) -> bool {
+        let (row, col) = (self.rect.row + row, self.rect.col + col);
+        !self.keep_out[..self.keep_outs]
+            .iter()
+            .any(|r| r.contains(row, col))
+    }
+
+    /// Fills the cell at `row`, `col` of the area in the background of `c`,
+    /// unless it is kept out.
+    fn fill(&self, c: &mut Cursor, row: usize, col: usize) {
+        if self.shows(row, col) {
+            c.fill_rect(self.rect.row + row, self.rect.col + col, 1, 1, b' ');
+        }
+    }
+
+    /// Blanks the cells pieces left on the last tick, then draws every
+    /// piece where it is now.
+    pub fn redraw(&self, c: &mut Cursor) {
+        let pieces = &self.pieces[..self.len];
+        {
+            let mut c = c.styled(c.fg, self.bg);
+            for &(row, col) in pieces.iter().filter_map(|p| p.left.as_ref()) {
+                self.fill(&mut c, row, col);
+            }
+        }
+        for p in pieces {
+            let mut c = c.styled(c.fg, p.color);
+            self.fill(&mut c, p.row, p.col);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 6;
+    const ROWS: usize = 5;
+
+    /// Returns the cells of `buf` with a piece in them.
+    fn pieces(buf: &[AChar]) -> Vec<(usize, usize)> {
+        let bg = crate::cursor::DEFAULT_BG;
+        let cells = buf.iter().enumerate();
+        cells
+            .filter(|(_, a)| a.background() != bg)
+            .map(|(i, _)| (i / COLS, i % COLS))
+            .collect()
+    }
+
+    #[test]
+    fn falls_around_what_is_kept_out_and_cleans_up() {
+        let mut buf = [AChar::from_ascii_char(b' '); COLS * ROWS];
+        let mut confetti = Confetti::new(Rect::new(0, 0, ROWS, COLS), 8, 2);
+        assert!(!confetti.tick(0));
+        confetti.start();
+        let message = Rect::new(1, 1, 3, 4);
+        assert!(confetti.keep_out(message));
+        assert!(confetti.tick(7));
+        let mut most = 0;
+        for frame in 8..40 {
+            confetti.redraw(&mut Cursor::new(&mut buf, COLS));
+            let now = pieces(&buf);
+            assert!(now.iter().all(|&(r, c)| !message.contains(r, c)));
+            most = usize::max(most, now.len());
+            confetti.tick(frame);
+        }
+        assert!(most > 0 && most <= 8);
+
+        confetti.stop(&mut Cursor::new(&mut buf, COLS));
+        assert!(pieces(&buf).is_empty());
+        assert!(!confetti.tick(40));
+    }
+
+    #[test]
+    fn falls_a_row_a_period_and_wraps() {
+        let mut buf = [AChar::from_ascii_char(b' '); COLS * ROWS];
+        let mut confetti = Confetti::new(Rect::new(0, 0, ROWS, COLS), 1, 3);
+        confetti.start();
+        confetti.tick(0);
+        confetti.redraw(&mut Cursor::new(&mut buf, COLS));
+        let mut at = pieces(&buf)[0];
+        let mut moves = Vec::new();
+        for frame in 1..30 {
+            if confetti.tick(frame) {
+                confetti.redraw(&mut Cursor::new(&mut buf, COLS));
+                let now = pieces(&buf);
+                assert_eq!(now.len(), 1);
+                assert_eq!(now[0].0, (at.0 + 1) % ROWS);
+                at = now[0];
+                moves.push(frame);
+            }
+        }
+        assert!(moves.windows(2).all(|w| w[1] - w[0] == 3));
+        assert!(moves.len() >= 9);
+    }
+}
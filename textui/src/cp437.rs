@@ -0,0 +1,91 @@
+//! Mapping from Unicode text onto the code page 437 glyphs in `font_10x16`.
+//!
+//! Rust strings are UTF-8, but the text modes index the font with a single
+//! byte. ASCII maps onto itself; the rest of the font is the upper half of
+//! CP437, which we reach by looking characters up in `HIGH`. A few common
+//! characters with no CP437 glyph (curly quotes, dashes) are approximated in
+//! ASCII, and anything else becomes `FALLBACK`.
+
+/// Glyph used for characters that have no reasonable equivalent in the font.
+pub const FALLBACK: u8 = b'?';
+
+/// The characters at positions 0x80 through 0xFF of CP437, in order.
+static HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä',
+    'Å', 'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥',
+    '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼',
+    '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗',
+    '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩',
+    '╦', '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘',
+    '┌', '█', '▄', '▌', '▐', '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ',
+    'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈',
+    '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{A0}',
+];
+
+/// Converts `c` into the byte that displays it, or the closest thing the font
+/// has.
+///
+/// ASCII, including control characters, passes through unchanged so that
+/// `Cursor::putc` can still interpret them.
+pub fn encode(c: char) -> u8 {
+    if (c as u32) < 0x80 {
+        return c as u8;
+    }
+    if let Some(i) = HIGH.iter().position(|&h| h == c) {
+        return 0x80 + i as u8;
+    }
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{2032}' => b'\'',
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{2033}' => b'"',
+        '\u{2010}' | '\u{2011}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => b'-',
+        '\u{2022}' => 0xF9, // bullet
+        'β' => 0xE1,
+        'μ' => 0xE6,
+        '\u{2126}' => 0xEA, // ohm sign
+        '\u{2211}' => 0xE4, // n-ary summation
+        _ => FALLBACK,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_passes_through() {
+        for b in 0..0x80u8 {
+            assert_eq!(encode(b as char), b);
+        }
+    }
+
+    #[test]
+    fn high_half_round_trips() {
+        for (i, &c) in HIGH.iter().enumerate() {
+            assert_eq!(encode(c), 0x80 + i as u8, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn latin1_does_not_alias() {
+        // These would land on unrelated glyphs if cast straight to u8.
+        assert_eq!(encode('°'), 0xF8);
+        assert_eq!(encode('é'), 0x82);
+        assert_eq!(encode('ø'), FALLBACK);
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(encode('\u{2019}'), b'\'');
+        assert_eq!(encode('\u{201C}'), b'"');
+        assert_eq!(encode('\u{2014}'), b'-');
+        assert_eq!(encode('─'), 0xC4);
+        assert_eq!(encode('╔'), 0xC9);
+    }
+
+    #[test]
+    fn unmappable_is_visible() {
+        assert_eq!(encode('\u{1F600}'), FALLBACK);
+        assert_eq!(encode('\u{100}'), FALLBACK);
+        assert_eq!(encode('\u{FFFD}'), FALLBACK);
+    }
+}
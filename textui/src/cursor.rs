@@ -0,0 +1,3178 @@
+//! Terminal-style cursor over an attributed text buffer.
+
+use core::fmt;
+
+use m4vga::rast::text_10x16::AChar;
+
+use crate::color::{self, ACharExt};
+use crate::cp437;
+
+/// Errors reported by the checked `Cursor` operations.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CursorError {
+    /// A character would have been written at `row`, `col`, or the cursor
+    /// moved there, which is outside the window.
+    OutOfBounds { row: usize, col: usize },
+    /// `push_state` or `push_colors` was called with `STATE_DEPTH` entries
+    /// already saved.
+    StackFull,
+    /// `pop_state` or `pop_colors` was called with nothing saved.
+    StackEmpty,
+    /// A region was copied to or from a slice too short to hold it.
+    BufferTooSmall { needed: usize, got: usize },
+}
+
+/// A rectangular area of the text buffer, in character cells.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Rect {
+    /// Top row.
+    pub row: usize,
+    /// Leftmost column.
+    pub col: usize,
+    /// Height in rows.
+    pub h: usize,
+    /// Width in columns.
+    pub w: usize,
+}
+
+impl Rect {
+    pub const fn new(row: usize, col: usize, h: usize, w: usize) -> Self {
+        Rect { row, col, h, w }
+    }
+
+    /// Checks whether the cell at `row`, `col` is inside the rectangle.
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        row >= self.row
+            && row - self.row < self.h
+            && col >= self.col
+            && col - self.col < self.w
+    }
+
+    /// Returns the area covered by both rectangles, or `None` if they don't
+    /// overlap.
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let row = usize::max(self.row, other.row);
+        let col = usize::max(self.col, other.col);
+        let bottom = usize::min(
+            self.row.saturating_add(self.h),
+            other.row.saturating_add(other.h),
+        );
+        let right = usize::min(
+            self.col.saturating_add(self.w),
+            other.col.saturating_add(other.w),
+        );
+        if row < bottom && col < right {
+            Some(Rect::new(row, col, bottom - row, right - col))
+        } else {
+            None
+        }
+    }
+
+    /// Returns an `h` by `w` rectangle centered in this one. When it can't be
+    /// centered exactly, the extra row or column goes below or to the right.
+    /// A larger rectangle hangs over the edges equally, as far as it can
+    /// without going above row 0 or left of column 0.
+    pub fn centered(&self, h: usize, w: usize) -> Rect {
+        Rect::new(
+            center(self.row, self.h, h),
+            center(self.col, self.w, w),
+            h,
+            w,
+        )
+    }
+
+    /// Returns an `h` by `w` rectangle centered in `parent`, like
+    /// `parent.centered(h, w)`, except that it is shrunk to fit rather than
+    /// hanging over the edges.
+    pub fn center_within(parent: &Rect, w: usize, h: usize) -> Rect {
+        parent.centered(usize::min(h, parent.h), usize::min(w, parent.w))
+    }
+
+    /// Returns the rectangle less `n` cells on every side. A rectangle too
+    /// small for that shrinks to nothing at its middle.
+    pub fn inset(&self, n: usize) -> Rect {
+        let h = self.h.saturating_sub(n.saturating_mul(2));
+        let w = self.w.saturating_sub(n.saturating_mul(2));
+        Rect::new(
+            self.row.saturating_add(usize::min(n, self.h / 2)),
+            self.col.saturating_add(usize::min(n, self.w / 2)),
+            h,
+            w,
+        )
+    }
+
+    /// Splits the rectangle side by side into the first `at` columns and the
+    /// rest. If `at` is past the right edge, the right part is empty.
+    pub fn split_h(&self, at: usize) -> (Rect, Rect) {
+        let at = usize::min(at, self.w);
+        (
+            Rect::new(self.row, self.col, self.h, at),
+            Rect::new(
+                self.row,
+                self.col.saturating_add(at),
+                self.h,
+                self.w - at,
+            ),
+        )
+    }
+
+    /// Splits the rectangle one above the other into the first `at` rows and
+    /// the rest. If `at` is past the bottom edge, the bottom part is empty.
+    pub fn split_v(&self, at: usize) -> (Rect, Rect) {
+        let at = usize::min(at, self.h);
+        (
+            Rect::new(self.row, self.col, at, self.w),
+            Rect::new(
+                self.row.saturating_add(at),
+                self.col,
+                self.h - at,
+                self.w,
+            ),
+        )
+    }
+
+    /// Returns the part of the rectangle inside `parent`. Unlike
+    /// `intersect`, this always gives a rectangle: one that doesn't overlap
+    /// `parent` comes back empty, on the nearest edge of it.
+    pub fn clamp_to(&self, parent: &Rect) -> Rect {
+        let bottom = parent.row.saturating_add(parent.h);
+        let right = parent.col.saturating_add(parent.w);
+        let clamp =
+            |x: usize, lo: usize, hi: usize| usize::min(usize::max(x, lo), hi);
+        let row = clamp(self.row, parent.row, bottom);
+        let col = clamp(self.col, parent.col, right);
+        let end_row = clamp(self.row.saturating_add(self.h), row, bottom);
+        let end_col = clamp(self.col.saturating_add(self.w), col, right);
+        Rect::new(row, col, end_row - row, end_col - col)
+    }
+}
+
+/// Returns where a span `len` long starts when centered in the span `outer`
+/// long starting at `start`. See `Rect::centered`.
+fn center(start: usize, outer: usize, len: usize) -> usize {
+    if len <= outer {
+        start + (outer - len) / 2
+    } else {
+        start.saturating_sub((len - outer) / 2)
+    }
+}
+
+/// Markup byte for `Cursor::puts_markup`: sets the foreground to the next
+/// byte.
+pub const MARKUP_FG: u8 = 0x01;
+/// Markup byte for `Cursor::puts_markup`: sets the background to the next
+/// byte.
+pub const MARKUP_BG: u8 = 0x02;
+/// Markup byte for `Cursor::puts_markup`: restores the original colors.
+pub const MARKUP_RESET: u8 = 0x03;
+/// Markup byte for `Cursor::puts_markup`: types the next byte literally.
+pub const MARKUP_LITERAL: u8 = 0x10;
+
+/// Number of cursor states that can be saved with `Cursor::push_state`, and
+/// separately, number of color pairs that can be saved with
+/// `Cursor::push_colors`.
+pub const STATE_DEPTH: usize = 8;
+
+/// ASCII backspace, which `putc` treats as a destructive move left.
+const BACKSPACE: u8 = 0x08;
+
+/// Glyph shown in place of the last visible character of text cut short by
+/// `Cursor::puts_truncated`. CP437 has no ellipsis, so this is `»`.
+pub const TRUNCATION_MARK: u8 = 0xAF;
+
+/// Colors a new cursor draws with, white on black.
+pub const DEFAULT_FG: m4vga::Pixel = 0b11_11_11;
+pub const DEFAULT_BG: m4vga::Pixel = 0b00_00_00;
+
+/// Cursor position and colors, as saved by `Cursor::push_state`.
+#[derive(Copy, Clone, Debug)]
+struct SavedState {
+    row: usize,
+    col: usize,
+    fg: m4vga::Pixel,
+    bg: m4vga::Pixel,
+}
+
+/// Formats text right-aligned so that it ends at column `right_col` of `row`.
+/// See `Cursor::write_right`.
+///
+/// ```ignore
+/// write_right!(c, 35, COLS - 1, "{:03b}", s)
+/// ```
+#[macro_export]
+macro_rules! write_right {
+    ($cursor:expr, $row:expr, $right_col:expr, $($arg:tt)*) => {
+        $cursor.write_right($row, $right_col, format_args!($($arg)*))
+    };
+}
+
+/// Formats text starting at `row`, `col`. See `Cursor::write_at`.
+///
+/// ```ignore
+/// write_at!(c, 12, 4, "Fare: ${}.{:02}", cents / 100, cents % 100)
+/// ```
+#[macro_export]
+macro_rules! write_at {
+    ($cursor:expr, $row:expr, $col:expr, $($arg:tt)*) => {
+        $cursor.write_at($row, $col, format_args!($($arg)*))
+    };
+}
+
+/// Set of glyphs used to draw a frame with `Cursor::draw_box`.
+///
+/// The predefined styles cover the common cases, but the fields are public so
+/// screens can define their own in static data.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BoxStyle {
+    pub top_left: u8,
+    pub top: u8,
+    pub top_right: u8,
+    pub left: u8,
+    pub right: u8,
+    pub bottom_left: u8,
+    pub bottom: u8,
+    pub bottom_right: u8,
+    /// Junctions where a horizontal divider meets the left and right sides.
+    pub tee_left: u8,
+    pub tee_right: u8,
+}
+
+impl BoxStyle {
+    /// CP437 single-line box drawing characters.
+    pub const SINGLE: Self = BoxStyle {
+        top_left: 0xDA,
+        top: 0xC4,
+        top_right: 0xBF,
+        left: 0xB3,
+        right: 0xB3,
+        bottom_left: 0xC0,
+        bottom: 0xC4,
+        bottom_right: 0xD9,
+        tee_left: 0xC3,
+        tee_right: 0xB4,
+    };
+
+    /// CP437 double-line box drawing characters.
+    pub const DOUBLE: Self = BoxStyle {
+        top_left: 0xC9,
+        top: 0xCD,
+        top_right: 0xBB,
+        left: 0xBA,
+        right: 0xBA,
+        bottom_left: 0xC8,
+        bottom: 0xCD,
+        bottom_right: 0xBC,
+        tee_left: 0xCC,
+        tee_right: 0xB9,
+    };
+
+    /// Plain ASCII, for when the box drawing glyphs are unwelcome.
+    pub const ASCII: Self = BoxStyle {
+        top_left: b'+',
+        top: b'-',
+        top_right: b'+',
+        left: b'|',
+        right: b'|',
+        bottom_left: b'+',
+        bottom: b'-',
+        bottom_right: b'+',
+        tee_left: b'+',
+        tee_right: b'+',
+    };
+}
+
+/// A simple cursor wrapping a text buffer. Provides terminal-style operations.
+///
+/// A cursor may be confined to a rectangular window of the buffer (see
+/// `Cursor::window`), in which case all of its coordinates are relative to the
+/// window's top-left corner, and nothing it does can affect cells outside the
+/// window. A cursor made with `Cursor::new` covers the whole buffer.
+///
+/// The buffer's dimensions are set at runtime, so the same cursor works for any
+/// text mode or for off-screen buffers.
+pub struct Cursor<'a> {
+    buf: &'a mut [AChar],
+    /// Number of columns in each row of the whole buffer.
+    stride: usize,
+    /// Position of the window's top-left corner in the buffer.
+    top: usize,
+    left: usize,
+    /// Size of the window.
+    height: usize,
+    width: usize,
+    /// Cursor position within the window.
+    row: usize,
+    col: usize,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// When set, characters drawn by the cursor keep the foreground color
+    /// already in the cell and `fg` is ignored. Useful for swapping out text
+    /// without disturbing a recolored area.
+    pub fg_transparent: bool,
+    /// When set, characters drawn by the cursor keep the background color
+    /// already in the cell and `bg` is ignored. Useful for labeling an
+    /// existing colored area without knowing its color.
+    pub bg_transparent: bool,
+    /// Whether words that would overflow the current line are moved to the
+    /// next one. See `set_wrap`.
+    wrap: bool,
+    /// Position of the first character of the word currently being typed, if
+    /// any. Used to move a partial word down when word wrap kicks in.
+    word_start: Option<(usize, usize)>,
+    /// Whether running off the bottom of the buffer scrolls it. See
+    /// `set_scroll`.
+    scroll: bool,
+    /// Stack of states saved by `push_state`; the first `depth` are valid.
+    saved: [SavedState; STATE_DEPTH],
+    depth: usize,
+    /// Stack of (fg, bg) pairs saved by `push_colors`; the first
+    /// `color_depth` are valid.
+    saved_colors: [(m4vga::Pixel, m4vga::Pixel); STATE_DEPTH],
+    color_depth: usize,
+    /// Distance between tab stops. See `set_tab_width`.
+    tab_width: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Makes a cursor covering all of `buf`, which is treated as rows of
+    /// `cols` characters each. Any partial row at the end is ignored.
+    ///
+    /// The cursor starts at the top-left corner, drawing white on black.
+    ///
+    /// # Panics
+    ///
+    /// If `cols` is zero.
+    pub fn new(buf: &'a mut [AChar], cols: usize) -> Self {
+        assert!(cols > 0);
+        let rows = buf.len() / cols;
+        Cursor {
+            buf,
+            stride: cols,
+            top: 0,
+            left: 0,
+            height: rows,
+            width: cols,
+            row: 0,
+            col: 0,
+            fg: DEFAULT_FG,
+            bg: DEFAULT_BG,
+            fg_transparent: false,
+            bg_transparent: false,
+            wrap: false,
+            word_start: None,
+            scroll: true,
+            saved: [SavedState {
+                row: 0,
+                col: 0,
+                fg: 0,
+                bg: 0,
+            }; STATE_DEPTH],
+            depth: 0,
+            saved_colors: [(0, 0); STATE_DEPTH],
+            color_depth: 0,
+            tab_width: 8,
+        }
+    }
+
+    /// Makes a cursor like `new`, but drawing in `fg` on `bg`.
+    ///
+    /// # Panics
+    ///
+    /// If `cols` is zero.
+    pub fn new_with(
+        buf: &'a mut [AChar],
+        cols: usize,
+        fg: m4vga::Pixel,
+        bg: m4vga::Pixel,
+    ) -> Self {
+        Cursor::new(buf, cols).fg(fg).bg(bg)
+    }
+
+    /// Sets the foreground color, for use when building a cursor:
+    ///
+    /// ```ignore
+    /// let mut c = Cursor::new(&mut buf, COLS).fg(WHITE).bg(BLUE).at(3, 0);
+    /// ```
+    pub fn fg(mut self, fg: m4vga::Pixel) -> Self {
+        self.fg = fg;
+        self
+    }
+
+    /// Sets the background color, for use when building a cursor. See `fg`.
+    pub fn bg(mut self, bg: m4vga::Pixel) -> Self {
+        self.bg = bg;
+        self
+    }
+
+    /// Moves to `row`, `col` like `goto`, for use when building a cursor. See
+    /// `fg`.
+    pub fn at(mut self, row: usize, col: usize) -> Self {
+        self.goto(row, col);
+        self
+    }
+
+    /// Makes a cursor confined to a window of this one's area.
+    ///
+    /// The window's top-left corner is at `row`, `col`, and the new cursor
+    /// starts there, at its own (0, 0), with this cursor's colors. Wrapping,
+    /// newlines, and scrolling happen at the window's edges, and anything that
+    /// would land outside the window is clipped. The window itself is clipped
+    /// to this cursor's area, so windows can be nested.
+    pub fn window(
+        &mut self,
+        row: usize,
+        col: usize,
+        height: usize,
+        width: usize,
+    ) -> Cursor<'_> {
+        let row = usize::min(row, self.height);
+        let col = usize::min(col, self.width);
+        let stride = self.stride;
+        let mut w = Cursor::new(&mut *self.buf, stride);
+        w.top = self.top + row;
+        w.left = self.left + col;
+        w.height = usize::min(height, self.height - row);
+        w.width = usize::min(width, self.width - col);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.fg_transparent = self.fg_transparent;
+        w.bg_transparent = self.bg_transparent;
+        w.tab_width = self.tab_width;
+        w
+    }
+
+    /// Returns the size of the window as (rows, columns).
+    pub fn size(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    /// Returns the cursor position within the window as (row, column).
+    pub fn position(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Enables or disables word wrap.
+    ///
+    /// With word wrap off (the default), text that runs past the last column
+    /// continues on the next row, splitting words wherever they happen to
+    /// fall. With it on, a word that would straddle the end of a row is moved
+    /// to the start of the next row instead. Words longer than a full row are
+    /// still split.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+        self.word_start = None;
+    }
+
+    /// Enables or disables scrolling.
+    ///
+    /// With scrolling on (the default), moving the cursor past the last row
+    /// shifts the entire window up by one row, blanks the bottom row using the
+    /// current colors, and leaves the cursor on the last row. With it off, the
+    /// cursor runs off the end of the buffer and the next character typed
+    /// will panic, which is occasionally useful for catching layout bugs in
+    /// fixed screens.
+    pub fn set_scroll(&mut self, scroll: bool) {
+        self.scroll = scroll;
+    }
+
+    /// Sets the distance between tab stops, which defaults to 8.
+    ///
+    /// Tab stops fall on every multiple of `width` columns. A width of zero
+    /// is treated as one.
+    pub fn set_tab_width(&mut self, width: usize) {
+        self.tab_width = usize::max(width, 1);
+    }
+
+    /// Saves the cursor position and colors so they can be restored later
+    /// with `pop_state`.
+    ///
+    /// Up to `STATE_DEPTH` states can be saved at once. Beyond that, this
+    /// returns `CursorError::StackFull` and saves nothing.
+    pub fn push_state(&mut self) -> Result<(), CursorError> {
+        if self.depth == STATE_DEPTH {
+            return Err(CursorError::StackFull);
+        }
+        self.saved[self.depth] = SavedState {
+            row: self.row,
+            col: self.col,
+            fg: self.fg,
+            bg: self.bg,
+        };
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Restores the position and colors saved by the most recent
+    /// `push_state`.
+    ///
+    /// If nothing has been saved, returns `CursorError::StackEmpty` and leaves
+    /// the cursor unchanged.
+    pub fn pop_state(&mut self) -> Result<(), CursorError> {
+        if self.depth == 0 {
+            return Err(CursorError::StackEmpty);
+        }
+        self.depth -= 1;
+        let s = self.saved[self.depth];
+        self.row = s.row;
+        self.col = s.col;
+        self.fg = s.fg;
+        self.bg = s.bg;
+        self.word_start = None;
+        Ok(())
+    }
+
+    /// Saves the current colors so they can be restored later with
+    /// `pop_colors`. Unlike `push_state`, this doesn't save the position.
+    ///
+    /// Up to `STATE_DEPTH` color pairs can be saved at once. Beyond that, this
+    /// returns `CursorError::StackFull` and saves nothing.
+    pub fn push_colors(&mut self) -> Result<(), CursorError> {
+        if self.color_depth == STATE_DEPTH {
+            return Err(CursorError::StackFull);
+        }
+        self.saved_colors[self.color_depth] = (self.fg, self.bg);
+        self.color_depth += 1;
+        Ok(())
+    }
+
+    /// Restores the colors saved by the most recent `push_colors`.
+    ///
+    /// If nothing has been saved, returns `CursorError::StackEmpty` and leaves
+    /// the colors unchanged.
+    pub fn pop_colors(&mut self) -> Result<(), CursorError> {
+        if self.color_depth == 0 {
+            return Err(CursorError::StackEmpty);
+        }
+        self.color_depth -= 1;
+        let (fg, bg) = self.saved_colors[self.color_depth];
+        self.fg = fg;
+        self.bg = bg;
+        Ok(())
+    }
+
+    /// Switches to the given colors until the returned guard is dropped, at
+    /// which point the previous colors are restored.
+    ///
+    /// The guard dereferences to the cursor, so it can be used in its place:
+    ///
+    /// ```ignore
+    /// {
+    ///     let mut c = c.styled(WHITE, RED);
+    ///     c.puts(b"Warning!");
+    /// }
+    /// // back to the old colors here
+    /// ```
+    ///
+    /// This doesn't use the `push_colors` stack, so it can't fail.
+    pub fn styled(
+        &mut self,
+        fg: m4vga::Pixel,
+        bg: m4vga::Pixel,
+    ) -> Styled<'_, 'a> {
+        let saved = (self.fg, self.bg);
+        self.fg = fg;
+        self.bg = bg;
+        Styled {
+            cursor: self,
+            saved,
+        }
+    }
+
+    /// Types a character terminal-style and advances the cursor. `'\n'`
+    /// blanks the rest of the current row, then moves to the start of the
+    /// next. `'\t'` advances to the next tab stop, filling the skipped cells
+    /// with spaces; a tab at or past the last stop on the row fills the rest
+    /// of the row and moves to the next.
+    ///
+    /// `'\r'` returns to column 0 of the current row without blanking
+    /// anything, so a `"\r\n"` pair ends up at the start of the next row
+    /// just like `'\n'`, but with the whole row it left blanked. Backspace
+    /// (`0x08`) moves left one cell and blanks it; at column 0 it does nothing.
+    ///
+    /// # Panics
+    ///
+    /// If the cursor has run off the end of the buffer, which can only happen
+    /// with scrolling disabled. Use `try_putc` to handle this gracefully.
+    pub fn putc(&mut self, c: u8) {
+        self.try_putc(c).expect("cursor out of bounds")
+    }
+
+    /// Types a character like `putc`, but returns an error instead of
+    /// panicking if the cursor has run off the end of the buffer. The buffer
+    /// is left unchanged in that case.
+    pub fn try_putc(&mut self, c: u8) -> Result<(), CursorError> {
+        self.check_bounds()?;
+
+        match c {
+            b'\n' => {
+                let row = self.row;
+                for col in self.col..self.width {
+                    self.put_at(row, col, b' ');
+                }
+                self.word_start = None;
+                self.line_feed();
+            }
+            b'\r' => {
+                self.word_start = None;
+                self.col = 0;
+            }
+            BACKSPACE => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    let (row, col) = (self.row, self.col);
+                    self.put_at(row, col, b' ');
+                    // Erasing the first letter of a word leaves no word.
+                    if self.word_start == Some((row, col)) {
+                        self.word_start = None;
+                    }
+                }
+            }
+            b'\t' => {
+                let stop = (self.col / self.tab_width + 1) * self.tab_width;
+                let stop = usize::min(stop, self.width);
+                let row = self.row;
+                for col in self.col..stop {
+                    self.put_at(row, col, b' ');
+                }
+                self.word_start = None;
+                self.col = stop;
+                if self.col == self.width {
+                    self.line_feed();
+                }
+            }
+            _ => {
+                if self.wrap {
+                    self.track_word(c);
+                }
+                let (row, col) = (self.row, self.col);
+                self.put_at(row, col, c);
+                self.col += 1;
+                if self.col == self.width {
+                    self.line_feed();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts a character at the cursor, shifting the rest of the row right
+    /// by one cell and discarding the last cell in the row. The shifted cells
+    /// keep their colors; the inserted one gets the current colors.
+    ///
+    /// The cursor advances past the inserted character, but stays on the
+    /// current row: inserting at the last column leaves the cursor there.
+    pub fn insert_char(&mut self, c: u8) -> Result<(), CursorError> {
+        self.check_bounds()?;
+        let start = self.index(self.row, self.col);
+        let end = self.index(self.row, self.width - 1);
+        for i in (start..end).rev() {
+            self.buf[i + 1] = self.buf[i];
+        }
+        let (row, col) = (self.row, self.col);
+        self.put_at(row, col, c);
+        self.col = usize::min(self.col + 1, self.width - 1);
+        self.word_start = None;
+        Ok(())
+    }
+
+    /// Deletes the character at the cursor, shifting the rest of the row left
+    /// by one cell. The shifted cells keep their colors; the cell freed up at
+    /// the end of the row is blanked in the current colors. The cursor does
+    /// not move.
+    pub fn delete_char(&mut self) -> Result<(), CursorError> {
+        self.check_bounds()?;
+        let start = self.index(self.row, self.col);
+        let end = self.index(self.row, self.width - 1);
+        for i in start..end {
+            self.buf[i] = self.buf[i + 1];
+        }
+        let (row, last) = (self.row, self.width - 1);
+        self.put_at(row, last, b' ');
+        self.word_start = None;
+        Ok(())
+    }
+
+    /// Returns the character cell at `row`, `col`, or an error if that
+    /// position is outside the window.
+    pub fn char_at(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> Result<AChar, CursorError> {
+        if row < self.height && col < self.width {
+            Ok(self.buf[self.index(row, col)])
+        } else {
+            Err(CursorError::OutOfBounds { row, col })
+        }
+    }
+
+    /// Copies the cells in `rect` into `out`, row by row.
+    ///
+    /// `rect` must lie entirely within the window, and `out` must hold at
+    /// least `rect.h * rect.w` cells; otherwise an error is returned and
+    /// nothing is copied.
+    pub fn read_region(
+        &self,
+        rect: Rect,
+        out: &mut [AChar],
+    ) -> Result<(), CursorError> {
+        self.check_region(rect, out.len())?;
+        for r in 0..rect.h {
+            let src = self.index(rect.row + r, rect.col);
+            let dst = r * rect.w;
+            out[dst..dst + rect.w]
+                .copy_from_slice(&self.buf[src..src + rect.w]);
+        }
+        Ok(())
+    }
+
+    /// Copies cells from `data`, row by row, into `rect`. This is the inverse
+    /// of `read_region`, and has the same requirements.
+    pub fn write_region(
+        &mut self,
+        rect: Rect,
+        data: &[AChar],
+    ) -> Result<(), CursorError> {
+        self.check_region(rect, data.len())?;
+        for r in 0..rect.h {
+            let dst = self.index(rect.row + r, rect.col);
+            let src = r * rect.w;
+            self.buf[dst..dst + rect.w]
+                .copy_from_slice(&data[src..src + rect.w]);
+        }
+        Ok(())
+    }
+
+    /// Checks that `rect` is inside the window and that a slice of length
+    /// `len` can hold it.
+    fn check_region(&self, rect: Rect, len: usize) -> Result<(), CursorError> {
+        let bottom = rect.row.saturating_add(rect.h);
+        let right = rect.col.saturating_add(rect.w);
+        if bottom > self.height || right > self.width {
+            return Err(CursorError::OutOfBounds {
+                row: bottom.saturating_sub(1),
+                col: right.saturating_sub(1),
+            });
+        }
+        let needed = rect.h * rect.w;
+        if len < needed {
+            return Err(CursorError::BufferTooSmall { needed, got: len });
+        }
+        Ok(())
+    }
+
+    /// Checks that the cursor is inside the window.
+    fn check_bounds(&self) -> Result<(), CursorError> {
+        if self.row >= self.height || self.col >= self.width {
+            Err(CursorError::OutOfBounds {
+                row: self.row,
+                col: self.col,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Moves the cursor to the start of the next row, scrolling if enabled.
+    fn line_feed(&mut self) {
+        self.col = 0;
+        if self.scroll && self.row + 1 >= self.height {
+            self.scroll_up();
+        } else {
+            self.row += 1;
+        }
+    }
+
+    /// Shifts every row of the window up by one, discarding the top row and
+    /// blanking the bottom row in the current colors.
+    fn scroll_up(&mut self) {
+        let all = Rect::new(0, 0, self.height, self.width);
+        self.scroll_region(all, 1);
+        // Keep any partially typed word pointing at the same characters.
+        self.word_start = match self.word_start {
+            Some((row, col)) if row > 0 => Some((row - 1, col)),
+            _ => None,
+        };
+    }
+
+    /// Word-wrap bookkeeping for a character about to be typed at the cursor.
+    ///
+    /// If `c` continues a word that began on the previous row and was cut off
+    /// by the end of that row, the partial word is moved down to the start of
+    /// the current row, and the cells it vacated are blanked.
+    fn track_word(&mut self, c: u8) {
+        if c == b' ' {
+            self.word_start = None;
+            return;
+        }
+
+        match self.word_start {
+            None => self.word_start = Some((self.row, self.col)),
+            Some((start_row, start_col)) => {
+                // We only need to act when the cursor has just wrapped onto a
+                // fresh row in the middle of a word. If the word began at
+                // column 0 it can't fit on any row, so let it split.
+                let wrapped = self.col == 0 && start_row + 1 == self.row;
+                if wrapped && start_col != 0 {
+                    let len = self.width - start_col;
+                    let src = self.index(start_row, start_col);
+                    let dst = self.index(self.row, 0);
+                    let blank = AChar::from_ascii_char(b' ')
+                        .with_colors(self.fg, self.bg);
+                    for i in 0..len {
+                        self.buf[dst + i] = self.buf[src + i];
+                        self.buf[src + i] = blank;
+                    }
+                    self.col = len;
+                    self.word_start = Some((self.row, 0));
+                }
+            }
+        }
+    }
+
+    /// Types each character from an ASCII slice.
+    pub fn puts(&mut self, s: &[u8]) {
+        for c in s {
+            self.putc(*c)
+        }
+    }
+
+    /// Types `ch` `n` times, exactly as if `putc` were called in a loop.
+    pub fn putc_n(&mut self, ch: u8, n: usize) {
+        for _ in 0..n {
+            self.putc(ch)
+        }
+    }
+
+    /// Types spaces until the cursor reaches column `col` of the current row.
+    /// Does nothing if the cursor is already at or past `col`.
+    ///
+    /// Padding to the window width (or beyond) finishes the row and moves to
+    /// the next one, as typing the last column always does.
+    pub fn pad_to(&mut self, col: usize) {
+        let col = usize::min(col, self.width);
+        let n = col.saturating_sub(self.col);
+        self.putc_n(b' ', n)
+    }
+
+    /// Types each character from an ASCII slice like `puts`, but interprets
+    /// inline color changes:
+    ///
+    /// - `MARKUP_FG` followed by a pixel value sets the foreground color.
+    /// - `MARKUP_BG` followed by a pixel value sets the background color.
+    /// - `MARKUP_RESET` returns to the colors in effect when this was called.
+    /// - `MARKUP_LITERAL` followed by any byte types that byte as-is, so the
+    ///   other markup bytes can still be displayed.
+    ///
+    /// A markup byte at the very end of `s`, missing its argument, is ignored.
+    /// The colors set by the markup remain in effect afterwards.
+    pub fn puts_markup(&mut self, s: &[u8]) {
+        let (fg, bg) = (self.fg, self.bg);
+        let mut bytes = s.iter();
+        while let Some(&c) = bytes.next() {
+            match c {
+                MARKUP_FG => {
+                    if let Some(&p) = bytes.next() {
+                        self.fg = p;
+                    }
+                }
+                MARKUP_BG => {
+                    if let Some(&p) = bytes.next() {
+                        self.bg = p;
+                    }
+                }
+                MARKUP_RESET => {
+                    self.fg = fg;
+                    self.bg = bg;
+                }
+                MARKUP_LITERAL => {
+                    if let Some(&c) = bytes.next() {
+                        self.putc(c);
+                    }
+                }
+                _ => self.putc(c),
+            }
+        }
+    }
+
+    /// Types each character from an ASCII slice like `puts`, stopping at the
+    /// first character that would fall outside the buffer.
+    pub fn try_puts(&mut self, s: &[u8]) -> Result<(), CursorError> {
+        for c in s {
+            self.try_putc(*c)?
+        }
+        Ok(())
+    }
+
+    /// Predicts how `puts(text)` would lay out if typed from the top left of
+    /// a window `max_width` columns wide, with this cursor's word wrap and tab
+    /// settings and room for every line. Returns the number of rows the text
+    /// touches and the width of the widest one.
+    ///
+    /// A row's width runs through its last non-space character, so trailing
+    /// spaces and the blanking done by `'\n'` don't count. This makes the
+    /// result suitable for sizing a box to fit the text.
+    ///
+    /// A `max_width` of zero is taken as one, and one over `MEASURE_WIDTH`
+    /// as `MEASURE_WIDTH`, so text for a wider window is measured as if it
+    /// wrapped there.
+    pub fn measure(&self, text: &[u8], max_width: usize) -> (usize, usize) {
+        let width = usize::min(usize::max(max_width, 1), MEASURE_WIDTH);
+        let mut m = Measure {
+            width,
+            tab_width: self.tab_width,
+            wrap: self.wrap,
+            row: 0,
+            col: 0,
+            word_start: None,
+            cur: 0,
+            prev: 0,
+            touched: false,
+            widest: 0,
+        };
+        for &c in text {
+            m.putc(c);
+        }
+        let lines = m.row + m.touched as usize;
+        let widest = usize::max(m.widest, extent(m.prev));
+        (lines, usize::max(widest, extent(m.cur)))
+    }
+
+    /// Repositions the cursor. A position outside the window is clamped to
+    /// the nearest cell inside it; use `try_goto` to detect this instead.
+    pub fn goto(&mut self, row: usize, col: usize) {
+        self.row = usize::min(row, self.height.saturating_sub(1));
+        self.col = usize::min(col, self.width.saturating_sub(1));
+        self.word_start = None;
+    }
+
+    /// Repositions the cursor like `goto`, but returns an error carrying the
+    /// requested position if it's outside the window. The cursor is left
+    /// where it was in that case.
+    pub fn try_goto(
+        &mut self,
+        row: usize,
+        col: usize,
+    ) -> Result<(), CursorError> {
+        if row < self.height && col < self.width {
+            self.goto(row, col);
+            Ok(())
+        } else {
+            Err(CursorError::OutOfBounds { row, col })
+        }
+    }
+
+    /// Moves to `row`, `col` and types `text` there, leaving the cursor after
+    /// it. If the position is outside the window, returns an error and writes
+    /// nothing.
+    pub fn puts_at(
+        &mut self,
+        row: usize,
+        col: usize,
+        text: &[u8],
+    ) -> Result<(), CursorError> {
+        self.try_goto(row, col)?;
+        self.try_puts(text)
+    }
+
+    /// Lays out `text` as word-wrapped prose filling `rect`, in the current
+    /// colors, and returns how many bytes of it were used.
+    ///
+    /// Runs of whitespace, including single line breaks, collapse to one
+    /// space, so prose can be written without regard to where the lines will
+    /// break. A blank line (two or more `'\n'`s in a run of whitespace) starts
+    /// a new paragraph, leaving an empty row in between. Words longer than the
+    /// rectangle is wide are split. Unused cells in the rectangle are blanked.
+    ///
+    /// Text that doesn't fit is left out, and the return value is less than
+    /// `text.len()`; passing `&text[used..]` to another call picks up where
+    /// this one stopped, which is how to page through a long text. Any part
+    /// of `rect` outside the window is clipped. The cursor is not moved.
+    pub fn put_paragraph(&mut self, rect: Rect, text: &[u8]) -> usize {
+        self.layout_paragraph(rect, text, false)
+    }
+
+    /// Lays out `text` like `put_paragraph`, but fully justified: extra
+    /// spaces are spread between the words of each line so that it reaches
+    /// both edges of `rect`. The last line of each paragraph, and any line
+    /// holding a single word, is left-aligned as usual.
+    ///
+    /// When the extra spaces don't divide evenly, the gaps on the left get one
+    /// more space than those on the right.
+    pub fn put_justified(&mut self, rect: Rect, text: &[u8]) -> usize {
+        self.layout_paragraph(rect, text, true)
+    }
+
+    /// Shared implementation of `put_paragraph` and `put_justified`.
+    fn layout_paragraph(
+        &mut self,
+        rect: Rect,
+        text: &[u8],
+        justify: bool,
+    ) -> usize {
+        let h = usize::min(rect.h, self.height.saturating_sub(rect.row));
+        let w = usize::min(rect.w, self.width.saturating_sub(rect.col));
+        if w == 0 {
+            return 0;
+        }
+
+        let mut pos = 0;
+        let mut row = 0;
+        while row < h {
+            let (start, newlines) = skip_space(text, pos);
+            if start == text.len() {
+                pos = start;
+                break;
+            }
+            if newlines >= 2 && row > 0 {
+                // Paragraph break.
+                self.fill_rect(rect.row + row, rect.col, 1, w, b' ');
+                row += 1;
+                pos = start;
+                continue;
+            }
+
+            let end = break_line(text, start, w);
+            let line = &text[start..end];
+            let words =
+                line.split(|&c| is_space(c)).filter(|word| !word.is_empty());
+            let (count, letters) = words
+                .clone()
+                .fold((0, 0), |(n, len), word| (n + 1, len + word.len()));
+            // Gaps between words are one space, unless justifying a line
+            // that isn't the last of its paragraph.
+            let gaps = count - 1;
+            let (gap, wide_gaps) = match skip_space(text, end) {
+                (next, newlines)
+                    if justify
+                        && gaps > 0
+                        && next < text.len()
+                        && newlines < 2 =>
+                {
+                    let spaces = w - letters;
+                    (spaces / gaps, spaces % gaps)
+                }
+                _ => (1, 0),
+            };
+
+            let mut col = 0;
+            for (i, word) in words.enumerate() {
+                if i > 0 {
+                    let n = if i <= wide_gaps { gap + 1 } else { gap };
+                    self.fill_rect(rect.row + row, rect.col + col, 1, n, b' ');
+                    col += n;
+                }
+                for &c in word {
+                    self.put_at(rect.row + row, rect.col + col, c);
+                    col += 1;
+                }
+            }
+            self.fill_rect(rect.row + row, rect.col + col, 1, w - col, b' ');
+            row += 1;
+            pos = end;
+        }
+
+        if row < h {
+            self.fill_rect(rect.row + row, rect.col, h - row, w, b' ');
+        }
+        // Trailing whitespace doesn't need another page.
+        match skip_space(text, pos) {
+            (end, _) if end == text.len() => end,
+            _ => pos,
+        }
+    }
+
+    /// Types `text` like `puts`, but using at most `max_width` cells and never
+    /// running past the end of the current row. If the text doesn't fit, its
+    /// last visible character is replaced with `TRUNCATION_MARK`.
+    pub fn puts_truncated(&mut self, text: &[u8], max_width: usize) {
+        let max_width =
+            usize::min(max_width, self.width.saturating_sub(self.col));
+        if text.len() <= max_width {
+            self.puts(text);
+        } else if max_width > 0 {
+            self.puts(&text[..max_width - 1]);
+            self.putc(TRUNCATION_MARK);
+        }
+    }
+
+    /// Moves to `row`, `col` and types `text` there like `puts_truncated`.
+    /// If the position is outside the window, returns an error and writes
+    /// nothing.
+    pub fn puts_truncated_at(
+        &mut self,
+        row: usize,
+        col: usize,
+        text: &[u8],
+        max_width: usize,
+    ) -> Result<(), CursorError> {
+        self.try_goto(row, col)?;
+        self.puts_truncated(text, max_width);
+        Ok(())
+    }
+
+    /// Formats `args` and types the result starting at `row`, `col`, like
+    /// `puts_at`. This is usually used through the `write_at!` macro.
+    pub fn write_at(
+        &mut self,
+        row: usize,
+        col: usize,
+        args: fmt::Arguments,
+    ) -> fmt::Result {
+        self.try_goto(row, col).map_err(|_| fmt::Error)?;
+        fmt::write(self, args)
+    }
+
+    /// Returns a writer for `write!` that types on the current row, starting at
+    /// the cursor, and silently drops anything past `max_width` cells or the
+    /// right edge of the window instead of wrapping, scrolling or failing.
+    ///
+    /// This is meant for values whose length isn't known ahead of time, like
+    /// counters in a status line: a value that turns out too long gets cut
+    /// short rather than halting the program.
+    ///
+    /// ```ignore
+    /// c.goto(36, 70);
+    /// write!(c.truncating(8), "frame {}", frame_no).ok();
+    /// ```
+    pub fn truncating(&mut self, max_width: usize) -> TruncatingCursor<'_, 'a> {
+        let end = usize::min(self.col.saturating_add(max_width), self.width);
+        self.word_start = None;
+        TruncatingCursor {
+            cursor: self,
+            end,
+            done: false,
+        }
+    }
+
+    /// Fills a rectangle with the character `ch` in the current colors.
+    ///
+    /// The rectangle's top-left corner is at `row`, `col`. Any part of it that
+    /// falls outside the window is clipped. The cursor is not moved.
+    pub fn fill_rect(
+        &mut self,
+        row: usize,
+        col: usize,
+        height: usize,
+        width: usize,
+        ch: u8,
+    ) {
+        let (fg, bg) = self.pen();
+        self.map_region(row, col, height, width, |old| paint(old, ch, fg, bg))
+    }
+
+    /// Fills all of `row` with the character `ch` in the current colors. The
+    /// cursor is not moved.
+    pub fn fill_row(&mut self, row: usize, ch: u8) {
+        self.fill_rows(row, row + 1, ch)
+    }
+
+    /// Fills rows `start` up to (but not including) `end` with the character
+    /// `ch` in the current colors, as for a multi-row banner. Rows past the
+    /// bottom of the window are ignored, and the cursor is not moved.
+    pub fn fill_rows(&mut self, start: usize, end: usize, ch: u8) {
+        let width = self.width;
+        self.fill_rect(start, 0, end.saturating_sub(start), width, ch)
+    }
+
+    /// Fills `rect` with `pattern` repeated across each row, in the current
+    /// colors. Handy with the CP437 shade characters `0xB0`-`0xB2` for
+    /// textured backgrounds.
+    ///
+    /// The leftmost column of `rect` gets `pattern[phase % pattern.len()]`.
+    /// To make adjacent fills line up, pass the same column-derived phase to
+    /// each, e.g. `rect.col`. An empty pattern draws nothing.
+    ///
+    /// Any part of the rectangle that falls outside the window is clipped.
+    /// The cursor is not moved.
+    pub fn fill_pattern(&mut self, rect: Rect, pattern: &[u8], phase: usize) {
+        if pattern.is_empty() {
+            return;
+        }
+        let row_end = usize::min(rect.row.saturating_add(rect.h), self.height);
+        let col_end = usize::min(rect.col.saturating_add(rect.w), self.width);
+        for row in rect.row..row_end {
+            for col in rect.col..col_end {
+                let ch = pattern[(phase + (col - rect.col)) % pattern.len()];
+                self.put_at(row, col, ch);
+            }
+        }
+    }
+
+    /// Draws a horizontal run of `len` copies of `ch` in the current colors,
+    /// starting at `row`, `col` and extending right. The line is clipped to
+    /// the window, and the cursor is not moved.
+    pub fn hline(&mut self, row: usize, col: usize, len: usize, ch: u8) {
+        self.fill_rect(row, col, 1, len, ch)
+    }
+
+    /// Draws a vertical run of `len` copies of `ch` in the current colors,
+    /// starting at `row`, `col` and extending down. The line is clipped to the
+    /// window, and the cursor is not moved.
+    pub fn vline(&mut self, row: usize, col: usize, len: usize, ch: u8) {
+        let row_end = usize::min(row.saturating_add(len), self.height);
+        for r in row..row_end {
+            self.put_at(r, col, ch);
+        }
+    }
+
+    /// Scrolls the contents of `rect` up by `lines` rows, or down if `lines`
+    /// is negative. Rows scrolled out of the rectangle are discarded, and the
+    /// rows vacated are blanked in the current colors. Cells outside the
+    /// rectangle are not affected.
+    ///
+    /// Any part of the rectangle that falls outside the window is clipped.
+    /// The cursor is not moved.
+    pub fn scroll_region(&mut self, rect: Rect, lines: isize) {
+        let h = usize::min(rect.h, self.height.saturating_sub(rect.row));
+        let w = usize::min(rect.w, self.width.saturating_sub(rect.col));
+        if h == 0 || w == 0 {
+            return;
+        }
+        let n = usize::min(lines.unsigned_abs(), h);
+
+        if lines > 0 {
+            for r in 0..h - n {
+                self.copy_row_span(rect.row + r + n, rect.row + r, rect.col, w);
+            }
+            self.fill_rect(rect.row + h - n, rect.col, n, w, b' ');
+        } else if lines < 0 {
+            for r in (0..h - n).rev() {
+                self.copy_row_span(rect.row + r, rect.row + r + n, rect.col, w);
+            }
+            self.fill_rect(rect.row, rect.col, n, w, b' ');
+        }
+    }
+
+    /// Copies the cells in `src` so that its top-left corner lands at
+    /// `dst_row`, `dst_col`. The source and destination may overlap; the
+    /// result is as if the whole rectangle were read before any of it was
+    /// written. Cells outside the destination are not affected.
+    ///
+    /// Any part of either rectangle that falls outside the window is clipped.
+    /// The cursor is not moved.
+    pub fn copy_region(&mut self, src: Rect, dst_row: usize, dst_col: usize) {
+        let h = usize::min(src.h, self.height.saturating_sub(src.row));
+        let h = usize::min(h, self.height.saturating_sub(dst_row));
+        let w = usize::min(src.w, self.width.saturating_sub(src.col));
+        let w = usize::min(w, self.width.saturating_sub(dst_col));
+        if h == 0 || w == 0 {
+            return;
+        }
+
+        let from = self.index(src.row, src.col);
+        let to = self.index(dst_row, dst_col);
+        let stride = self.stride;
+        let copy = |buf: &mut [AChar], r: usize, c: usize| {
+            buf[to + r * stride + c] = buf[from + r * stride + c];
+        };
+        // Both rectangles have the same shape, so each destination cell is a
+        // fixed distance from its source cell in the buffer. If that distance
+        // is positive, working backwards through the buffer reads every
+        // source cell before it is overwritten; otherwise, work forwards.
+        if to > from {
+            for r in (0..h).rev() {
+                for c in (0..w).rev() {
+                    copy(self.buf, r, c);
+                }
+            }
+        } else {
+            for r in 0..h {
+                for c in 0..w {
+                    copy(self.buf, r, c);
+                }
+            }
+        }
+    }
+
+    /// Copies `width` cells starting at column `col` from row `src` to row
+    /// `dst`. The span must be inside the window.
+    fn copy_row_span(
+        &mut self,
+        src: usize,
+        dst: usize,
+        col: usize,
+        width: usize,
+    ) {
+        let src = self.index(src, col);
+        let dst = self.index(dst, col);
+        for i in 0..width {
+            self.buf[dst + i] = self.buf[src + i];
+        }
+    }
+
+    /// Swaps the foreground and background colors of every cell in a
+    /// rectangle, leaving the characters alone. Inverting the same rectangle
+    /// twice restores it exactly, which makes this handy for blinking a
+    /// selection.
+    ///
+    /// Any part of the rectangle that falls outside the window is clipped.
+    /// The cursor is not moved.
+    pub fn invert_region(
+        &mut self,
+        row: usize,
+        col: usize,
+        height: usize,
+        width: usize,
+    ) {
+        self.map_region(row, col, height, width, ACharExt::swapped_colors)
+    }
+
+    /// Changes the colors of every cell in `rect` without touching the
+    /// characters. `Some` color replaces that attribute, and `None` leaves it
+    /// as it is. Useful for moving a highlight or flashing an area without
+    /// retyping its text.
+    ///
+    /// Any part of the rectangle that falls outside the window is clipped.
+    /// The cursor is not moved.
+    pub fn recolor_region(
+        &mut self,
+        rect: Rect,
+        fg: Option<m4vga::Pixel>,
+        bg: Option<m4vga::Pixel>,
+    ) {
+        self.map_region(rect.row, rect.col, rect.h, rect.w, |a| {
+            paint(a, a.ascii_char(), fg, bg)
+        })
+    }
+
+    /// Darkens the cells just below and just right of `rect`, offset by one
+    /// cell, so the rectangle appears to cast a shadow down and to the right.
+    /// Shadowed cells keep their characters and foreground colors; only the
+    /// background is darkened, by halving each channel.
+    ///
+    /// Draw the shadow once, after whatever it falls on; drawing it again
+    /// darkens it further. Any part of the shadow outside the window is
+    /// clipped. The cursor is not moved.
+    pub fn draw_shadow(&mut self, rect: Rect) {
+        let shade = |a: AChar| a.with_background(color::halved(a.background()));
+        let (bottom, right) = (rect.row + rect.h, rect.col + rect.w);
+        self.map_region(bottom, rect.col + 1, 1, rect.w, shade);
+        self.map_region(
+            rect.row + 1,
+            right,
+            rect.h.saturating_sub(1),
+            1,
+            shade,
+        );
+    }
+
+    /// Replaces every cell in a rectangle, clipped to the window, with the
+    /// result of applying `f` to it.
+    fn map_region(
+        &mut self,
+        row: usize,
+        col: usize,
+        height: usize,
+        width: usize,
+        mut f: impl FnMut(AChar) -> AChar,
+    ) {
+        let row_end = usize::min(row.saturating_add(height), self.height);
+        let col_end = usize::min(col.saturating_add(width), self.width);
+        if col >= col_end {
+            return;
+        }
+        for r in row..row_end {
+            let start = self.index(r, col);
+            for p in &mut self.buf[start..start + (col_end - col)] {
+                *p = f(*p);
+            }
+        }
+    }
+
+    /// Writes `text` centered on `row`, filling the rest of the row with
+    /// spaces, all in the current colors.
+    ///
+    /// When the text can't be centered exactly, the extra space goes on the
+    /// right. Text wider than the window is cut off on the right. The cursor
+    /// is not moved.
+    pub fn puts_centered(&mut self, row: usize, text: &[u8]) {
+        let text = &text[..usize::min(text.len(), self.width)];
+        let left = (self.width - text.len()) / 2;
+        for col in 0..left {
+            self.put_at(row, col, b' ');
+        }
+        for (i, &ch) in text.iter().enumerate() {
+            self.put_at(row, left + i, ch);
+        }
+        for col in left + text.len()..self.width {
+            self.put_at(row, col, b' ');
+        }
+    }
+
+    /// Writes `text` so that its last character lands at `right_col` on `row`,
+    /// in the current colors.
+    ///
+    /// If the text is too long to fit to the left of `right_col`, it is cut
+    /// off on the left. The cursor is not moved.
+    pub fn puts_right(&mut self, row: usize, right_col: usize, text: &[u8]) {
+        let skip = text.len().saturating_sub(right_col + 1);
+        let left = right_col + 1 - (text.len() - skip);
+        for (i, &ch) in text[skip..].iter().enumerate() {
+            self.put_at(row, left + i, ch);
+        }
+    }
+
+    /// Formats `args` and writes the result right-aligned like `puts_right`.
+    /// This is usually used through the `write_right!` macro.
+    pub fn write_right(
+        &mut self,
+        row: usize,
+        right_col: usize,
+        args: fmt::Arguments,
+    ) -> fmt::Result {
+        let mut line = LineBuf {
+            buf: [0; LINE_BUF_LEN],
+            len: 0,
+        };
+        fmt::write(&mut line, args)?;
+        self.puts_right(row, right_col, &line.buf[..line.len]);
+        Ok(())
+    }
+
+    /// Draws a frame using the glyphs from `style`.
+    ///
+    /// The frame's top-left corner is at `row`, `col`. Only the border cells
+    /// are written, in the current colors; the interior is left untouched. A
+    /// box one row tall degenerates to a horizontal line, and one column wide
+    /// to a vertical line. Any part of the frame that falls outside the window
+    /// is clipped. The cursor is not moved.
+    pub fn draw_box(
+        &mut self,
+        row: usize,
+        col: usize,
+        height: usize,
+        width: usize,
+        style: &BoxStyle,
+    ) {
+        if height == 0 || width == 0 {
+            return;
+        }
+        if height == 1 {
+            for c in col..col.saturating_add(width) {
+                self.put_at(row, c, style.top);
+            }
+            return;
+        }
+        if width == 1 {
+            for r in row..row.saturating_add(height) {
+                self.put_at(r, col, style.left);
+            }
+            return;
+        }
+
+        let bottom = row.saturating_add(height) - 1;
+        let right = col.saturating_add(width) - 1;
+        for c in col + 1..right {
+            self.put_at(row, c, style.top);
+            self.put_at(bottom, c, style.bottom);
+        }
+        for r in row + 1..bottom {
+            self.put_at(r, col, style.left);
+            self.put_at(r, right, style.right);
+        }
+        self.put_at(row, col, style.top_left);
+        self.put_at(row, right, style.top_right);
+        self.put_at(bottom, col, style.bottom_left);
+        self.put_at(bottom, right, style.bottom_right);
+    }
+
+    /// Writes a single character at `row`, `col` in the current colors,
+    /// without moving the cursor. Writes outside the window are ignored.
+    fn put_at(&mut self, row: usize, col: usize, ch: u8) {
+        if row < self.height && col < self.width {
+            let i = self.index(row, col);
+            let (fg, bg) = self.pen();
+            self.buf[i] = paint(self.buf[i], ch, fg, bg);
+        }
+    }
+
+    /// Returns the colors to draw with, or `None` for a color that should be
+    /// kept from the cell being drawn over.
+    fn pen(&self) -> (Option<m4vga::Pixel>, Option<m4vga::Pixel>) {
+        let fg = if self.fg_transparent {
+            None
+        } else {
+            Some(self.fg)
+        };
+        let bg = if self.bg_transparent {
+            None
+        } else {
+            Some(self.bg)
+        };
+        (fg, bg)
+    }
+
+    /// Converts a position within the window into an index into the buffer.
+    fn index(&self, row: usize, col: usize) -> usize {
+        (self.top + row) * self.stride + self.left + col
+    }
+
+    /// Clears the window to spaces in the current colors and moves the cursor
+    /// to the top left.
+    pub fn clear(&mut self) {
+        let height = self.height;
+        self.clear_rows(0, height);
+        self.goto(0, 0);
+        self.word_start = None;
+    }
+
+    /// Clears rows `start` up to (but not including) `end` of the window to
+    /// spaces in the current colors. Rows past the bottom of the window are
+    /// ignored, and the cursor is not moved.
+    pub fn clear_rows(&mut self, start: usize, end: usize) {
+        let end = usize::min(end, self.height);
+        if start >= end {
+            return;
+        }
+        let blank = AChar::from_ascii_char(b' ').with_colors(self.fg, self.bg);
+        if self.width == self.stride {
+            // The rows are contiguous in the buffer, so do them in one go.
+            let (first, last) = (self.index(start, 0), self.index(end, 0));
+            for p in &mut self.buf[first..last] {
+                *p = blank;
+            }
+        } else {
+            for row in start..end {
+                let first = self.index(row, 0);
+                for p in &mut self.buf[first..first + self.width] {
+                    *p = blank;
+                }
+            }
+        }
+    }
+}
+
+/// Guard returned by `Cursor::styled`, which restores the cursor's colors when
+/// dropped.
+pub struct Styled<'c, 'a> {
+    cursor: &'c mut Cursor<'a>,
+    saved: (m4vga::Pixel, m4vga::Pixel),
+}
+
+impl<'c, 'a> core::ops::Deref for Styled<'c, 'a> {
+    type Target = Cursor<'a>;
+
+    fn deref(&self) -> &Cursor<'a> {
+        self.cursor
+    }
+}
+
+impl<'c, 'a> core::ops::DerefMut for Styled<'c, 'a> {
+    fn deref_mut(&mut self) -> &mut Cursor<'a> {
+        self.cursor
+    }
+}
+
+impl<'c, 'a> Drop for Styled<'c, 'a> {
+    fn drop(&mut self) {
+        self.cursor.fg = self.saved.0;
+        self.cursor.bg = self.saved.1;
+    }
+}
+
+/// Writer returned by `Cursor::truncating`.
+///
+/// Characters are converted with `cp437::encode` and drawn as glyphs, without
+/// interpreting control characters, except that a newline ends the output.
+/// The cursor advances with the text but stops on the last cell of the window
+/// if the text reaches the right edge.
+pub struct TruncatingCursor<'c, 'a> {
+    cursor: &'c mut Cursor<'a>,
+    /// Column just past the last cell that may be written.
+    end: usize,
+    /// Set once a newline has been seen; everything after it is dropped.
+    done: bool,
+}
+
+impl<'c, 'a> fmt::Write for TruncatingCursor<'c, 'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let c = &mut *self.cursor;
+        for ch in s.chars() {
+            if self.done || ch == '\n' {
+                self.done = true;
+                break;
+            }
+            if c.col >= self.end {
+                break;
+            }
+            c.put_at(c.row, c.col, cp437::encode(ch));
+            if c.col + 1 < c.width {
+                c.col += 1;
+            } else {
+                // Park on the last cell rather than wrapping.
+                self.end = c.col;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the character in `old` with `ch`, and each color with the given
+/// one unless it's `None`. See `Cursor::pen`.
+fn paint(
+    old: AChar,
+    ch: u8,
+    fg: Option<m4vga::Pixel>,
+    bg: Option<m4vga::Pixel>,
+) -> AChar {
+    let a = old.with_ascii_char(ch);
+    let a = match fg {
+        Some(fg) => a.with_foreground(fg),
+        None => a,
+    };
+    match bg {
+        Some(bg) => a.with_background(bg),
+        None => a,
+    }
+}
+
+/// Checks whether `c` separates words for `Cursor::put_paragraph`.
+fn is_space(c: u8) -> bool {
+    c == b' ' || c == b'\t' || c == b'\n' || c == b'\r'
+}
+
+/// Skips whitespace in `text` starting at `pos`. Returns the position of the
+/// next word, or `text.len()`, and the number of line breaks skipped.
+fn skip_space(text: &[u8], mut pos: usize) -> (usize, usize) {
+    let mut newlines = 0;
+    while pos < text.len() && is_space(text[pos]) {
+        if text[pos] == b'\n' {
+            newlines += 1;
+        }
+        pos += 1;
+    }
+    (pos, newlines)
+}
+
+/// Returns the number of rows `Cursor::put_paragraph` needs to lay out all of
+/// `text` in a rectangle `width` columns wide.
+pub fn paragraph_rows(text: &[u8], width: usize) -> usize {
+    if width == 0 {
+        return 0;
+    }
+    let mut pos = 0;
+    let mut rows = 0;
+    loop {
+        let (start, newlines) = skip_space(text, pos);
+        if start == text.len() {
+            return rows;
+        }
+        if newlines >= 2 && rows > 0 {
+            // Blank row for the paragraph break.
+            rows += 1;
+        }
+        pos = break_line(text, start, width);
+        rows += 1;
+    }
+}
+
+/// Calls `f` with the byte range of `text` that `Cursor::put_paragraph` puts
+/// on each row, in order, when laying all of it out `width` columns wide. The
+/// blank row of a paragraph break gets an empty range.
+///
+/// Laying out a range on its own, one row tall and `width` wide, gives the
+/// same row again, which is how to draw any rows of a text without starting
+/// from the top.
+pub(crate) fn paragraph_lines(
+    text: &[u8],
+    width: usize,
+    mut f: impl FnMut(core::ops::Range<usize>),
+) {
+    if width == 0 {
+        return;
+    }
+    let mut pos = 0;
+    let mut row = 0;
+    loop {
+        let (start, newlines) = skip_space(text, pos);
+        if start == text.len() {
+            return;
+        }
+        if newlines >= 2 && row > 0 {
+            f(start..start);
+        }
+        pos = break_line(text, start, width);
+        f(start..pos);
+        row += 1;
+    }
+}
+
+/// Finds the end of the line of prose starting with the word at `start`,
+/// when lines are `width` cells wide and words are separated by one space.
+/// The line ends early at a paragraph break. A first word too long for the
+/// line is split at `width`.
+fn break_line(text: &[u8], start: usize, width: usize) -> usize {
+    let mut end = start;
+    let mut len = 0;
+    loop {
+        let (word, newlines) = skip_space(text, end);
+        if word == text.len() || (len > 0 && newlines >= 2) {
+            return end;
+        }
+        let mut word_end = word;
+        while word_end < text.len() && !is_space(text[word_end]) {
+            word_end += 1;
+        }
+
+        let needed = if len == 0 { 0 } else { len + 1 } + word_end - word;
+        if needed <= width {
+            len = needed;
+            end = word_end;
+        } else if len == 0 {
+            return word + width;
+        } else {
+            return end;
+        }
+    }
+}
+
+/// Widest window `Cursor::measure` lays text out in: the number of columns
+/// in its row bitmaps, wider than any text mode.
+pub const MEASURE_WIDTH: usize = 128;
+
+/// Layout state for `Cursor::measure`.
+///
+/// This mirrors `Cursor::try_putc` step for step, but instead of a buffer it
+/// keeps one bit per column recording which cells hold something other than
+/// a space. Only the current row and the one above it are kept, because word
+/// wrap never reaches back further than that.
+struct Measure {
+    width: usize,
+    tab_width: usize,
+    wrap: bool,
+    row: usize,
+    col: usize,
+    word_start: Option<(usize, usize)>,
+    /// Non-space cells of the current row, bit 0 being column 0.
+    cur: u128,
+    /// Non-space cells of the previous row.
+    prev: u128,
+    /// Whether anything has been written to the current row.
+    touched: bool,
+    /// Widest row before `prev`.
+    widest: usize,
+}
+
+impl Measure {
+    fn putc(&mut self, c: u8) {
+        match c {
+            b'\n' => {
+                let col = self.col;
+                self.put_range(col, self.width, b' ');
+                self.word_start = None;
+                self.line_feed();
+            }
+            b'\r' => {
+                self.word_start = None;
+                self.col = 0;
+            }
+            BACKSPACE => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    let col = self.col;
+                    self.put_range(col, col + 1, b' ');
+                    if self.word_start == Some((self.row, col)) {
+                        self.word_start = None;
+                    }
+                }
+            }
+            b'\t' => {
+                let stop = (self.col / self.tab_width + 1) * self.tab_width;
+                let stop = usize::min(stop, self.width);
+                let col = self.col;
+                self.put_range(col, stop, b' ');
+                self.word_start = None;
+                self.col = stop;
+                if self.col == self.width {
+                    self.line_feed();
+                }
+            }
+            _ => {
+                if self.wrap {
+                    self.track_word(c);
+                }
+                let col = self.col;
+                self.put_range(col, col + 1, c);
+                self.col += 1;
+                if self.col == self.width {
+                    self.line_feed();
+                }
+            }
+        }
+    }
+
+    /// Records `c` being written to columns `start` up to `end` of the
+    /// current row.
+    fn put_range(&mut self, start: usize, end: usize, c: u8) {
+        if start < end {
+            self.touched = true;
+        }
+        let bits = mask(end) & !mask(start);
+        if c == b' ' {
+            self.cur &= !bits;
+        } else {
+            self.cur |= bits;
+        }
+    }
+
+    fn line_feed(&mut self) {
+        self.widest = usize::max(self.widest, extent(self.prev));
+        self.prev = self.cur;
+        self.cur = 0;
+        self.touched = false;
+        self.col = 0;
+        self.row += 1;
+    }
+
+    /// Equivalent of `Cursor::track_word`.
+    fn track_word(&mut self, c: u8) {
+        if c == b' ' {
+            self.word_start = None;
+            return;
+        }
+
+        match self.word_start {
+            None => self.word_start = Some((self.row, self.col)),
+            Some((start_row, start_col)) => {
+                let wrapped = self.col == 0 && start_row + 1 == self.row;
+                if wrapped && start_col != 0 {
+                    let len = self.width - start_col;
+                    let moved = (self.prev >> start_col) & mask(len);
+                    self.prev &= mask(start_col);
+                    self.cur = (self.cur & !mask(len)) | moved;
+                    self.touched = true;
+                    self.col = len;
+                    self.word_start = Some((self.row, 0));
+                }
+            }
+        }
+    }
+}
+
+/// Returns a `Measure` row bitmap with the first `n` columns set.
+fn mask(n: usize) -> u128 {
+    if n >= MEASURE_WIDTH {
+        !0
+    } else {
+        (1 << n) - 1
+    }
+}
+
+/// Returns the width of a `Measure` row bitmap, through its last set bit.
+fn extent(row: u128) -> usize {
+    MEASURE_WIDTH - row.leading_zeros() as usize
+}
+
+/// Length of `LineBuf`: a full row of the widest text mode the driver could
+/// display, assuming glyphs at least 8 pixels wide.
+const LINE_BUF_LEN: usize = m4vga::MAX_PIXELS_PER_LINE / 8;
+
+/// Row-sized scratch buffer for formatting text before positioning it.
+///
+/// Since anything wider than a row would be clipped anyway, once the buffer
+/// fills up the oldest characters are discarded, keeping the rightmost
+/// `LINE_BUF_LEN`.
+struct LineBuf {
+    buf: [u8; LINE_BUF_LEN],
+    len: usize,
+}
+
+impl fmt::Write for LineBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.len == LINE_BUF_LEN {
+                for i in 1..LINE_BUF_LEN {
+                    self.buf[i - 1] = self.buf[i];
+                }
+                self.len -= 1;
+            }
+            self.buf[self.len] = cp437::encode(c);
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Allows use of a `Cursor` in formatting and `write!`.
+///
+/// Characters are converted with `cp437::encode`. Running off the end of the
+/// buffer is reported as `fmt::Error` rather than a panic.
+impl<'a> core::fmt::Write for Cursor<'a> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            self.try_putc(cp437::encode(c))
+                .map_err(|_| core::fmt::Error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLS: usize = 10;
+    const ROWS: usize = 4;
+
+    fn blank() -> [AChar; COLS * ROWS] {
+        [AChar::from_ascii_char(b'.'); COLS * ROWS]
+    }
+
+    fn row_text(buf: &[AChar], row: usize) -> String {
+        buf[row * COLS..(row + 1) * COLS]
+            .iter()
+            .map(|c| c.char())
+            .collect()
+    }
+
+    #[test]
+    fn size_comes_from_buffer() {
+        let mut buf = blank();
+        let c = Cursor::new(&mut buf, COLS);
+        assert_eq!(c.size(), (ROWS, COLS));
+
+        // A partial trailing row is ignored.
+        let c = Cursor::new(&mut buf[..COLS * ROWS - 1], COLS);
+        assert_eq!(c.size(), (ROWS - 1, COLS));
+    }
+
+    #[test]
+    fn goto_uses_stride() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(2, 3);
+            c.putc(b'x');
+            assert_eq!(c.position(), (2, 4));
+        }
+        assert_eq!(buf[2 * COLS + 3].char(), 'x');
+    }
+
+    #[test]
+    fn wraps_at_width() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(0, 8);
+            c.puts(b"abc");
+            assert_eq!(c.position(), (1, 1));
+        }
+        assert_eq!(row_text(&buf, 0), "........ab");
+        assert_eq!(row_text(&buf, 1), "c.........");
+    }
+
+    #[test]
+    fn scrolls_at_height() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(ROWS - 1, 0);
+            c.puts(b"a\nb");
+            assert_eq!(c.position(), (ROWS - 1, 1));
+        }
+        assert_eq!(row_text(&buf, ROWS - 2), "a         ");
+        assert!(row_text(&buf, ROWS - 1).starts_with('b'));
+    }
+
+    #[test]
+    fn backspace_blanks_previous_cell() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(1, 0);
+            c.puts(b"123\x084");
+            assert_eq!(c.position(), (1, 3));
+        }
+        assert_eq!(row_text(&buf, 1), "124.......");
+    }
+
+    #[test]
+    fn backspace_stops_at_column_0() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(1, 1);
+            c.puts(b"\x08\x08\x08");
+            assert_eq!(c.position(), (1, 0));
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), " .........");
+    }
+
+    #[test]
+    fn carriage_return_stays_on_row() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.puts(b"abc\rx");
+            assert_eq!(c.position(), (0, 1));
+        }
+        assert_eq!(row_text(&buf, 0), "xbc.......");
+    }
+
+    #[test]
+    fn newline_at_col_0_blanks_row() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.puts(b"\n\n");
+            assert_eq!(c.position(), (2, 0));
+        }
+        assert_eq!(row_text(&buf, 0), "          ");
+        assert_eq!(row_text(&buf, 1), "          ");
+        assert_eq!(row_text(&buf, 2), "..........");
+    }
+
+    #[test]
+    fn newline_mid_row_blanks_rest_of_row() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(1, 1);
+            c.puts(b"\n");
+            assert_eq!(c.position(), (2, 0));
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), ".         ");
+        assert_eq!(row_text(&buf, 2), "..........");
+    }
+
+    #[test]
+    fn newline_at_last_col_blanks_one_cell() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(1, COLS - 1);
+            c.puts(b"\n");
+            assert_eq!(c.position(), (2, 0));
+        }
+        assert_eq!(row_text(&buf, 1), "......... ");
+        assert_eq!(row_text(&buf, 2), "..........");
+    }
+
+    #[test]
+    fn crlf_acts_like_newline() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.puts(b"ab\r\ncd\r\n");
+            assert_eq!(c.position(), (2, 0));
+        }
+        // The newline blanks from the start of the row, where the return
+        // left the cursor.
+        assert_eq!(row_text(&buf, 0), "          ");
+        assert_eq!(row_text(&buf, 1), "          ");
+        assert_eq!(row_text(&buf, 2), "..........");
+    }
+
+    #[test]
+    fn transparent_background_keeps_cell_color() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.bg = 0b11;
+            c.fill_rect(0, 0, 1, COLS, b' ');
+            c.bg = 0b1100;
+            c.bg_transparent = true;
+            c.fg = 0b110000;
+            c.puts(b"hi");
+        }
+        assert_eq!(row_text(&buf, 0), "hi        ");
+        assert_eq!(buf[0].background(), 0b11);
+        assert_eq!(buf[1].foreground(), 0b110000);
+    }
+
+    #[test]
+    fn transparent_foreground_keeps_cell_color() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.fg = 0b11;
+            c.puts(b"ab");
+            c.goto(0, 0);
+            c.fg_transparent = true;
+            c.fg = 0b1100;
+            c.bg = 0b110000;
+            c.putc(b'x');
+        }
+        assert_eq!(row_text(&buf, 0), "xb........");
+        assert_eq!(buf[0].foreground(), 0b11);
+        assert_eq!(buf[0].background(), 0b110000);
+    }
+
+    fn all_text(buf: &[AChar]) -> String {
+        buf.iter().map(|c| c.char()).collect()
+    }
+
+    /// Fills the buffer with distinct letters so copies can be traced.
+    fn lettered() -> [AChar; COLS * ROWS] {
+        let mut buf = blank();
+        for (i, a) in buf.iter_mut().enumerate() {
+            *a = AChar::from_ascii_char(b'A' + (i % 26) as u8);
+        }
+        buf
+    }
+
+    /// Copies `src` to (`dst_row`, `dst_col`) cell by cell from a snapshot,
+    /// which is trivially correct for overlapping rectangles.
+    fn copy_reference(
+        buf: &mut [AChar],
+        src: Rect,
+        dst_row: usize,
+        dst_col: usize,
+    ) {
+        let before = buf.to_vec();
+        for r in 0..src.h {
+            for c in 0..src.w {
+                let (dr, dc) = (dst_row + r, dst_col + c);
+                if src.row + r < ROWS
+                    && src.col + c < COLS
+                    && dr < ROWS
+                    && dc < COLS
+                {
+                    buf[dr * COLS + dc] =
+                        before[(src.row + r) * COLS + src.col + c];
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn copy_region_overlapping() {
+        let src = Rect::new(1, 2, 2, 5);
+        for &(dr, dc) in &[
+            (0, 2),
+            (2, 2),
+            (1, 1),
+            (1, 3),
+            (0, 1),
+            (0, 3),
+            (2, 1),
+            (2, 3),
+        ] {
+            let mut buf = lettered();
+            Cursor::new(&mut buf, COLS).copy_region(src, dr, dc);
+            let mut expected = lettered();
+            copy_reference(&mut expected, src, dr, dc);
+            assert_eq!(
+                all_text(&buf),
+                all_text(&expected),
+                "copy to ({}, {}) clobbered its source",
+                dr,
+                dc
+            );
+        }
+    }
+
+    #[test]
+    fn copy_region_clips() {
+        let mut buf = lettered();
+        Cursor::new(&mut buf, COLS).copy_region(
+            Rect::new(0, 0, ROWS, COLS),
+            ROWS - 1,
+            COLS - 2,
+        );
+        let mut expected = lettered();
+        copy_reference(
+            &mut expected,
+            Rect::new(0, 0, ROWS, COLS),
+            ROWS - 1,
+            COLS - 2,
+        );
+        assert_eq!(all_text(&buf), all_text(&expected));
+
+        // Destinations entirely outside the window do nothing.
+        Cursor::new(&mut buf, COLS).copy_region(Rect::new(0, 0, 2, 2), ROWS, 0);
+        assert_eq!(all_text(&buf), all_text(&expected));
+    }
+
+    #[test]
+    fn fill_pattern_tiles_and_clips() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.fill_pattern(Rect::new(1, 6, 9, 9), b"abc", 1);
+            c.fill_pattern(Rect::new(0, 0, 1, 4), b"", 0);
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), "......bcab");
+        assert_eq!(row_text(&buf, 3), "......bcab");
+    }
+
+    #[test]
+    fn clear_resets_cursor() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(2, 5);
+            c.bg = 0b11;
+            c.clear();
+            assert_eq!(c.position(), (0, 0));
+        }
+        for row in 0..ROWS {
+            assert_eq!(row_text(&buf, row), "          ");
+        }
+        assert!(buf.iter().all(|a| a.background() == 0b11));
+    }
+
+    #[test]
+    fn clear_rows_in_window() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let mut w = c.window(1, 2, 3, 4);
+            w.clear_rows(1, 10);
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), "..........");
+        assert_eq!(row_text(&buf, 2), "..    ....");
+        assert_eq!(row_text(&buf, 3), "..    ....");
+    }
+
+    #[test]
+    fn goto_clamps() {
+        let mut buf = blank();
+        let mut c = Cursor::new(&mut buf, COLS);
+        c.goto(ROWS + 3, 2);
+        assert_eq!(c.position(), (ROWS - 1, 2));
+        c.goto(1, 0usize.wrapping_sub(4));
+        assert_eq!(c.position(), (1, COLS - 1));
+    }
+
+    #[test]
+    fn try_goto_reports_position() {
+        let mut buf = blank();
+        let mut c = Cursor::new(&mut buf, COLS);
+        assert_eq!(c.try_goto(2, 3), Ok(()));
+        assert_eq!(
+            c.try_goto(2, COLS),
+            Err(CursorError::OutOfBounds { row: 2, col: COLS })
+        );
+        assert_eq!(c.position(), (2, 3));
+    }
+
+    #[test]
+    fn putc_n_and_pad_to() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.putc_n(b'-', 3);
+            c.pad_to(6);
+            c.putc(b'|');
+            c.pad_to(2);
+            assert_eq!(c.position(), (0, 7));
+            c.putc_n(b'x', 4);
+            assert_eq!(c.position(), (1, 1));
+            c.pad_to(COLS + 5);
+            assert_eq!(c.position(), (2, 0));
+        }
+        assert_eq!(row_text(&buf, 0), "---   |xxx");
+        assert_eq!(row_text(&buf, 1), "x         ");
+    }
+
+    #[test]
+    fn puts_at_and_write_at() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            assert_eq!(c.puts_at(1, 2, b"ab"), Ok(()));
+            assert_eq!(c.position(), (1, 4));
+            assert!(write_at!(c, 2, 7, "{}", 42).is_ok());
+            assert_eq!(c.position(), (2, 9));
+            assert_eq!(
+                c.puts_at(ROWS, 0, b"zz"),
+                Err(CursorError::OutOfBounds { row: ROWS, col: 0 })
+            );
+            assert!(write_at!(c, 0, COLS, "zz").is_err());
+            assert_eq!(c.position(), (2, 9));
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), "..ab......");
+        assert_eq!(row_text(&buf, 2), ".......42.");
+    }
+
+    #[test]
+    fn fill_rows_spans_window() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let mut w = c.window(0, 1, ROWS, 8);
+            w.fill_row(0, b'=');
+            w.fill_rows(2, ROWS + 1, b'#');
+            w.fill_rows(2, 1, b'!');
+        }
+        assert_eq!(row_text(&buf, 0), ".========.");
+        assert_eq!(row_text(&buf, 1), "..........");
+        assert_eq!(row_text(&buf, 2), ".########.");
+        assert_eq!(row_text(&buf, ROWS - 1), ".########.");
+    }
+
+    #[test]
+    fn recolor_region_keeps_text() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.fg = 0b01;
+            c.bg = 0b10;
+            c.puts(b"abcd");
+            c.recolor_region(Rect::new(0, 1, 1, 2), None, Some(0b11));
+            c.recolor_region(Rect::new(0, 2, 1, COLS), Some(0b1100), None);
+        }
+        assert_eq!(row_text(&buf, 0), "abcd......");
+        let colors: Vec<_> = buf[..4]
+            .iter()
+            .map(|a| (a.foreground(), a.background()))
+            .collect();
+        assert_eq!(
+            colors,
+            [(0b01, 0b10), (0b01, 0b11), (0b1100, 0b11), (0b1100, 0b10)]
+        );
+    }
+
+    #[test]
+    fn measure_agrees_with_puts() {
+        const TEXTS: &[&[u8]] = &[
+            b"",
+            b"hi",
+            b"hello world",
+            b"the quick brown fox jumps over the lazy dog",
+            b"supercalifragilistic word",
+            b"a\n\nb",
+            b"trailing   \n",
+            b"a\n\n",
+            b"x\ty\tz\tend",
+            b"abc\x08\x08",
+            b"   \r",
+            b"long line\r\nnext",
+            b"12345678901234567890",
+            b"one two  three\tfour five six seven",
+        ];
+        const W: usize = 40;
+        const H: usize = 64;
+        for &width in &[1, 4, 5, 8, 12] {
+            for &wrap in &[false, true] {
+                for &text in TEXTS {
+                    let mut buf = [AChar::from_ascii_char(b'.'); W * H];
+                    let (predicted, (row, col)) = {
+                        let mut c = Cursor::new(&mut buf, W);
+                        c.set_wrap(wrap);
+                        c.set_tab_width(3);
+                        let predicted = c.measure(text, width);
+                        let mut w = c.window(0, 0, H, width);
+                        w.set_wrap(wrap);
+                        w.set_tab_width(3);
+                        w.puts(text);
+                        (predicted, w.position())
+                    };
+
+                    let mut lines = row + (col > 0) as usize;
+                    let mut widest = 0;
+                    for r in 0..H {
+                        let cells = &buf[r * W..r * W + width];
+                        if cells.iter().any(|a| a.char() != '.') {
+                            lines = usize::max(lines, r + 1);
+                        }
+                        if let Some(i) = cells
+                            .iter()
+                            .rposition(|a| a.char() != '.' && a.char() != ' ')
+                        {
+                            widest = usize::max(widest, i + 1);
+                        }
+                    }
+                    assert_eq!(
+                        predicted,
+                        (lines, widest),
+                        "{:?} at width {}, wrap {}",
+                        String::from_utf8_lossy(text),
+                        width,
+                        wrap
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn measure_clamps_width() {
+        const W: usize = 200;
+        let mut buf = [AChar::from_ascii_char(b'.'); W * 2];
+        let c = Cursor::new(&mut buf, W);
+        let long = [b'x'; 150];
+        assert_eq!(c.measure(&long, W), (2, MEASURE_WIDTH));
+        assert_eq!(c.measure(&long[..MEASURE_WIDTH], W), (1, MEASURE_WIDTH));
+        assert_eq!(c.measure(b"ab", 0), (2, 1));
+    }
+
+    #[test]
+    fn puts_truncated_marks_cut() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.puts_truncated(b"abc", 3);
+            c.puts_truncated(b"defg", 3);
+            assert_eq!(c.position(), (0, 6));
+            c.puts_truncated(b"hi", 0);
+            c.puts_truncated(b"hi", 1);
+            c.puts_truncated(b"j", 1);
+            assert_eq!(c.puts_truncated_at(1, 6, b"klmnop", 20), Ok(()));
+            assert!(c.puts_truncated_at(ROWS, 0, b"q", 1).is_err());
+        }
+        let mark = TRUNCATION_MARK as char;
+        assert_eq!(row_text(&buf, 0), format!("abcde{}{}j..", mark, mark));
+        assert_eq!(row_text(&buf, 1), format!("......klm{}", mark));
+    }
+
+    #[test]
+    fn truncating_write_stops_at_corner() {
+        use core::fmt::Write;
+
+        const W: usize = 80;
+        const H: usize = 37;
+        let mut buf = [AChar::from_ascii_char(b'.'); W * H];
+        let long: String = (0..100).map(|i| (b'a' + i % 26) as char).collect();
+        {
+            let mut c = Cursor::new(&mut buf, W);
+            c.goto(36, 70);
+            assert!(write!(c.truncating(usize::MAX), "{}", long).is_ok());
+            assert_eq!(c.position(), (36, 79));
+            // The cursor parks on the last cell, so the next write lands
+            // there rather than scrolling.
+            assert!(write!(c.truncating(usize::MAX), "!?").is_ok());
+            assert_eq!(c.position(), (36, 79));
+        }
+        let text: String = buf.iter().map(|a| a.char()).collect();
+        assert!(text[..36 * W].chars().all(|c| c == '.'));
+        assert_eq!(
+            &text[36 * W..],
+            format!("{}{}!", ".".repeat(70), &long[..9])
+        );
+    }
+
+    #[test]
+    fn truncating_write_respects_limit() {
+        use core::fmt::Write;
+
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(1, 2);
+            assert!(write!(c.truncating(3), "{}{}", 12, 345).is_ok());
+            assert_eq!(c.position(), (1, 5));
+            c.goto(2, 0);
+            assert!(write!(c.truncating(COLS), "ab\ncd").is_ok());
+            assert_eq!(c.position(), (2, 2));
+        }
+        assert_eq!(row_text(&buf, 1), "..123.....");
+        assert_eq!(row_text(&buf, 2), "ab........");
+        assert_eq!(row_text(&buf, 3), "..........");
+    }
+
+    #[test]
+    fn paragraph_wraps_and_collapses_space() {
+        let mut buf = blank();
+        let used = Cursor::new(&mut buf, COLS).put_paragraph(
+            Rect::new(0, 1, ROWS, 8),
+            b"  one two\nthree   four\tfive\n",
+        );
+        assert_eq!(used, 28);
+        assert_eq!(row_text(&buf, 0), ".one two .");
+        assert_eq!(row_text(&buf, 1), ".three   .");
+        assert_eq!(row_text(&buf, 2), ".four    .");
+        assert_eq!(row_text(&buf, 3), ".five    .");
+    }
+
+    #[test]
+    fn paragraph_exact_width_lines() {
+        let mut buf = blank();
+        Cursor::new(&mut buf, COLS)
+            .put_paragraph(Rect::new(0, 0, ROWS, 7), b"abc def ghijklm n");
+        assert_eq!(row_text(&buf, 0), "abc def...");
+        assert_eq!(row_text(&buf, 1), "ghijklm...");
+        assert_eq!(row_text(&buf, 2), "n      ...");
+    }
+
+    #[test]
+    fn paragraph_splits_long_words() {
+        let mut buf = blank();
+        Cursor::new(&mut buf, COLS)
+            .put_paragraph(Rect::new(0, 0, ROWS, 4), b"a bcdefghij k");
+        assert_eq!(row_text(&buf, 0), "a   ......");
+        assert_eq!(row_text(&buf, 1), "bcde......");
+        assert_eq!(row_text(&buf, 2), "fghi......");
+        assert_eq!(row_text(&buf, 3), "j k ......");
+    }
+
+    #[test]
+    fn paragraph_breaks_and_paging() {
+        let text: &[u8] = b"aa bb\n\n\ncc dd ee";
+        let mut buf = blank();
+        let used = Cursor::new(&mut buf, COLS)
+            .put_paragraph(Rect::new(1, 0, 2, 5), text);
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), "aa bb.....");
+        assert_eq!(row_text(&buf, 2), "     .....");
+        assert_eq!(row_text(&buf, 3), "..........");
+
+        // The next page starts with the next paragraph, not a blank line.
+        let rest = &text[used..];
+        let used2 = Cursor::new(&mut buf, COLS)
+            .put_paragraph(Rect::new(1, 0, 2, 5), rest);
+        assert_eq!(used2, rest.len());
+        assert_eq!(row_text(&buf, 1), "cc dd.....");
+        assert_eq!(row_text(&buf, 2), "ee   .....");
+    }
+
+    #[test]
+    fn paragraph_rows_agrees_with_layout() {
+        const TEXTS: &[&[u8]] = &[
+            b"",
+            b"  \n\n ",
+            b"one",
+            b"aa bb\n\n\ncc dd ee",
+            b"\n\nlead  and trail\n\n",
+            b"a bcdefghij k",
+            b"the quick brown fox jumps over the lazy dog",
+        ];
+        for &text in TEXTS {
+            for w in 1..=COLS {
+                let rows = paragraph_rows(text, w);
+                let mut lines = 0;
+                paragraph_lines(text, w, |_| lines += 1);
+                assert_eq!(lines, rows, "{:?} at {}", text, w);
+                let mut buf = [AChar::from_ascii_char(b'.'); COLS * 64];
+                let used = Cursor::new(&mut buf, COLS)
+                    .put_paragraph(Rect::new(0, 0, rows, w), text);
+                assert_eq!(used, text.len(), "{:?} at {}", text, w);
+                if rows > 0 {
+                    let used = Cursor::new(&mut buf, COLS)
+                        .put_paragraph(Rect::new(0, 0, rows - 1, w), text);
+                    assert!(used < text.len(), "{:?} at {}", text, w);
+                }
+            }
+        }
+        assert_eq!(paragraph_rows(b"abc", 0), 0);
+    }
+
+    #[test]
+    fn justified_spreads_gaps_from_left() {
+        let mut buf = blank();
+        let text: &[u8] = b"a b c d e f g h i j k l\n\nmm nn oo pp qq rr";
+        Cursor::new(&mut buf, COLS)
+            .put_justified(Rect::new(0, 0, ROWS, 8), text);
+        // 4 letters and 3 gaps: 4 spaces spread 2, 1, 1.
+        assert_eq!(row_text(&buf, 0), "a  b c d..");
+        assert_eq!(row_text(&buf, 1), "e  f g h..");
+        // Last line of a paragraph stays ragged.
+        assert_eq!(row_text(&buf, 2), "i j k l ..");
+        assert_eq!(row_text(&buf, 3), "        ..");
+    }
+
+    #[test]
+    fn justified_single_word_and_last_line() {
+        let mut buf = blank();
+        let text: &[u8] = b"abcdef gh ij kl";
+        let used = Cursor::new(&mut buf, COLS)
+            .put_justified(Rect::new(0, 0, 3, 7), text);
+        assert_eq!(used, text.len());
+        assert_eq!(row_text(&buf, 0), "abcdef ...");
+        assert_eq!(row_text(&buf, 1), "gh   ij...");
+        assert_eq!(row_text(&buf, 2), "kl     ...");
+    }
+
+    #[test]
+    fn shadow_darkens_background_only() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.fg = 0b11_00_00;
+            c.bg = 0b11_10_01;
+            c.fill_rect(0, 0, ROWS, COLS, b'x');
+            c.draw_shadow(Rect::new(0, 6, 2, 3));
+            // Clipped at the right edge.
+            c.draw_shadow(Rect::new(2, 7, 1, 5));
+        }
+        let shaded: Vec<String> = (0..ROWS)
+            .map(|r| {
+                buf[r * COLS..(r + 1) * COLS]
+                    .iter()
+                    .map(|a| {
+                        assert_eq!(a.char(), 'x');
+                        assert_eq!(a.foreground(), 0b11_00_00);
+                        match a.background() {
+                            0b11_10_01 => '.',
+                            0b01_01_00 => '#',
+                            _ => '?',
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            shaded,
+            ["..........", ".........#", ".......###", "........##"]
+        );
+    }
+
+    #[test]
+    fn builder_sets_colors_and_position() {
+        let mut buf = blank();
+        {
+            let c = Cursor::new(&mut buf, COLS);
+            assert_eq!((c.fg, c.bg), (DEFAULT_FG, DEFAULT_BG));
+        }
+        {
+            let mut c = Cursor::new(&mut buf, COLS).fg(3).bg(12).at(2, 4);
+            assert_eq!(c.position(), (2, 4));
+            c.putc(b'x');
+            let c = Cursor::new_with(&mut buf, COLS, 5, 6).at(ROWS, COLS);
+            assert_eq!(c.position(), (ROWS - 1, COLS - 1));
+            assert_eq!((c.fg, c.bg), (5, 6));
+        }
+        assert_eq!(row_text(&buf, 2), "....x.....");
+        assert_eq!(buf[2 * COLS + 4].foreground(), 3);
+        assert_eq!(buf[2 * COLS + 4].background(), 12);
+    }
+
+    #[test]
+    fn rect_contains_and_intersect() {
+        let r = Rect::new(2, 3, 4, 5);
+        assert!(r.contains(2, 3));
+        assert!(r.contains(5, 7));
+        assert!(!r.contains(6, 7));
+        assert!(!r.contains(5, 8));
+        assert!(!r.contains(1, 4));
+        assert!(!Rect::new(0, 0, 0, 3).contains(0, 0));
+
+        assert_eq!(
+            r.intersect(&Rect::new(0, 5, 3, 10)),
+            Some(Rect::new(2, 5, 1, 3))
+        );
+        assert_eq!(r.intersect(&Rect::new(0, 0, 10, 10)), Some(r));
+        // Touching edges don't overlap.
+        assert_eq!(r.intersect(&Rect::new(6, 3, 2, 2)), None);
+        assert_eq!(r.intersect(&Rect::new(2, 8, 2, 2)), None);
+    }
+
+    #[test]
+    fn rect_centered() {
+        let r = Rect::new(10, 20, 5, 11);
+        assert_eq!(r.centered(3, 9), Rect::new(11, 21, 3, 9));
+        assert_eq!(r.centered(2, 4), Rect::new(11, 23, 2, 4));
+        assert_eq!(r.centered(9, 11), Rect::new(8, 20, 9, 11));
+        assert_eq!(Rect::new(0, 0, 2, 2).centered(6, 6), Rect::new(0, 0, 6, 6));
+    }
+
+    #[test]
+    fn rect_center_within_fits() {
+        let parent = Rect::new(0, 0, 36, 80);
+        assert_eq!(
+            Rect::center_within(&parent, 30, 7),
+            Rect::new(14, 25, 7, 30)
+        );
+        assert_eq!(
+            Rect::center_within(&Rect::new(2, 3, 4, 5), 9, 1),
+            Rect::new(3, 3, 1, 5)
+        );
+        assert_eq!(
+            Rect::center_within(&Rect::new(2, 3, 0, 0), 4, 4),
+            Rect::new(2, 3, 0, 0)
+        );
+    }
+
+    #[test]
+    fn rect_inset() {
+        let r = Rect::new(2, 3, 6, 10);
+        assert_eq!(r.inset(0), r);
+        assert_eq!(r.inset(1), Rect::new(3, 4, 4, 8));
+        assert_eq!(r.inset(3), Rect::new(5, 6, 0, 4));
+        assert_eq!(r.inset(9), Rect::new(5, 8, 0, 0));
+        assert_eq!(r.inset(usize::MAX), Rect::new(5, 8, 0, 0));
+        assert_eq!(Rect::new(0, 0, 1, 1).inset(1), Rect::new(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn rect_split() {
+        let r = Rect::new(2, 3, 4, 10);
+        assert_eq!(
+            r.split_h(4),
+            (Rect::new(2, 3, 4, 4), Rect::new(2, 7, 4, 6))
+        );
+        assert_eq!(r.split_h(0), (Rect::new(2, 3, 4, 0), r));
+        assert_eq!(r.split_h(11), (r, Rect::new(2, 13, 4, 0)));
+        assert_eq!(
+            r.split_v(1),
+            (Rect::new(2, 3, 1, 10), Rect::new(3, 3, 3, 10))
+        );
+        assert_eq!(r.split_v(4), (r, Rect::new(6, 3, 0, 10)));
+        assert_eq!(r.split_v(usize::MAX), (r, Rect::new(6, 3, 0, 10)));
+    }
+
+    #[test]
+    fn rect_clamp_to() {
+        let parent = Rect::new(2, 3, 4, 5);
+        assert_eq!(Rect::new(0, 0, 10, 10).clamp_to(&parent), parent);
+        assert_eq!(
+            Rect::new(3, 6, 9, 9).clamp_to(&parent),
+            Rect::new(3, 6, 3, 2)
+        );
+        assert_eq!(parent.clamp_to(&parent), parent);
+        // Off to the bottom right, and above left: empty on the edge.
+        assert_eq!(
+            Rect::new(9, 9, 2, 2).clamp_to(&parent),
+            Rect::new(6, 8, 0, 0)
+        );
+        assert_eq!(
+            Rect::new(0, 0, 1, 1).clamp_to(&parent),
+            Rect::new(2, 3, 0, 0)
+        );
+        assert_eq!(
+            Rect::new(usize::MAX, 4, usize::MAX, 1).clamp_to(&parent),
+            Rect::new(6, 4, 0, 1)
+        );
+    }
+
+    #[test]
+    fn window_inside_stride() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let mut w = c.window(1, 2, 2, 3);
+            w.puts(b"abcd");
+        }
+        assert_eq!(row_text(&buf, 1), "..abc.....");
+        assert_eq!(row_text(&buf, 2), "..d.......");
+    }
+
+    #[test]
+    fn word_wrap_moves_cut_word_down() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.set_wrap(true);
+            c.puts(b"one two three");
+            assert_eq!(c.position(), (1, 5));
+            // Too long for any row, so split where it falls.
+            c.goto(2, 0);
+            c.puts(b"abcdefghijkl");
+        }
+        assert_eq!(row_text(&buf, 0), "one two   ");
+        assert_eq!(row_text(&buf, 1), "three.....");
+        assert_eq!(row_text(&buf, 2), "abcdefghij");
+        assert_eq!(row_text(&buf, 3), "kl........");
+    }
+
+    #[test]
+    fn scroll_blanks_bottom_row_in_current_colors() {
+        let before = lettered();
+        let mut buf = lettered();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).bg(0b11);
+            c.goto(ROWS - 1, COLS - 1);
+            c.putc(b'!');
+            assert_eq!(c.position(), (ROWS - 1, 0));
+        }
+        for row in 0..ROWS - 2 {
+            assert_eq!(row_text(&buf, row), row_text(&before, row + 1));
+        }
+        assert!(row_text(&buf, ROWS - 2).ends_with('!'));
+        assert_eq!(row_text(&buf, ROWS - 1), "          ");
+        let bottom = &buf[(ROWS - 1) * COLS..];
+        assert!(bottom.iter().all(|a| a.background() == 0b11));
+    }
+
+    #[test]
+    fn no_scroll_runs_off_the_end() {
+        let before = lettered();
+        let mut buf = lettered();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.set_scroll(false);
+            c.goto(ROWS - 1, COLS - 1);
+            c.putc(b'!');
+            assert_eq!(c.position(), (ROWS, 0));
+        }
+        assert_eq!(row_text(&buf, 0), row_text(&before, 0));
+        assert!(row_text(&buf, ROWS - 1).ends_with('!'));
+    }
+
+    #[test]
+    fn try_putc_reports_overflow() {
+        use core::fmt::Write;
+
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.set_scroll(false);
+            c.goto(ROWS - 1, COLS - 2);
+            let past = CursorError::OutOfBounds { row: ROWS, col: 0 };
+            assert_eq!(c.try_puts(b"abc"), Err(past));
+            assert_eq!(c.try_putc(b'd'), Err(past));
+            assert!(write!(c, "e").is_err());
+        }
+        assert_eq!(row_text(&buf, ROWS - 1), "........ab");
+        assert_eq!(all_text(&buf).matches(|c| c != '.').count(), 2);
+    }
+
+    #[test]
+    fn fill_rect_clips_and_keeps_cursor() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).bg(0b11).at(0, 1);
+            c.fill_rect(1, 7, 5, 5, b'#');
+            c.fill_rect(0, 0, 0, COLS, b'#');
+            assert_eq!(c.position(), (0, 1));
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        for row in 1..ROWS {
+            assert_eq!(row_text(&buf, row), ".......###");
+        }
+        assert_eq!(buf[COLS + 7].background(), 0b11);
+        assert_ne!(buf[COLS + 6].background(), 0b11);
+    }
+
+    #[test]
+    fn draw_box_frames_without_filling() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).at(3, 9);
+            c.draw_box(0, 0, 3, 4, &BoxStyle::SINGLE);
+            c.draw_box(1, 6, 0, 3, &BoxStyle::SINGLE);
+            assert_eq!(c.position(), (3, 9));
+        }
+        let at = |row: usize, col: usize| buf[row * COLS + col].ascii_char();
+        assert_eq!([at(0, 0), at(0, 1), at(0, 3)], [0xDA, 0xC4, 0xBF]);
+        assert_eq!([at(1, 0), at(1, 1), at(1, 3)], [0xB3, b'.', 0xB3]);
+        assert_eq!([at(2, 0), at(2, 2), at(2, 3)], [0xC0, 0xC4, 0xD9]);
+        // Nothing drawn outside the frame, nor for an empty box.
+        for row in 0..ROWS {
+            let from = if row < 3 { 4 } else { 0 };
+            assert!((from..COLS).all(|col| at(row, col) == b'.'));
+        }
+    }
+
+    #[test]
+    fn box_styles_clip_and_degenerate_to_lines() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.draw_box(0, 0, 3, 4, &BoxStyle::ASCII);
+            // Clipped at the bottom right.
+            c.draw_box(2, 7, 5, 5, &BoxStyle::ASCII);
+            // One row tall and one column wide.
+            c.draw_box(3, 0, 1, 3, &BoxStyle::ASCII);
+            c.draw_box(0, 5, 2, 1, &BoxStyle::ASCII);
+        }
+        assert_eq!(row_text(&buf, 0), "+--+.|....");
+        assert_eq!(row_text(&buf, 1), "|..|.|....");
+        assert_eq!(row_text(&buf, 2), "+--+...+--");
+        assert_eq!(row_text(&buf, 3), "---....|..");
+
+        let custom = BoxStyle {
+            top_left: b'1',
+            top_right: b'2',
+            bottom_left: b'3',
+            bottom_right: b'4',
+            ..BoxStyle::DOUBLE
+        };
+        let mut buf = blank();
+        Cursor::new(&mut buf, COLS).draw_box(0, 0, 3, 3, &custom);
+        let at = |row: usize, col: usize| buf[row * COLS + col].ascii_char();
+        assert_eq!([at(0, 0), at(0, 1), at(0, 2)], [b'1', 0xCD, b'2']);
+        assert_eq!([at(1, 0), at(1, 1), at(1, 2)], [0xBA, b'.', 0xBA]);
+        assert_eq!([at(2, 0), at(2, 1), at(2, 2)], [b'3', 0xCD, b'4']);
+    }
+
+    #[test]
+    fn puts_centered_pads_both_sides() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).at(3, 3);
+            c.puts_centered(0, b"abc");
+            c.puts_centered(1, b"abcd");
+            c.puts_centered(2, b"abcdefghijkl");
+            assert_eq!(c.position(), (3, 3));
+        }
+        // The odd space goes on the right.
+        assert_eq!(row_text(&buf, 0), "   abc    ");
+        assert_eq!(row_text(&buf, 1), "   abcd   ");
+        assert_eq!(row_text(&buf, 2), "abcdefghij");
+        assert_eq!(row_text(&buf, 3), "..........");
+    }
+
+    #[test]
+    fn puts_right_ends_at_column() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).at(3, 0);
+            c.puts_right(0, COLS - 1, b"abc");
+            c.puts_right(1, 4, b"abc");
+            // Cut off on the left.
+            c.puts_right(2, 2, b"abcdef");
+            assert_eq!(c.position(), (3, 0));
+            write_right!(c, 3, COLS - 1, "{:03}", 7).unwrap();
+        }
+        assert_eq!(row_text(&buf, 0), ".......abc");
+        assert_eq!(row_text(&buf, 1), "..abc.....");
+        assert_eq!(row_text(&buf, 2), "def.......");
+        assert_eq!(row_text(&buf, 3), ".......007");
+    }
+
+    #[test]
+    fn state_stack_restores_position_and_colors() {
+        let mut buf = blank();
+        let mut c = Cursor::new(&mut buf, COLS).fg(1).bg(2).at(1, 2);
+        c.push_state().unwrap();
+        c.fg = 3;
+        c.bg = 4;
+        c.puts(b"abc");
+        c.pop_state().unwrap();
+        assert_eq!(c.position(), (1, 2));
+        assert_eq!((c.fg, c.bg), (1, 2));
+        assert_eq!(c.pop_state(), Err(CursorError::StackEmpty));
+
+        for row in 0..STATE_DEPTH {
+            c.goto(row, 0);
+            c.push_state().unwrap();
+        }
+        assert_eq!(c.push_state(), Err(CursorError::StackFull));
+        c.goto(0, 5);
+        c.pop_state().unwrap();
+        assert_eq!(c.position(), (ROWS - 1, 0));
+    }
+
+    #[test]
+    fn tabs_fill_to_next_stop() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.set_tab_width(4);
+            c.puts(b"a\tb\tc");
+            assert_eq!(c.position(), (0, 9));
+            // Past the last stop, the row is finished.
+            c.puts(b"\td");
+            assert_eq!(c.position(), (1, 1));
+            c.set_tab_width(0);
+            c.putc(b'\t');
+            assert_eq!(c.position(), (1, 2));
+        }
+        assert_eq!(row_text(&buf, 0), "a   b   c ");
+        assert_eq!(row_text(&buf, 1), "d ........");
+    }
+
+    #[test]
+    fn window_clips_and_scrolls_inside() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let mut w = c.window(1, 6, 9, 9);
+            assert_eq!(w.size(), (3, 4));
+            w.puts(b"ab");
+            w.goto(2, 2);
+            // Scrolls the window, and nothing else, up a row.
+            w.puts(b"cdef");
+            assert_eq!(w.position(), (2, 2));
+            let mut inner = w.window(0, 3, 5, 5);
+            assert_eq!(inner.size(), (3, 1));
+            inner.putc(b'x');
+        }
+        assert_eq!(row_text(&buf, 0), "..........");
+        assert_eq!(row_text(&buf, 1), ".........x");
+        assert_eq!(row_text(&buf, 2), "........cd");
+        assert_eq!(row_text(&buf, 3), "......ef  ");
+    }
+
+    fn colors(a: AChar) -> (m4vga::Pixel, m4vga::Pixel) {
+        (a.foreground(), a.background())
+    }
+
+    #[test]
+    fn color_stack_and_styled_guard() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).fg(1).bg(2);
+            c.push_colors().unwrap();
+            c.fg = 3;
+            c.bg = 4;
+            {
+                let mut c = c.styled(5, 6);
+                c.putc(b'a');
+            }
+            c.putc(b'b');
+            c.pop_colors().unwrap();
+            c.putc(b'c');
+            // Only the colors were saved.
+            assert_eq!(c.position(), (0, 3));
+            assert_eq!(c.pop_colors(), Err(CursorError::StackEmpty));
+            for _ in 0..STATE_DEPTH {
+                c.push_colors().unwrap();
+            }
+            assert_eq!(c.push_colors(), Err(CursorError::StackFull));
+        }
+        assert_eq!(row_text(&buf, 0), "abc.......");
+        assert_eq!(colors(buf[0]), (5, 6));
+        assert_eq!(colors(buf[1]), (3, 4));
+        assert_eq!(colors(buf[2]), (1, 2));
+    }
+
+    #[test]
+    fn insert_and_delete_shift_the_row() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).bg(1);
+            c.puts(b"abcdef");
+            c.goto(0, 2);
+            c.bg = 7;
+            c.insert_char(b'X').unwrap();
+            assert_eq!(c.position(), (0, 3));
+            assert_eq!(row_text(&c.buf[..COLS], 0), "abXcdef...");
+            c.delete_char().unwrap();
+            assert_eq!(c.position(), (0, 3));
+            // Stays on the row at its end.
+            c.goto(0, COLS - 2);
+            c.insert_char(b'Y').unwrap();
+            c.insert_char(b'Z').unwrap();
+            assert_eq!(c.position(), (0, COLS - 1));
+        }
+        assert_eq!(row_text(&buf, 0), "abXdef..YZ");
+        assert_eq!(row_text(&buf, 1), "..........");
+        // Shifted cells keep their colors.
+        assert_eq!(buf[3].background(), 1);
+        assert_eq!(buf[2].background(), 7);
+    }
+
+    #[test]
+    fn read_back_cells_and_regions() {
+        let mut buf = lettered();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            assert_eq!(c.char_at(1, 2).unwrap().char(), 'M');
+            let past = CursorError::OutOfBounds { row: ROWS, col: 0 };
+            assert_eq!(c.char_at(ROWS, 0).err(), Some(past));
+
+            let rect = Rect::new(1, 2, 2, 3);
+            let mut saved = [AChar::from_ascii_char(b' '); 6];
+            c.read_region(rect, &mut saved).unwrap();
+            let text: String = saved.iter().map(|a| a.char()).collect();
+            assert_eq!(text, "MNOWXY");
+            c.fill_rect(1, 2, 2, 3, b'#');
+            c.write_region(rect, &saved).unwrap();
+
+            let short = CursorError::BufferTooSmall { needed: 6, got: 5 };
+            assert_eq!(c.read_region(rect, &mut saved[..5]), Err(short));
+            let outside = CursorError::OutOfBounds { row: ROWS, col: 4 };
+            let low = Rect::new(3, 2, 2, 3);
+            assert_eq!(c.write_region(low, &saved), Err(outside));
+        }
+        assert_eq!(all_text(&buf), all_text(&lettered()));
+    }
+
+    #[test]
+    fn markup_changes_colors_inline() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).fg(1).bg(2);
+            c.puts_markup(&[
+                b'a',
+                MARKUP_FG,
+                3,
+                b'b',
+                MARKUP_BG,
+                4,
+                b'c',
+                MARKUP_RESET,
+                b'd',
+                MARKUP_LITERAL,
+                MARKUP_FG,
+                MARKUP_FG,
+                5,
+                b'e',
+                // Missing its color, so ignored.
+                MARKUP_BG,
+            ]);
+            assert_eq!(c.position(), (0, 6));
+            assert_eq!((c.fg, c.bg), (5, 2));
+        }
+        let cells: Vec<_> = buf[..6]
+            .iter()
+            .map(|&a| (a.ascii_char(), colors(a)))
+            .collect();
+        assert_eq!(
+            cells,
+            [
+                (b'a', (1, 2)),
+                (b'b', (3, 2)),
+                (b'c', (3, 4)),
+                (b'd', (1, 2)),
+                (MARKUP_FG, (1, 2)),
+                (b'e', (5, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn invert_region_swaps_colors_and_back() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).fg(1).bg(2).at(0, 3);
+            c.fill_rect(0, 0, ROWS, COLS, b'x');
+            c.invert_region(1, 8, 2, 5);
+            assert_eq!(c.position(), (0, 3));
+        }
+        let inverted = |row: usize, col: usize| colors(buf[row * COLS + col]);
+        assert_eq!(inverted(1, 8), (2, 1));
+        assert_eq!(inverted(2, 9), (2, 1));
+        assert_eq!(inverted(1, 7), (1, 2));
+        assert_eq!(inverted(3, 8), (1, 2));
+        assert!(all_text(&buf).chars().all(|c| c == 'x'));
+
+        Cursor::new(&mut buf, COLS).invert_region(1, 8, 2, 5);
+        assert!(buf.iter().all(|&a| colors(a) == (1, 2)));
+    }
+
+    #[test]
+    fn hline_and_vline_clip() {
+        let mut buf = blank();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).bg(3).at(3, 0);
+            c.hline(0, 7, 5, b'-');
+            c.vline(1, 2, 9, b'|');
+            c.vline(0, COLS, 2, b'|');
+            assert_eq!(c.position(), (3, 0));
+        }
+        assert_eq!(row_text(&buf, 0), ".......---");
+        for row in 1..ROWS {
+            assert_eq!(row_text(&buf, row), "..|.......");
+        }
+        assert_eq!(buf[COLS + 2].background(), 3);
+    }
+
+    #[test]
+    fn scroll_region_moves_only_inside() {
+        let mut buf = lettered();
+        {
+            let mut c = Cursor::new(&mut buf, COLS).bg(7).at(3, 3);
+            c.scroll_region(Rect::new(0, 2, 3, 3), 1);
+            // Down two, clipped to the window.
+            c.scroll_region(Rect::new(1, 7, 9, 9), -2);
+            assert_eq!(c.position(), (3, 3));
+        }
+        assert_eq!(row_text(&buf, 0), "ABMNOFGHIJ");
+        assert_eq!(row_text(&buf, 1), "KLWXYPQ   ");
+        assert_eq!(row_text(&buf, 2), "UV   ZA   ");
+        assert_eq!(row_text(&buf, 3), "EFGHIJKRST");
+        assert_eq!(buf[2 * COLS + 2].background(), 7);
+
+        // Further than the rectangle is tall blanks all of it.
+        let mut buf = lettered();
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.scroll_region(Rect::new(0, 0, 1, COLS), isize::MIN);
+            c.scroll_region(Rect::new(3, 0, 1, COLS), isize::MAX);
+        }
+        assert_eq!(row_text(&buf, 0), "          ");
+        assert_eq!(row_text(&buf, 1), "KLMNOPQRST");
+        assert_eq!(row_text(&buf, 3), "          ");
+    }
+}
@@ -0,0 +1,110 @@
+//! Single-cell icons for status indicators.
+//!
+//! Each icon is a CP437 glyph, named for what it means rather than what it
+//! looks like, so a screen can say `ICON_OK` and leave the choice of glyph to
+//! this module. Several are in the control character range, which `puts`
+//! interprets rather than draws; use `draw_icon` or `IconLabel` to show them.
+
+use crate::cursor::{Cursor, Rect};
+use crate::widget::Widget;
+
+/// Something went right (`√`).
+pub const ICON_OK: u8 = 0xFB;
+/// Something went wrong. CP437 has no cross, so this is a plain `X`.
+pub const ICON_FAIL: u8 = b'X';
+/// Something needs attention (`▲`).
+pub const ICON_WARN: u8 = 0x1E;
+pub const ICON_UP: u8 = 0x18;
+pub const ICON_DOWN: u8 = 0x19;
+pub const ICON_RIGHT: u8 = 0x1A;
+pub const ICON_LEFT: u8 = 0x1B;
+/// Payment by card (`▬`).
+pub const ICON_CARD: u8 = 0x16;
+/// Payment by coin (`○`).
+pub const ICON_COIN: u8 = 0x09;
+/// Payment by tapping a card or phone (`≈`).
+pub const ICON_TAP: u8 = 0xF7;
+
+/// Draws `icon` at `row`, `col` in `fg`, keeping the background already
+/// there. Writes outside the window are ignored, and the cursor is not moved.
+pub fn draw_icon(
+    c: &mut Cursor,
+    row: usize,
+    col: usize,
+    icon: u8,
+    fg: m4vga::Pixel,
+) {
+    let bg = c.bg;
+    let mut c = c.styled(fg, bg);
+    let transparent = c.bg_transparent;
+    c.bg_transparent = true;
+    c.hline(row, col, 1, icon);
+    c.bg_transparent = transparent;
+}
+
+/// Icon followed by a line of text, e.g. `√ Ticket issued`, left-aligned in
+/// its area. Text that doesn't fit is truncated.
+#[derive(Copy, Clone, Debug)]
+pub struct IconLabel<'t> {
+    pub icon: u8,
+    pub text: &'t [u8],
+    pub icon_fg: m4vga::Pixel,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+}
+
+impl<'t> IconLabel<'t> {
+    /// Makes a label in white on black.
+    pub const fn new(icon: u8, text: &'t [u8]) -> Self {
+        IconLabel {
+            icon,
+            text,
+            icon_fg: crate::cursor::DEFAULT_FG,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+        }
+    }
+}
+
+impl<'t> Widget for IconLabel<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.set_scroll(false);
+        w.fill_rect(0, 0, 1, area.w, b' ');
+        draw_icon(&mut w, 0, 0, self.icon, self.icon_fg);
+        w.puts_truncated_at(0, 2, self.text, area.w.saturating_sub(2))
+            .ok();
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, self.text.len() + 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    #[test]
+    fn label_draws_control_glyphs_in_icon_color() {
+        const COLS: usize = 8;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let mut label = IconLabel::new(ICON_COIN, b"Coins");
+        label.icon_fg = 0b00_11_00;
+        label.draw(&mut Cursor::new(&mut buf, COLS), Rect::new(0, 1, 1, 6));
+        let text: String = buf
+            .iter()
+            .map(|a| match a.ascii_char() {
+                ICON_COIN => 'o',
+                other => other as char,
+            })
+            .collect();
+        assert_eq!(text, ".o Coi\u{af}.");
+        assert_eq!(buf[1].foreground(), 0b00_11_00);
+        assert_eq!(buf[3].foreground(), crate::cursor::DEFAULT_FG);
+        assert_eq!(buf[1].background(), crate::cursor::DEFAULT_BG);
+    }
+}
@@ -0,0 +1,763 @@
+//! Fields for typing text into.
+//!
+//! A field owns a small fixed buffer, so it never allocates and can sit in
+//! the main loop's state. Typing always happens at the end of the text, where
+//! a blinking block shows where the next character goes.
+//!
+//! A `NumberField` keeps its digits in an `InputField`, adding a range and
+//! buttons-friendly `inc` and `dec`. A `PinField` keeps a secret in one,
+//! drawn masked.
+//!
+//! Without a keypad fitted, a `Keypad` drawn on screen types into a field
+//! using only the arrow and select buttons.
+
+use crate::banner::FULL_BLOCK;
+use crate::cursor::{BoxStyle, Cursor, Rect};
+use crate::widget::{Focusable, MenuEvent, Widget};
+
+/// Most characters an `InputField` can hold.
+pub const INPUT_CAP: usize = 32;
+
+/// Number of frames the field's cursor spends shown, then hidden.
+pub const BLINK_FRAMES: u32 = 30;
+
+/// Number of frames a `PinField` shows the last digit typed before masking
+/// it: one second at 60 Hz.
+pub const REVEAL_FRAMES: u32 = 60;
+
+/// Errors reported when editing a field.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InputError {
+    /// The field already holds `max_len` characters.
+    Full,
+    /// The character isn't allowed in the field, such as a letter in a
+    /// `NumberField`.
+    Rejected,
+    /// The character would take a `NumberField` past its maximum.
+    OutOfRange,
+}
+
+/// One row of editable text.
+///
+/// Text too long for the field's width scrolls to the left so that the end,
+/// where typing happens, stays in view.
+///
+/// With a `mask`, every character is drawn as the mask instead, except that
+/// the last one typed with `insert_at` can be shown for `reveal_frames`
+/// frames first, so the user can check it.
+#[derive(Copy, Clone, Debug)]
+pub struct InputField {
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Most characters the field accepts, up to `INPUT_CAP`.
+    pub max_len: usize,
+    /// Character drawn in place of each one typed, if any.
+    pub mask: Option<u8>,
+    /// Frames for which a masked field shows the last character typed.
+    pub reveal_frames: u32,
+    buf: [u8; INPUT_CAP],
+    len: usize,
+    /// Frame on which the last character was typed, if it was typed with
+    /// `insert_at` and is still there.
+    typed_at: Option<u32>,
+}
+
+impl InputField {
+    /// Makes an empty field in white on black.
+    pub const fn new(rect: Rect) -> Self {
+        InputField {
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            max_len: INPUT_CAP,
+            mask: None,
+            reveal_frames: 0,
+            buf: [0; INPUT_CAP],
+            len: 0,
+            typed_at: None,
+        }
+    }
+
+    /// Adds `ch` to the end of the text, unless the field is full.
+    pub fn insert(&mut self, ch: u8) -> Result<(), InputError> {
+        if self.len >= usize::min(self.max_len, INPUT_CAP) {
+            return Err(InputError::Full);
+        }
+        self.buf[self.len] = ch;
+        self.len += 1;
+        self.typed_at = None;
+        Ok(())
+    }
+
+    /// Adds `ch` like `insert`, noting that it was typed on frame `frame_no`
+    /// so that a masked field can show it for a moment.
+    pub fn insert_at(
+        &mut self,
+        ch: u8,
+        frame_no: u32,
+    ) -> Result<(), InputError> {
+        self.insert(ch)?;
+        self.typed_at = Some(frame_no);
+        Ok(())
+    }
+
+    /// Removes the last character, returning whether there was one.
+    pub fn backspace(&mut self) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        self.len -= 1;
+        self.typed_at = None;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.typed_at = None;
+    }
+
+    /// Returns the text typed so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Draws the field at its `rect`, with the cursor shown or hidden as
+    /// suits frame `frame_no` of the main loop. Only the top row of `rect` is
+    /// used.
+    pub fn draw(&self, c: &mut Cursor, frame_no: u32) {
+        let width = self.rect.w;
+        let mut w = c.window(self.rect.row, self.rect.col, 1, width);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        if width == 0 {
+            return;
+        }
+        // Leave room for the cursor after the text.
+        let start = (self.len + 1).saturating_sub(width);
+        let revealed = match self.typed_at {
+            Some(at) => frame_no.wrapping_sub(at) < self.reveal_frames,
+            None => false,
+        };
+        for (i, &ch) in self.as_bytes()[start..].iter().enumerate() {
+            let ch = match self.mask {
+                Some(_) if revealed && start + i + 1 == self.len => ch,
+                Some(mask) => mask,
+                None => ch,
+            };
+            w.hline(0, i, 1, ch);
+        }
+        if (frame_no / BLINK_FRAMES) & 1 == 0 {
+            w.hline(0, self.len - start, 1, FULL_BLOCK);
+        }
+    }
+}
+
+/// What a `NumberField` does when `inc` or `dec` would pass one of its
+/// bounds.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Bounds {
+    /// Stays at the bound.
+    Clamp,
+    /// Goes around to the other bound.
+    Wrap,
+}
+
+/// Field holding a number in a range, typed in digit by digit or stepped
+/// with `inc` and `dec`.
+///
+/// While the user is typing the number may be below the minimum, since
+/// `12` starts out as `1`; `value` reports it clamped into range.
+#[derive(Copy, Clone, Debug)]
+pub struct NumberField {
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Digits as typed. Only its contents are used, not its looks.
+    field: InputField,
+    min: u32,
+    max: u32,
+    bounds: Bounds,
+    /// Digits to show at least, padding with leading zeros.
+    pub zero_pad: usize,
+    /// Whether the field has the focus, and is drawn with its colors
+    /// swapped.
+    pub focused: bool,
+}
+
+impl NumberField {
+    /// Makes a field holding `min`, in white on black. `max` is raised to
+    /// `min` if it is smaller.
+    pub fn new(rect: Rect, min: u32, max: u32, bounds: Bounds) -> Self {
+        let mut field = NumberField {
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            field: InputField::new(rect),
+            min,
+            max: u32::max(min, max),
+            bounds,
+            zero_pad: 0,
+            focused: false,
+        };
+        field.set(min);
+        field
+    }
+
+    /// Adds a digit to the end of the number, unless `ch` isn't a digit or
+    /// the number would go past the maximum.
+    pub fn insert(&mut self, ch: u8) -> Result<(), InputError> {
+        if !ch.is_ascii_digit() {
+            return Err(InputError::Rejected);
+        }
+        let typed = u64::from(self.typed().unwrap_or(0));
+        if typed * 10 + u64::from(ch - b'0') > u64::from(self.max) {
+            return Err(InputError::OutOfRange);
+        }
+        self.field.insert(ch)
+    }
+
+    /// Removes the last digit, returning whether there was one.
+    pub fn backspace(&mut self) -> bool {
+        self.field.backspace()
+    }
+
+    /// Replaces the number with `value`, clamped into range.
+    pub fn set(&mut self, value: u32) {
+        let value = u32::min(u32::max(value, self.min), self.max);
+        let mut digits = [0; 10];
+        self.field.clear();
+        for &d in format_digits(value, &mut digits) {
+            // Ten digits always fit.
+            self.field.insert(d).ok();
+        }
+    }
+
+    /// Returns the number, clamped into range; an empty field counts as the
+    /// minimum.
+    pub fn value(&self) -> u32 {
+        let typed = self.typed().unwrap_or(self.min);
+        u32::min(u32::max(typed, self.min), self.max)
+    }
+
+    /// Steps the number up by one, clamping or wrapping at the maximum.
+    pub fn inc(&mut self) {
+        let value = self.value();
+        if value < self.max {
+            self.set(value + 1);
+        } else if self.bounds == Bounds::Wrap {
+            self.set(self.min);
+        } else {
+            self.set(self.max);
+        }
+    }
+
+    /// Steps the number down by one, clamping or wrapping at the minimum.
+    pub fn dec(&mut self) {
+        let value = self.value();
+        if value > self.min {
+            self.set(value - 1);
+        } else if self.bounds == Bounds::Wrap {
+            self.set(self.max);
+        } else {
+            self.set(self.min);
+        }
+    }
+
+    /// Draws the field at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+
+    /// Returns the number as typed, without clamping, if anything has been.
+    fn typed(&self) -> Option<u32> {
+        if self.field.is_empty() {
+            return None;
+        }
+        // `insert` keeps the number in range, so this can't overflow.
+        let digits = self.field.as_bytes().iter();
+        Some(digits.fold(0, |n, &d| n * 10 + u32::from(d - b'0')))
+    }
+}
+
+impl Widget for NumberField {
+    /// Draws the number as typed, right-aligned in the top row of `area`
+    /// and padded with zeros to `zero_pad` digits. An empty field is drawn
+    /// blank, unless padded.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, 1, area.w);
+        if self.focused {
+            w.fg = self.bg;
+            w.bg = self.fg;
+        } else {
+            w.fg = self.fg;
+            w.bg = self.bg;
+        }
+        w.clear();
+        if area.w == 0 {
+            return;
+        }
+        let mut digits = [0; 10];
+        let text = match self.typed() {
+            Some(typed) => format_digits(typed, &mut digits),
+            None => &[],
+        };
+        let pad = self.zero_pad.saturating_sub(text.len());
+        let right = area.w - 1;
+        w.puts_right(0, right, text);
+        let pad_right = right.saturating_sub(text.len());
+        for i in 0..usize::min(pad, pad_right + 1) {
+            w.hline(0, pad_right - i, 1, b'0');
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        let mut digits = [0; 10];
+        let width = format_digits(self.max, &mut digits).len();
+        (1, usize::max(width, self.zero_pad))
+    }
+}
+
+impl Focusable for NumberField {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Steps the number up for `Up` and down for `Down`.
+    fn on_event(&mut self, event: MenuEvent) -> bool {
+        let before = self.value();
+        match event {
+            MenuEvent::Up => self.inc(),
+            MenuEvent::Down => self.dec(),
+            MenuEvent::Select | MenuEvent::Left | MenuEvent::Right => {
+                return false
+            }
+        }
+        self.value() != before
+    }
+}
+
+/// Field for typing a PIN, drawn as `*`s, that can check what was typed.
+///
+/// The field counts failed checks, so that the caller can lock the user out
+/// after a few; resetting the count is up to the caller too.
+#[derive(Copy, Clone, Debug)]
+pub struct PinField {
+    /// Digits as typed. Its looks, including the mask, can be changed.
+    pub field: InputField,
+    failures: u32,
+}
+
+impl PinField {
+    /// Makes an empty field for up to `max_len` digits, masked with `*` and
+    /// showing each digit for `REVEAL_FRAMES` frames.
+    pub fn new(rect: Rect, max_len: usize) -> Self {
+        let mut field = InputField::new(rect);
+        field.max_len = max_len;
+        field.mask = Some(b'*');
+        field.reveal_frames = REVEAL_FRAMES;
+        PinField { field, failures: 0 }
+    }
+
+    /// Adds a digit typed on frame `frame_no`, unless `ch` isn't a digit or
+    /// the field is full.
+    pub fn insert(&mut self, ch: u8, frame_no: u32) -> Result<(), InputError> {
+        if !ch.is_ascii_digit() {
+            return Err(InputError::Rejected);
+        }
+        self.field.insert_at(ch, frame_no)
+    }
+
+    /// Removes the last digit, returning whether there was one.
+    pub fn backspace(&mut self) -> bool {
+        self.field.backspace()
+    }
+
+    pub fn clear(&mut self) {
+        self.field.clear();
+    }
+
+    /// Checks whether the digits typed are `pin`, counting a failure if
+    /// not.
+    ///
+    /// The comparison looks at every byte of `pin` whatever it finds, so
+    /// how long it takes doesn't tell how many digits were right.
+    pub fn verify(&mut self, pin: &[u8]) -> bool {
+        let typed = self.field.as_bytes();
+        let mut diff = (typed.len() != pin.len()) as u8;
+        for (i, &expected) in pin.iter().enumerate() {
+            diff |= typed.get(i).cloned().unwrap_or(0) ^ expected;
+        }
+        let ok = diff == 0;
+        if !ok {
+            self.failures = self.failures.saturating_add(1);
+        }
+        ok
+    }
+
+    /// Returns the number of failed `verify` calls since the field was made
+    /// or `reset_attempts` was called.
+    pub fn failed_attempts(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn reset_attempts(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Draws the field like `InputField::draw`.
+    pub fn draw(&self, c: &mut Cursor, frame_no: u32) {
+        self.field.draw(c, frame_no)
+    }
+}
+
+/// Keys on a `Keypad`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Key {
+    /// An ASCII digit.
+    Digit(u8),
+    Clear,
+    Enter,
+}
+
+/// Layout of a `Keypad`, like a phone's.
+const KEYS: [[Key; KEYPAD_COLS]; KEYPAD_ROWS] = [
+    [Key::Digit(b'1'), Key::Digit(b'2'), Key::Digit(b'3')],
+    [Key::Digit(b'4'), Key::Digit(b'5'), Key::Digit(b'6')],
+    [Key::Digit(b'7'), Key::Digit(b'8'), Key::Digit(b'9')],
+    [Key::Clear, Key::Digit(b'0'), Key::Enter],
+];
+
+/// Number of rows and columns of keys on a `Keypad`.
+pub const KEYPAD_ROWS: usize = 4;
+pub const KEYPAD_COLS: usize = 3;
+
+/// Cells each key of a `Keypad` takes, so that the whole pad is 12 rows by
+/// 18 columns.
+pub const KEY_H: usize = 3;
+pub const KEY_W: usize = 6;
+
+/// On-screen keypad of framed digit keys, with clear and enter keys at the
+/// bottom, for typing numbers with only the arrow and select buttons.
+///
+/// The current key is drawn with its colors swapped. Clear and enter have
+/// backgrounds of their own, `clear_bg` and `enter_bg`, to set them apart
+/// from the digits.
+#[derive(Copy, Clone, Debug)]
+pub struct Keypad {
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub clear_bg: m4vga::Pixel,
+    pub enter_bg: m4vga::Pixel,
+    pub style: BoxStyle,
+    /// Row and column of the current key.
+    row: usize,
+    col: usize,
+}
+
+impl Keypad {
+    /// Makes a keypad with `5` as the current key, in white on black, with
+    /// a red clear key and a green enter key.
+    pub const fn new(rect: Rect) -> Self {
+        Keypad {
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            clear_bg: 0b00_00_10,
+            enter_bg: 0b00_10_00,
+            style: BoxStyle::SINGLE,
+            row: 1,
+            col: 1,
+        }
+    }
+
+    /// Returns the current key.
+    pub fn current(&self) -> Key {
+        KEYS[self.row][self.col]
+    }
+
+    /// Moves the current key for the arrows, wrapping around at the edges.
+    /// For `Select`, presses the current key: a digit is typed into `field`,
+    /// if it has room, and clear empties it. Returns the key pressed, so
+    /// that the caller can act on enter.
+    pub fn handle(
+        &mut self,
+        event: MenuEvent,
+        field: &mut InputField,
+    ) -> Option<Key> {
+        match event {
+            MenuEvent::Up => {
+                self.row = (self.row + KEYPAD_ROWS - 1) % KEYPAD_ROWS
+            }
+            MenuEvent::Down => self.row = (self.row + 1) % KEYPAD_ROWS,
+            MenuEvent::Left => {
+                self.col = (self.col + KEYPAD_COLS - 1) % KEYPAD_COLS
+            }
+            MenuEvent::Right => self.col = (self.col + 1) % KEYPAD_COLS,
+            MenuEvent::Select => {
+                let key = self.current();
+                match key {
+                    Key::Digit(d) => {
+                        // A full field just doesn't take any more.
+                        field.insert(d).ok();
+                    }
+                    Key::Clear => field.clear(),
+                    Key::Enter => (),
+                }
+                return Some(key);
+            }
+        }
+        None
+    }
+
+    /// Draws the keypad at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl Widget for Keypad {
+    /// Draws the keys in an even grid filling `area`, as far as it divides.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let key_h = area.h / KEYPAD_ROWS;
+        let key_w = area.w / KEYPAD_COLS;
+        for (row, keys) in KEYS.iter().enumerate() {
+            for (col, &key) in keys.iter().enumerate() {
+                let bg = match key {
+                    Key::Digit(_) => self.bg,
+                    Key::Clear => self.clear_bg,
+                    Key::Enter => self.enter_bg,
+                };
+                let mut w = c.window(
+                    area.row + row * key_h,
+                    area.col + col * key_w,
+                    key_h,
+                    key_w,
+                );
+                if (row, col) == (self.row, self.col) {
+                    w.fg = bg;
+                    w.bg = self.fg;
+                } else {
+                    w.fg = self.fg;
+                    w.bg = bg;
+                }
+                w.clear();
+                w.draw_box(0, 0, key_h, key_w, &self.style);
+                let digit;
+                let label: &[u8] = match key {
+                    Key::Digit(d) => {
+                        digit = [d];
+                        &digit
+                    }
+                    Key::Clear => b"CLR",
+                    Key::Enter => b"ENT",
+                };
+                let col = key_w.saturating_sub(label.len()) / 2;
+                w.puts_at(key_h / 2, col, label).ok();
+            }
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (KEYPAD_ROWS * KEY_H, KEYPAD_COLS * KEY_W)
+    }
+}
+
+/// Writes `value` in decimal into `buf`, returning the part used.
+fn format_digits(mut value: u32, buf: &mut [u8; 10]) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    &buf[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 6;
+
+    fn shown(field: &InputField, frame_no: u32) -> String {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        field.draw(&mut Cursor::new(&mut buf, COLS), frame_no);
+        buf.iter()
+            .map(|a| match a.ascii_char() {
+                FULL_BLOCK => '#',
+                other => other as char,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn edits_and_rejects_overflow() {
+        let mut field = InputField::new(Rect::new(0, 0, 1, 4));
+        field.max_len = 3;
+        assert!(!field.backspace());
+        for &ch in b"abc" {
+            field.insert(ch).unwrap();
+        }
+        assert_eq!(field.insert(b'd'), Err(InputError::Full));
+        assert_eq!(field.as_bytes(), b"abc");
+        assert!(field.backspace());
+        assert_eq!(field.as_bytes(), b"ab");
+        field.clear();
+        assert!(field.is_empty());
+
+        let mut field = InputField::new(Rect::new(0, 0, 1, 4));
+        for _ in 0..INPUT_CAP {
+            field.insert(b'x').unwrap();
+        }
+        assert_eq!(field.insert(b'x'), Err(InputError::Full));
+    }
+
+    #[test]
+    fn cursor_blinks_and_stays_in_view() {
+        let mut field = InputField::new(Rect::new(0, 1, 1, 4));
+        assert_eq!(shown(&field, 0), ".#   .");
+        assert_eq!(shown(&field, BLINK_FRAMES), ".    .");
+        for &ch in b"abc" {
+            field.insert(ch).unwrap();
+        }
+        assert_eq!(shown(&field, 0), ".abc#.");
+        field.insert(b'd').unwrap();
+        field.insert(b'e').unwrap();
+        assert_eq!(shown(&field, 0), ".cde#.");
+        assert_eq!(shown(&field, BLINK_FRAMES + 1), ".cde .");
+    }
+
+    fn number(field: &NumberField) -> String {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        field.redraw(&mut Cursor::new(&mut buf, COLS));
+        buf.iter().map(|a| a.char()).collect()
+    }
+
+    #[test]
+    fn number_field_types_and_steps() {
+        let mut qty =
+            NumberField::new(Rect::new(0, 1, 1, 4), 1, 12, Bounds::Clamp);
+        assert_eq!(qty.value(), 1);
+        assert_eq!(number(&qty), ".   1.");
+        assert_eq!(qty.insert(b'x'), Err(InputError::Rejected));
+        assert_eq!(qty.insert(b'3'), Err(InputError::OutOfRange));
+        qty.backspace();
+        assert_eq!(qty.value(), 1);
+        assert_eq!(number(&qty), ".    .");
+        qty.insert(b'0').unwrap();
+        assert_eq!(qty.value(), 1);
+        qty.insert(b'9').unwrap();
+        assert_eq!(qty.value(), 9);
+        qty.zero_pad = 3;
+        assert_eq!(number(&qty), ". 009.");
+
+        qty.set(12);
+        qty.inc();
+        assert_eq!(qty.value(), 12);
+        qty.set(0);
+        qty.dec();
+        assert_eq!(qty.value(), 1);
+
+        let mut minute =
+            NumberField::new(Rect::new(0, 0, 1, 2), 0, 59, Bounds::Wrap);
+        minute.zero_pad = 2;
+        minute.dec();
+        assert_eq!(minute.value(), 59);
+        minute.inc();
+        assert_eq!(minute.value(), 0);
+        assert_eq!(number(&minute), "00....");
+        minute.set(123);
+        assert_eq!(minute.value(), 59);
+    }
+
+    #[test]
+    fn pin_is_masked_after_a_moment() {
+        let mut pin = PinField::new(Rect::new(0, 0, 1, 5), 4);
+        assert_eq!(pin.insert(b'a', 0), Err(InputError::Rejected));
+        pin.insert(b'1', 0).unwrap();
+        pin.insert(b'2', 10).unwrap();
+        assert_eq!(shown(&pin.field, 10), "*2#  .");
+        assert_eq!(shown(&pin.field, 10 + REVEAL_FRAMES), "**#  .");
+        pin.backspace();
+        assert_eq!(shown(&pin.field, 10), "*#   .");
+        for &d in b"234" {
+            pin.insert(d, 20).unwrap();
+        }
+        assert_eq!(pin.insert(b'5', 20), Err(InputError::Full));
+    }
+
+    #[test]
+    fn pin_verify_counts_failures() {
+        let mut pin = PinField::new(Rect::new(0, 0, 1, 5), 4);
+        for &d in b"1234" {
+            pin.insert(d, 0).unwrap();
+        }
+        assert!(!pin.verify(b"1235"));
+        assert!(!pin.verify(b"123"));
+        assert!(!pin.verify(b"12345"));
+        assert!(!pin.verify(b""));
+        assert_eq!(pin.failed_attempts(), 4);
+        assert!(pin.verify(b"1234"));
+        assert_eq!(pin.failed_attempts(), 4);
+        pin.reset_attempts();
+        assert_eq!(pin.failed_attempts(), 0);
+        pin.clear();
+        assert!(pin.verify(b""));
+    }
+
+    #[test]
+    fn keypad_wraps_and_types_into_its_field() {
+        let mut field = InputField::new(Rect::new(0, 0, 1, 4));
+        field.max_len = 2;
+        let mut pad = Keypad::new(Rect::new(0, 0, 12, 9));
+        let mut press = |events: &[MenuEvent], field: &mut InputField| {
+            let mut last = None;
+            for &event in events {
+                last = pad.handle(event, field);
+            }
+            last
+        };
+        use MenuEvent::*;
+        let f = &mut field;
+        assert_eq!(press(&[Up, Up, Select], f), Some(Key::Digit(b'0')));
+        assert_eq!(press(&[Left, Left, Select], f), Some(Key::Enter));
+        assert_eq!(press(&[Right, Up, Select], f), Some(Key::Digit(b'7')));
+        assert_eq!(press(&[Select], f), Some(Key::Digit(b'7')));
+        assert_eq!(f.as_bytes(), b"07");
+        assert_eq!(press(&[Down, Select], f), Some(Key::Clear));
+        assert!(field.is_empty());
+
+        const W: usize = 9;
+        let mut buf = [AChar::from_ascii_char(b'.'); W * 12];
+        pad.style = BoxStyle::ASCII;
+        pad.redraw(&mut Cursor::new(&mut buf, W));
+        let rows: Vec<String> = buf
+            .chunks(W)
+            .map(|row| row.iter().map(|a| a.char()).collect())
+            .collect();
+        assert_eq!(&rows[..3], ["+-++-++-+", "|1||2||3|", "+-++-++-+"]);
+        assert_eq!(&rows[10..], ["CLR|0|ENT", "+-++-++-+"]);
+        assert_eq!(buf[10 * W].background(), pad.fg);
+        assert_eq!(buf[10 * W + 6].background(), pad.enter_bg);
+        assert_eq!(buf[4].background(), pad.bg);
+    }
+}
@@ -0,0 +1,223 @@
+//! Placing widgets in rows and columns by their sizes.
+//!
+//! A `Stack` works out where its children go in a parent `Rect` from how big
+//! each one needs to be, so that a screen built with one still lines up when
+//! a label changes length, instead of every position being counted by hand.
+
+use crate::cursor::Rect;
+use crate::widget::Widget;
+
+/// Most children a `Stack` can hold.
+pub const STACK_CAP: usize = 8;
+
+/// Direction a `Stack` places its children in.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Axis {
+    /// Top to bottom.
+    Column,
+    /// Left to right.
+    Row,
+}
+
+/// Where things go in the space they have.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    /// Across a stack, stretched to the full size; along one, spaced out
+    /// evenly, with as much space before the first and after the last as
+    /// between each.
+    Fill,
+}
+
+/// Children laid out one after another in a column or row, each given its
+/// size and no more.
+///
+/// Add the children's sizes in order with `push`, or `push_widget`, then
+/// ask `layout` for where they go. Children that don't fit are cut short at
+/// the edge of the parent, and those past it get empty rectangles.
+#[derive(Copy, Clone, Debug)]
+pub struct Stack {
+    pub axis: Axis,
+    /// Cells between one child and the next.
+    pub spacing: usize,
+    /// Where each child goes across the stack.
+    pub align: Align,
+    /// Where the children, as a group, go along the stack.
+    pub justify: Align,
+    /// (rows, columns) of each child.
+    sizes: [(usize, usize); STACK_CAP],
+    len: usize,
+}
+
+impl Stack {
+    /// Makes an empty column, its children left-aligned and packed at the
+    /// top, `spacing` rows apart.
+    pub const fn column(spacing: usize) -> Self {
+        Stack::new(Axis::Column, spacing)
+    }
+
+    /// Makes an empty row, its children top-aligned and packed at the left,
+    /// `spacing` columns apart.
+    pub const fn row(spacing: usize) -> Self {
+        Stack::new(Axis::Row, spacing)
+    }
+
+    const fn new(axis: Axis, spacing: usize) -> Self {
+        Stack {
+            axis,
+            spacing,
+            align: Align::Start,
+            justify: Align::Start,
+            sizes: [(0, 0); STACK_CAP],
+            len: 0,
+        }
+    }
+
+    /// Adds a child of `size`, in (rows, columns), after the others. Returns
+    /// false, and leaves it out, if there are already `STACK_CAP`.
+    pub fn push(&mut self, size: (usize, usize)) -> bool {
+        if self.len == STACK_CAP {
+            return false;
+        }
+        self.sizes[self.len] = size;
+        self.len += 1;
+        true
+    }
+
+    /// Adds a child the size of `widget`'s `min_size`, like `push`.
+    pub fn push_widget(&mut self, widget: &dyn Widget) -> bool {
+        self.push(widget.min_size())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the (rows, columns) the children need, spacing included.
+    pub fn min_size(&self) -> (usize, usize) {
+        let sizes = self.sizes[..self.len].iter().map(|&s| self.split(s));
+        let (along, across) = sizes.fold((0, 0), |(along, across), (m, c)| {
+            (along + m, usize::max(across, c))
+        });
+        let gaps = self.spacing * self.len.saturating_sub(1);
+        self.join(along + gaps, across)
+    }
+
+    /// Returns where each child goes in `parent`, in the order they were
+    /// added. Entries past `len` are empty.
+    pub fn layout(&self, parent: Rect) -> [Rect; STACK_CAP] {
+        let mut rects = [Rect::new(parent.row, parent.col, 0, 0); STACK_CAP];
+        let (room, across_room) = self.split((parent.h, parent.w));
+        let needed = self.split(self.min_size()).0;
+        let extra = room.saturating_sub(needed);
+        // Extra space in each of the `len + 1` slots before, between and
+        // after the children.
+        let slots = self.len + 1;
+        let pad = |slot: usize| match self.justify {
+            Align::Start => 0,
+            Align::Center if slot == 0 => extra / 2,
+            Align::End if slot == 0 => extra,
+            Align::Center | Align::End => 0,
+            Align::Fill => extra / slots + (slot < extra % slots) as usize,
+        };
+
+        let mut at = pad(0);
+        for (i, &size) in self.sizes[..self.len].iter().enumerate() {
+            let (along, across) = self.split(size);
+            let start = usize::min(at, room);
+            let along = usize::min(along, room - start);
+            let across = match self.align {
+                Align::Fill => across_room,
+                _ => usize::min(across, across_room),
+            };
+            let offset = match self.align {
+                Align::Start | Align::Fill => 0,
+                Align::Center => (across_room - across) / 2,
+                Align::End => across_room - across,
+            };
+            let (row, col) = self.join(start, offset);
+            let (h, w) = self.join(along, across);
+            rects[i] = Rect::new(parent.row + row, parent.col + col, h, w);
+            at = start + along + self.spacing + pad(i + 1);
+        }
+        rects
+    }
+
+    /// Turns (rows, columns) into (along, across) the stack.
+    fn split(&self, (rows, cols): (usize, usize)) -> (usize, usize) {
+        match self.axis {
+            Axis::Column => (rows, cols),
+            Axis::Row => (cols, rows),
+        }
+    }
+
+    /// Turns (along, across) the stack into (rows, columns).
+    fn join(&self, along: usize, across: usize) -> (usize, usize) {
+        self.split((along, across))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rects(stack: &Stack, parent: Rect) -> Vec<(usize, usize, usize, usize)> {
+        stack.layout(parent)[..stack.len()]
+            .iter()
+            .map(|r| (r.row, r.col, r.h, r.w))
+            .collect()
+    }
+
+    #[test]
+    fn column_centers_both_ways() {
+        let mut column = Stack::column(1);
+        column.align = Align::Center;
+        column.justify = Align::Center;
+        assert!(column.push((2, 6)));
+        assert!(column.push((1, 3)));
+        assert_eq!(column.min_size(), (4, 6));
+        assert_eq!(
+            rects(&column, Rect::new(10, 20, 9, 11)),
+            [(12, 22, 2, 6), (15, 24, 1, 3)]
+        );
+    }
+
+    #[test]
+    fn row_spreads_and_fills() {
+        let mut row = Stack::row(0);
+        row.align = Align::Fill;
+        row.justify = Align::Fill;
+        row.push((1, 3));
+        row.push((1, 2));
+        // Five spare columns in three slots: 2, 2, 1.
+        assert_eq!(
+            rects(&row, Rect::new(0, 0, 3, 10)),
+            [(0, 2, 3, 3), (0, 7, 3, 2)]
+        );
+        row.justify = Align::End;
+        row.align = Align::End;
+        assert_eq!(
+            rects(&row, Rect::new(0, 0, 3, 10)),
+            [(2, 5, 1, 3), (2, 8, 1, 2)]
+        );
+    }
+
+    #[test]
+    fn overflow_is_cut_at_the_edge() {
+        let mut column = Stack::column(1);
+        for _ in 0..STACK_CAP {
+            assert!(column.push((2, 9)));
+        }
+        assert!(!column.push((1, 1)));
+        let laid = rects(&column, Rect::new(0, 0, 4, 5));
+        assert_eq!(laid[0], (0, 0, 2, 5));
+        assert_eq!(laid[1], (3, 0, 1, 5));
+        assert_eq!(laid[2], (4, 0, 0, 5));
+    }
+}
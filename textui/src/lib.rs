@@ -0,0 +1,37 @@
+//! Text user interface helpers for the attributed text modes.
+//!
+//! This crate is deliberately architecture-independent to allow for testing on
+//! the host. Nothing here knows the size of the screen; dimensions are taken
+//! from the buffer handed to `Cursor::new`.
+
+#![cfg_attr(not(test), no_std)]
+
+#[macro_use]
+pub mod cursor;
+pub mod anim;
+pub mod ansi;
+pub mod banner;
+pub mod board;
+pub mod cart;
+pub mod chart;
+pub mod chrome;
+pub mod clock;
+pub mod code;
+pub mod color;
+pub mod confetti;
+pub mod cp437;
+pub mod icons;
+pub mod input;
+pub mod layout;
+pub mod list;
+pub mod map;
+pub mod overlay;
+pub mod price;
+pub mod reference;
+pub mod table;
+pub mod viewer;
+pub mod widget;
+
+pub use ansi::AnsiWriter;
+pub use cursor::{BoxStyle, Cursor, CursorError, Rect, TruncatingCursor};
+pub use widget::Widget;
@@ -0,0 +1,186 @@
+//! Bulleted and numbered lists with hanging indents.
+//!
+//! Each item is laid out as prose by `Cursor::put_paragraph`, in a column to
+//! the right of its marker, so that continuation lines line up under the
+//! item's text rather than under the marker.
+
+use crate::cursor::{paragraph_rows, Cursor, Rect};
+
+/// CP437 glyph used as the bullet (`•`).
+pub const BULLET: u8 = 0x07;
+
+/// Draws `items` as a list filling `rect`, in the current colors, each marked
+/// with a bullet or, if `numbered` is set, its number counting from 1.
+///
+/// Returns how many items were drawn. Items are only drawn whole, so the
+/// first one that doesn't fit in the rows left ends the list there, and
+/// `&items[drawn..]` can go on the next page. The exception is an item too
+/// tall for `rect` on its own, which is cut short rather than holding up the
+/// pager forever. Unused cells in `rect` are blanked. The cursor is not moved.
+pub fn draw_list(
+    c: &mut Cursor,
+    rect: Rect,
+    items: &[&[u8]],
+    numbered: bool,
+) -> usize {
+    let first = if numbered { Some(1) } else { None };
+    layout_list(c, rect, items, first)
+}
+
+/// Draws a numbered list like `draw_list`, but counting from `first`, so that
+/// later pages of a list carry on its numbering.
+pub fn draw_numbered_list(
+    c: &mut Cursor,
+    rect: Rect,
+    items: &[&[u8]],
+    first: usize,
+) -> usize {
+    layout_list(c, rect, items, Some(first))
+}
+
+/// Common implementation of the list functions. `first` is the number of the
+/// first item, or `None` for bullets.
+fn layout_list(
+    c: &mut Cursor,
+    rect: Rect,
+    items: &[&[u8]],
+    first: Option<usize>,
+) -> usize {
+    // Markers are right-aligned so the item text lines up: " 9. ", "10. ".
+    let indent = match first {
+        Some(first) => digits(first + items.len().saturating_sub(1)) + 2,
+        None => 2,
+    };
+    let (height, width) = c.size();
+    let h = usize::min(rect.h, height.saturating_sub(rect.row));
+    let w = usize::min(rect.w, width.saturating_sub(rect.col));
+    let text_w = w.saturating_sub(indent);
+
+    let mut row = 0;
+    let mut drawn = 0;
+    for (i, &item) in items.iter().enumerate() {
+        let rows = usize::max(paragraph_rows(item, text_w), 1);
+        let left = h - row;
+        if left == 0 || (rows > left && drawn > 0) {
+            break;
+        }
+        let rows = usize::min(rows, left);
+
+        c.fill_rect(rect.row + row, rect.col, rows, indent, b' ');
+        let mark_end = rect.col + indent - 2;
+        match first {
+            Some(first) => {
+                c.write_right(
+                    rect.row + row,
+                    mark_end - 1,
+                    format_args!("{}", first + i),
+                )
+                .ok();
+                c.hline(rect.row + row, mark_end, 1, b'.');
+            }
+            None => c.hline(rect.row + row, mark_end, 1, BULLET),
+        }
+        let text = Rect::new(rect.row + row, rect.col + indent, rows, text_w);
+        c.put_paragraph(text, item);
+
+        row += rows;
+        drawn += 1;
+    }
+    c.fill_rect(rect.row + row, rect.col, h - row, w, b' ');
+    drawn
+}
+
+/// Returns the number of decimal digits in `value`.
+pub(crate) fn digits(mut value: usize) -> usize {
+    let mut n = 1;
+    while value >= 10 {
+        value /= 10;
+        n += 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 14;
+    const ROWS: usize = 5;
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        BULLET => '*',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bullets_hang_and_stop_at_whole_items() {
+        let items: [&[u8]; 3] = [b"one", b"two words wrap here", b"three"];
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let drawn = draw_list(
+            &mut Cursor::new(&mut buf, COLS),
+            Rect::new(1, 1, 3, 12),
+            &items,
+            false,
+        );
+        assert_eq!(drawn, 2);
+        assert_eq!(
+            text(&buf),
+            [
+                "..............",
+                ".* one       .",
+                ".* two words .",
+                ".  wrap here .",
+                "..............",
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_line_up_and_continue() {
+        let items: [&[u8]; 3] = [b"a", b"b c", b"d"];
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let drawn = draw_numbered_list(
+            &mut Cursor::new(&mut buf, COLS),
+            Rect::new(0, 0, ROWS, 6),
+            &items,
+            9,
+        );
+        assert_eq!(drawn, 3);
+        assert_eq!(
+            text(&buf),
+            [
+                " 9. a ........",
+                "10. b ........",
+                "    c ........",
+                "11. d ........",
+                "      ........",
+            ]
+        );
+    }
+
+    #[test]
+    fn oversized_first_item_is_cut() {
+        let items: [&[u8]; 2] = [b"aa bb cc dd", b"ee"];
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let drawn = draw_list(
+            &mut Cursor::new(&mut buf, COLS),
+            Rect::new(0, 0, 2, 5),
+            &items,
+            true,
+        );
+        assert_eq!(drawn, 1);
+        assert_eq!(
+            &text(&buf)[..3],
+            ["1. aa.........", "   bb.........", ".............."]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Schematic map of a small transit network, for choosing a line.
+//!
+//! Like the diagram on a station wall, the map shows the order of the
+//! stations and nothing of where they are: each line is a straight track
+//! across the map with its stations evenly spaced along it, and stations
+//! where passengers can change lines are marked as interchanges. The network
+//! is described in static data, which the map borrows.
+
+use crate::cursor::{Cursor, Rect};
+use crate::widget::Widget;
+
+/// CP437 glyphs for the track (`─`), a station whose name is above it (`┴`)
+/// or below it (`┬`), and an interchange (`■`).
+pub const TRACK: u8 = 0xC4;
+pub const STATION_UP: u8 = 0xC1;
+pub const STATION_DOWN: u8 = 0xC2;
+pub const INTERCHANGE: u8 = 0xFE;
+
+/// Rows each line takes: station names above the track, the track, and
+/// station names below it.
+pub const MAP_LINE_H: usize = 3;
+
+/// One line of the network.
+#[derive(Copy, Clone, Debug)]
+pub struct MapLine<'t> {
+    pub name: &'t [u8],
+    /// Color the line is drawn in when it is selected.
+    pub color: m4vga::Pixel,
+    /// Names of the stations, in order along the line.
+    pub stations: &'t [&'t [u8]],
+    /// Indices into `stations` of those where passengers can change lines.
+    pub interchanges: &'t [usize],
+}
+
+impl<'t> MapLine<'t> {
+    pub fn is_interchange(&self, station: usize) -> bool {
+        self.interchanges.contains(&station)
+    }
+}
+
+/// The lines of a network, one under the other, spaced evenly down the map.
+///
+/// Each line's name is on the left, level with its track. Station names
+/// take turns above and below the track, so each has the room of two
+/// stations, and are cut short if even that isn't enough. The selected line
+/// is drawn in its own color, with its name in reverse; the others are
+/// dimmed.
+#[derive(Copy, Clone, Debug)]
+pub struct LineMap<'t> {
+    pub lines: &'t [MapLine<'t>],
+    pub rect: Rect,
+    /// Colors of the station names, and of the lines not selected.
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub dim_fg: m4vga::Pixel,
+    /// Index into `lines` of the line highlighted.
+    pub selected: usize,
+}
+
+impl<'t> LineMap<'t> {
+    /// Makes a map in white on black, with unselected lines in gray and the
+    /// first line selected.
+    pub const fn new(lines: &'t [MapLine<'t>], rect: Rect) -> Self {
+        LineMap {
+            lines,
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            dim_fg: 0b10_10_10,
+            selected: 0,
+        }
+    }
+
+    /// Selects the line after the selected one, or before it if `!forward`,
+    /// wrapping around at either end.
+    pub fn step(&mut self, forward: bool) {
+        let n = self.lines.len();
+        if n == 0 {
+            return;
+        }
+        self.selected = if forward {
+            (self.selected + 1) % n
+        } else {
+            (self.selected + n - 1) % n
+        };
+    }
+
+    /// Draws the map at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+
+    /// Returns the columns taken by the line names, and the gap after them.
+    fn name_w(&self) -> usize {
+        let longest = self.lines.iter().map(|l| l.name.len()).max();
+        longest.unwrap_or(0) + 2
+    }
+
+    /// Draws line `i` with its track on `row` of the window `w`.
+    fn draw_line(&self, w: &mut Cursor, i: usize, row: usize) {
+        let line = &self.lines[i];
+        let (_, width) = w.size();
+        let selected = i == self.selected;
+        let color = if selected { line.color } else { self.dim_fg };
+        {
+            let (fg, bg) = if selected {
+                (self.bg, color)
+            } else {
+                (color, self.bg)
+            };
+            let mut w = w.styled(fg, bg);
+            w.puts_truncated_at(row, 0, line.name, width).ok();
+        }
+        let n = line.stations.len();
+        let left = self.name_w();
+        if n == 0 || width <= left {
+            return;
+        }
+        let spacing = (width - left) / n;
+        if spacing == 0 {
+            return;
+        }
+        let tick = |s: usize| left + s * spacing + spacing / 2;
+        let name_fg = if selected { self.fg } else { self.dim_fg };
+        let mut w = w.styled(color, self.bg);
+        w.hline(row, tick(0), tick(n - 1) + 1 - tick(0), TRACK);
+        for (s, name) in line.stations.iter().enumerate() {
+            let above = s % 2 == 1;
+            let mark = if line.is_interchange(s) {
+                INTERCHANGE
+            } else if above {
+                STATION_UP
+            } else {
+                STATION_DOWN
+            };
+            w.hline(row, tick(s), 1, mark);
+            // Centered on the tick, but kept on the map.
+            let room = usize::min(2 * spacing - 1, width - left);
+            let len = usize::min(name.len(), room);
+            let col = tick(s).saturating_sub(len / 2);
+            let col = usize::min(usize::max(col, left), width - len);
+            let name_row = if above { row - 1 } else { row + 1 };
+            let mut w = w.styled(name_fg, self.bg);
+            w.puts_truncated_at(name_row, col, name, len).ok();
+        }
+    }
+}
+
+impl<'t> Widget for LineMap<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.set_scroll(false);
+        w.clear();
+        let n = self.lines.len();
+        if n == 0 {
+            return;
+        }
+        // Each line in the middle of an equal share of the rows.
+        let share = area.h / n;
+        if share < MAP_LINE_H {
+            return;
+        }
+        for i in 0..n {
+            let row = i * share + (share - MAP_LINE_H) / 2 + 1;
+            self.draw_line(&mut w, i, row);
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        let most = self.lines.iter().map(|l| l.stations.len()).max();
+        (
+            MAP_LINE_H * self.lines.len(),
+            self.name_w() + most.unwrap_or(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::TRUNCATION_MARK;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 24;
+    const ROWS: usize = 6;
+
+    static LINES: [MapLine; 2] = [
+        MapLine {
+            name: b"L1",
+            color: 0b00_00_11,
+            stations: &[b"North", b"Market Square", b"South"],
+            interchanges: &[1],
+        },
+        MapLine {
+            name: b"L2",
+            color: 0b00_11_00,
+            stations: &[b"West", b"Market", b"East"],
+            interchanges: &[1],
+        },
+    ];
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        TRACK => '-',
+                        STATION_UP => '\'',
+                        STATION_DOWN => ',',
+                        INTERCHANGE => '#',
+                        TRUNCATION_MARK => '~',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lines_stations_and_selection() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let mut map = LineMap::new(&LINES, Rect::new(0, 0, ROWS, COLS));
+        map.selected = 1;
+        map.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(
+            text(&buf),
+            [
+                "        Market Squ~     ",
+                "L1     ,-----#-----,    ",
+                "     North       South  ",
+                "          Market        ",
+                "L2     ,-----#-----,    ",
+                "     West        East   ",
+            ]
+        );
+        // The selected line in its color, with its name reversed.
+        let track = &buf[4 * COLS + 8];
+        assert_eq!(track.foreground(), LINES[1].color);
+        assert_eq!(buf[4 * COLS].background(), LINES[1].color);
+        assert_eq!(buf[COLS + 8].foreground(), map.dim_fg);
+        assert_eq!(buf[COLS].background(), map.bg);
+
+        map.step(true);
+        assert_eq!(map.selected, 0);
+        map.step(false);
+        assert_eq!(map.selected, 1);
+    }
+}
@@ -0,0 +1,570 @@
+//! Things drawn over a screen for a while, which put back what they covered
+//! when they go away.
+//!
+//! Each overlay keeps a copy of the cells under it, taken with
+//! `Cursor::read_region` just before it is drawn, in a fixed-size buffer of
+//! its own. Restoring is a `write_region` of that copy, so the screen
+//! underneath doesn't need to know how to redraw itself.
+
+use m4vga::rast::text_10x16::AChar;
+
+use crate::chrome::{Corner, SoftKeys};
+use crate::cursor::{paragraph_rows, BoxStyle, Cursor, CursorError, Rect};
+use crate::layout::{Align, Stack};
+use crate::widget::{ButtonStyle, MenuEvent};
+
+/// Widest a `Toast` gets, including a blank cell at either end of its text.
+pub const TOAST_MAX_W: usize = 60;
+
+/// Short message shown on one row for a number of frames, then removed.
+///
+/// The main loop calls `draw` every frame whether or not a message is up;
+/// it only touches the screen when a message appears, changes or expires.
+pub struct Toast<'t> {
+    /// Row the toast is drawn on, centered across the window.
+    pub row: usize,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Message to show, if any.
+    text: Option<&'t [u8]>,
+    /// Frames left before the message is removed.
+    remaining: u32,
+    /// Whether the message needs drawing.
+    dirty: bool,
+    /// Cells under the toast, and where they came from, while it is drawn.
+    saved: [AChar; TOAST_MAX_W],
+    saved_rect: Option<Rect>,
+}
+
+impl<'t> Toast<'t> {
+    /// Makes a toast with nothing to show, in white on black.
+    pub const fn new(row: usize) -> Self {
+        Toast {
+            row,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            text: None,
+            remaining: 0,
+            dirty: false,
+            saved: [AChar::from_ascii_char(0); TOAST_MAX_W],
+            saved_rect: None,
+        }
+    }
+
+    /// Shows `text` for the next `frames` calls to `draw`, replacing any
+    /// message already up.
+    pub fn show(&mut self, text: &'t [u8], frames: u32) {
+        self.text = Some(text);
+        self.remaining = frames;
+        self.dirty = true;
+    }
+
+    /// Returns whether a message is up, or about to be.
+    pub fn is_active(&self) -> bool {
+        self.text.is_some()
+    }
+
+    /// Removes the message now, as if it had expired. The cells under it are
+    /// put back on the next `draw`.
+    pub fn dismiss(&mut self) {
+        self.text = None;
+        self.dirty = true;
+    }
+
+    /// Forgets the cells under the toast without putting them back, and
+    /// draws it again on the next `draw`. Use this after redrawing the
+    /// screen under a toast, so that the old screen isn't put back over the
+    /// new one.
+    pub fn invalidate(&mut self) {
+        self.saved_rect = None;
+        self.dirty = true;
+    }
+
+    /// Draws, keeps or removes the toast, as this frame calls for.
+    pub fn draw(&mut self, c: &mut Cursor) {
+        if self.text.is_some() && self.remaining == 0 {
+            self.text = None;
+            self.dirty = true;
+        }
+        if self.dirty {
+            self.restore(c);
+            if let Some(text) = self.text {
+                self.paint(c, text);
+            }
+            self.dirty = false;
+        }
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    /// Puts back the cells under the toast, if it is drawn.
+    fn restore(&mut self, c: &mut Cursor) {
+        if let Some(rect) = self.saved_rect.take() {
+            c.write_region(rect, &self.saved).ok();
+        }
+    }
+
+    /// Saves the cells where `text` goes, then draws it there.
+    fn paint(&mut self, c: &mut Cursor, text: &[u8]) {
+        let (height, width) = c.size();
+        if self.row >= height {
+            return;
+        }
+        let w = usize::min(usize::min(text.len() + 2, TOAST_MAX_W), width);
+        let rect = Rect::new(self.row, (width - w) / 2, 1, w);
+        if c.read_region(rect, &mut self.saved).is_err() {
+            return;
+        }
+        self.saved_rect = Some(rect);
+        let mut box_ = c.window(rect.row, rect.col, 1, w);
+        box_.fg = self.fg;
+        box_.bg = self.bg;
+        box_.clear();
+        box_.set_scroll(false);
+        box_.goto(0, 1);
+        box_.puts_truncated(text, w.saturating_sub(2));
+    }
+}
+
+/// Largest a `Dialog` can be, in rows and columns.
+pub const DIALOG_MAX_H: usize = 12;
+pub const DIALOG_MAX_W: usize = 40;
+
+/// Framed box asking a question, with two buttons to answer it, drawn over
+/// whatever is on screen.
+///
+/// `open` saves the cells the dialog covers before drawing it, and closing
+/// it, by answering or with `close`, puts them back, so the screen behind
+/// it reappears as it was.
+///
+/// The message and the buttons below it are centered top to bottom in the
+/// box, however many rows the message takes.
+pub struct Dialog<'t> {
+    /// Question asked, laid out as by `Cursor::put_paragraph`.
+    pub message: &'t [u8],
+    /// Labels of the two buttons, left to right.
+    pub buttons: [&'t [u8]; 2],
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub style: BoxStyle,
+    /// Index of the button an answer would pick.
+    selected: usize,
+    /// Cells under the dialog, and where they came from, while it is open.
+    saved: [AChar; DIALOG_MAX_H * DIALOG_MAX_W],
+    rect: Option<Rect>,
+}
+
+impl<'t> Dialog<'t> {
+    /// Makes a closed dialog, in white on black with a single-line frame.
+    pub const fn new(message: &'t [u8], buttons: [&'t [u8]; 2]) -> Self {
+        Dialog {
+            message,
+            buttons,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            style: BoxStyle::SINGLE,
+            selected: 0,
+            saved: [AChar::from_ascii_char(0); DIALOG_MAX_H * DIALOG_MAX_W],
+            rect: None,
+        }
+    }
+
+    /// Saves what is in `rect` and draws the dialog there, with the first
+    /// button selected. An open dialog is closed first.
+    ///
+    /// `rect` must fit in the window, and in `DIALOG_MAX_H` by
+    /// `DIALOG_MAX_W`; if not, nothing is drawn and the error is returned.
+    pub fn open(
+        &mut self,
+        c: &mut Cursor,
+        rect: Rect,
+    ) -> Result<(), CursorError> {
+        self.close(c);
+        if rect.h > DIALOG_MAX_H || rect.w > DIALOG_MAX_W {
+            return Err(CursorError::BufferTooSmall {
+                needed: rect.h * rect.w,
+                got: usize::min(rect.h, DIALOG_MAX_H)
+                    * usize::min(rect.w, DIALOG_MAX_W),
+            });
+        }
+        c.read_region(rect, &mut self.saved)?;
+        self.rect = Some(rect);
+        self.selected = 0;
+        self.draw(c);
+        Ok(())
+    }
+
+    /// Puts back what was under the dialog, if it is open.
+    pub fn close(&mut self, c: &mut Cursor) {
+        if let Some(rect) = self.rect.take() {
+            c.write_region(rect, &self.saved).ok();
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.rect.is_some()
+    }
+
+    /// Returns the index of the selected button.
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Acts on a button press while the dialog is open. Any of the arrows
+    /// select the other button; `Select` closes the dialog and returns the
+    /// index of the button chosen. Returns `None` otherwise.
+    pub fn handle(
+        &mut self,
+        c: &mut Cursor,
+        event: MenuEvent,
+    ) -> Option<usize> {
+        if !self.is_open() {
+            return None;
+        }
+        match event {
+            MenuEvent::Up
+            | MenuEvent::Down
+            | MenuEvent::Left
+            | MenuEvent::Right => {
+                self.selected = 1 - self.selected;
+                self.draw(c);
+                None
+            }
+            MenuEvent::Select => {
+                self.close(c);
+                Some(self.selected)
+            }
+        }
+    }
+
+    /// Draws the dialog where it is open. Nothing is saved, so this is safe
+    /// to repeat.
+    pub fn draw(&self, c: &mut Cursor) {
+        let rect = match self.rect {
+            Some(rect) => rect,
+            None => return,
+        };
+        let mut w = c.window(rect.row, rect.col, rect.h, rect.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        w.draw_box(0, 0, rect.h, rect.w, &self.style);
+        // The message over the row of buttons, a row apart, centered inside
+        // the frame less a cell of margin at the sides.
+        let inside =
+            Rect::new(1, 2, rect.h.saturating_sub(2), rect.w.saturating_sub(4));
+        let mut column = Stack::column(1);
+        column.align = Align::Fill;
+        column.justify = Align::Center;
+        column.push((paragraph_rows(self.message, inside.w), inside.w));
+        column.push((1, inside.w));
+        let parts = column.layout(inside);
+        w.put_paragraph(parts[0], self.message);
+
+        let mut row = Stack::row(0);
+        row.justify = Align::Fill;
+        for label in self.buttons.iter() {
+            row.push((1, label.len() + 2));
+        }
+        let places = row.layout(parts[1]);
+        for (i, label) in self.buttons.iter().enumerate() {
+            let place = places[i];
+            let mut button = w.window(place.row, place.col, 1, place.w);
+            if i == self.selected {
+                button.fg = self.bg;
+                button.bg = self.fg;
+            }
+            button.clear();
+            button.puts_at(0, 1, label).ok();
+        }
+    }
+}
+
+/// Answer to a `Confirm` question.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Decision {
+    Yes,
+    No,
+}
+
+/// Looks of the yes and no keys of a `Confirm`: white on green, and white
+/// on blue.
+pub const YES_STYLE: ButtonStyle = ButtonStyle {
+    normal: (0b11_11_11, 0b00_10_00),
+    focused: (0b00_10_00, 0b11_11_11),
+    pressed: (0b11_11_11, 0b00_11_00),
+    frame: BoxStyle::SINGLE,
+};
+pub const NO_STYLE: ButtonStyle = ButtonStyle {
+    normal: (0b11_11_11, 0b11_00_00),
+    focused: (0b11_00_00, 0b11_11_11),
+    pressed: (0b11_11_11, 0b00_00_11),
+    frame: BoxStyle::SINGLE,
+};
+
+/// Yes or no question, asked in a `Dialog` over the screen, and answered
+/// with the buttons by two of the screen corners.
+///
+/// `open` draws the dialog and labels the two corners' soft keys, blanking
+/// the other two. The keys have the labels of the dialog's buttons, yes then
+/// no, which callers can change, as for another language. Presses of those buttons, through `press`, or moves
+/// through the dialog's own buttons, through `handle`, come back as a
+/// `Decision`; what to do about it is up to the caller. Answering closes the
+/// dialog. The soft keys are left for the next screen to draw over.
+pub struct Confirm<'t> {
+    /// Box the question is asked in. Its colors and frame can be changed.
+    pub dialog: Dialog<'t>,
+    /// Corners whose buttons answer yes and no.
+    pub yes: Corner,
+    pub no: Corner,
+    pub yes_style: &'t ButtonStyle,
+    pub no_style: &'t ButtonStyle,
+}
+
+impl<'t> Confirm<'t> {
+    /// Makes a closed question, answered yes by the button at `yes` and no
+    /// by the one at `no`, with keys labelled YES and NO in `YES_STYLE` and
+    /// `NO_STYLE`.
+    pub const fn new(question: &'t [u8], yes: Corner, no: Corner) -> Self {
+        Confirm {
+            dialog: Dialog::new(question, [b"YES", b"NO"]),
+            yes,
+            no,
+            yes_style: &YES_STYLE,
+            no_style: &NO_STYLE,
+        }
+    }
+
+    /// Asks the question in a dialog at `rect`, like `Dialog::open`, and
+    /// draws the soft keys. Pass the window from `screen_area`; corners
+    /// without a key are blanked in its current colors.
+    pub fn open(
+        &mut self,
+        c: &mut Cursor,
+        rect: Rect,
+    ) -> Result<(), CursorError> {
+        self.dialog.open(c, rect)?;
+        self.keys().draw(c);
+        Ok(())
+    }
+
+    /// Takes the dialog down without an answer.
+    pub fn close(&mut self, c: &mut Cursor) {
+        self.dialog.close(c);
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.dialog.is_open()
+    }
+
+    /// Answers for a press of the button at `corner`, if the question is
+    /// open and that is the yes or no button.
+    pub fn press(
+        &mut self,
+        c: &mut Cursor,
+        corner: Corner,
+    ) -> Option<Decision> {
+        if !self.is_open() {
+            return None;
+        }
+        let decision = if corner == self.yes {
+            Decision::Yes
+        } else if corner == self.no {
+            Decision::No
+        } else {
+            return None;
+        };
+        self.close(c);
+        Some(decision)
+    }
+
+    /// Passes `event` to the dialog, like `Dialog::handle`, answering with
+    /// the button chosen.
+    pub fn handle(
+        &mut self,
+        c: &mut Cursor,
+        event: MenuEvent,
+    ) -> Option<Decision> {
+        match self.dialog.handle(c, event)? {
+            0 => Some(Decision::Yes),
+            _ => Some(Decision::No),
+        }
+    }
+
+    /// Returns the soft keys for the question, as `open` draws them.
+    pub fn keys(&self) -> SoftKeys<'t> {
+        let [yes, no] = self.dialog.buttons;
+        let mut keys = SoftKeys::new(self.no_style);
+        keys.set(self.yes, Some(yes));
+        keys.set_style(self.yes, Some(self.yes_style));
+        keys.set(self.no, Some(no));
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLS: usize = 10;
+
+    fn row(buf: &[AChar]) -> String {
+        buf.iter().map(|a| a.char()).collect()
+    }
+
+    #[test]
+    fn toast_expires_and_restores() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * 2];
+        let mut toast = Toast::new(1);
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.set_scroll(false);
+            c.puts_at(1, 0, b"0123456789").ok();
+            toast.draw(&mut c);
+            toast.show(b"hi", 2);
+            toast.draw(&mut c);
+        }
+        assert!(toast.is_active());
+        assert_eq!(row(&buf[COLS..]), "012 hi 789");
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            toast.draw(&mut c);
+            toast.draw(&mut c);
+        }
+        assert!(!toast.is_active());
+        assert_eq!(row(&buf[COLS..]), "0123456789");
+    }
+
+    #[test]
+    fn newer_toast_replaces_older() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let mut toast = Toast::new(0);
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.set_scroll(false);
+            c.puts_at(0, 0, b"abcdefghij").ok();
+            toast.show(b"long one", 10);
+            toast.draw(&mut c);
+            toast.show(b"x", 10);
+            toast.draw(&mut c);
+        }
+        assert_eq!(row(&buf), "abc x ghij");
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            // The screen is redrawn under the toast.
+            c.set_scroll(false);
+            c.puts_at(0, 0, b"ABCDEFGHIJ").ok();
+            toast.invalidate();
+            toast.draw(&mut c);
+            toast.dismiss();
+            toast.draw(&mut c);
+        }
+        assert_eq!(row(&buf), "ABCDEFGHIJ");
+    }
+
+    #[test]
+    fn dialog_restores_what_it_covered() {
+        const W: usize = 16;
+        let mut buf = [AChar::from_ascii_char(b'.'); W * 6];
+        let mut dialog = Dialog::new(b"Go on?", [b"YES", b"NO"]);
+        dialog.fg = 1;
+        dialog.bg = 2;
+        dialog.style = BoxStyle::ASCII;
+        {
+            let mut c = Cursor::new(&mut buf, W);
+            c.puts_at(0, 0, b"behind").ok();
+            dialog.open(&mut c, Rect::new(0, 1, 5, 14)).unwrap();
+        }
+        let rows = |buf: &[AChar]| -> Vec<String> {
+            buf.chunks(W)
+                .map(|row| {
+                    row.iter()
+                        .map(|a| match a.ascii_char() {
+                            b' ' if a.background() == 1 => '#',
+                            other => other as char,
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        assert_eq!(
+            rows(&buf),
+            [
+                "b+------------+.",
+                ".| Go on?     |.",
+                ".|            |.",
+                ".|  #YES# NO  |.",
+                ".+------------+.",
+                "................",
+            ]
+        );
+        {
+            let mut c = Cursor::new(&mut buf, W);
+            assert_eq!(dialog.handle(&mut c, MenuEvent::Down), None);
+        }
+        assert_eq!(rows(&buf)[3], ".|   YES #NO# |.");
+        {
+            let mut c = Cursor::new(&mut buf, W);
+            assert_eq!(dialog.handle(&mut c, MenuEvent::Select), Some(1));
+            assert!(!dialog.is_open());
+            assert_eq!(dialog.handle(&mut c, MenuEvent::Select), None);
+        }
+        assert_eq!(rows(&buf)[0], "behind..........");
+        assert_eq!(rows(&buf)[3], "................");
+
+        let mut c = Cursor::new(&mut buf, W);
+        let too_big = Rect::new(0, 0, DIALOG_MAX_H + 1, 4);
+        assert!(dialog.open(&mut c, too_big).is_err());
+        assert!(!dialog.is_open());
+    }
+
+    #[test]
+    fn confirm_labels_corners_and_decides() {
+        const W: usize = 20;
+        let mut buf = [AChar::from_ascii_char(b'.'); W * 28];
+        let mut confirm =
+            Confirm::new(b"Sure?", Corner::BottomLeft, Corner::TopRight);
+        confirm.dialog.style = BoxStyle::ASCII;
+        let text = |buf: &[AChar], row: usize, col: usize| -> String {
+            let at = row * W + col;
+            let side = BoxStyle::SINGLE.left;
+            buf[at..at + 6]
+                .iter()
+                .map(|a| match a.ascii_char() {
+                    ch if ch == side => '|',
+                    ch => ch as char,
+                })
+                .collect()
+        };
+        {
+            let mut c = Cursor::new(&mut buf, W);
+            confirm.open(&mut c, Rect::new(3, 7, 3, 6)).unwrap();
+            assert_eq!(confirm.press(&mut c, Corner::TopLeft), None);
+            assert!(confirm.is_open());
+        }
+        assert_eq!(text(&buf, 3, 7), "+----+");
+        assert_eq!(text(&buf, 26, 1), "|YES |");
+        assert_eq!(buf[26 * W + 2].background(), YES_STYLE.normal.1);
+        assert_eq!(text(&buf, 11, 13), "| NO |");
+        assert_eq!(buf[11 * W + 15].background(), NO_STYLE.normal.1);
+        assert_eq!(text(&buf, 11, 1), "      ");
+        {
+            let mut c = Cursor::new(&mut buf, W);
+            let no = confirm.press(&mut c, Corner::TopRight);
+            assert_eq!(no, Some(Decision::No));
+            assert!(!confirm.is_open());
+            assert_eq!(confirm.press(&mut c, Corner::BottomLeft), None);
+            confirm.open(&mut c, Rect::new(3, 7, 3, 6)).unwrap();
+            let yes = confirm.handle(&mut c, MenuEvent::Select);
+            assert_eq!(yes, Some(Decision::Yes));
+        }
+        assert_eq!(text(&buf, 3, 7), "......");
+
+        // In other words.
+        confirm.dialog.buttons = [b"OUI", b"NON"];
+        confirm
+            .open(&mut Cursor::new(&mut buf, W), Rect::new(3, 7, 3, 6))
+            .unwrap();
+        assert_eq!(text(&buf, 26, 1), "|OUI |");
+        assert_eq!(text(&buf, 11, 13), "|NON |");
+    }
+}
@@ -0,0 +1,185 @@
+//! Amounts of money, kept as whole numbers of cents.
+//!
+//! There's no floating point here: the decimal point goes in as the amount
+//! is formatted, by splitting off the last two digits.
+
+use core::fmt;
+
+use crate::cursor::{Cursor, Rect};
+use crate::widget::Widget;
+
+/// Currency prefix for pounds sterling, the CP437 `£`.
+pub const POUND: &[u8] = b"\x9C";
+
+/// Amount without its sign, formatted with thousands grouping and two
+/// decimal places: `1,234.50`.
+struct Amount(u64);
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (whole, cents) = (self.0 / 100, self.0 % 100);
+        let mut scale = 1;
+        while whole / scale >= 1000 {
+            scale *= 1000;
+        }
+        write!(f, "{}", whole / scale)?;
+        while scale > 1 {
+            scale /= 1000;
+            write!(f, ",{:03}", whole / scale % 1000)?;
+        }
+        write!(f, ".{:02}", cents)
+    }
+}
+
+/// Returns the size of `cents`, without its sign.
+fn magnitude(cents: i32) -> u64 {
+    (-i64::from(cents)).max(i64::from(cents)) as u64
+}
+
+/// Writes `cents` as an amount with two decimal places, grouping thousands
+/// with commas and leading with a minus if negative: `-1,234.50`. No
+/// currency is written; `Price` adds that.
+pub fn format_price(cents: i32, out: &mut impl fmt::Write) -> fmt::Result {
+    if cents < 0 {
+        out.write_char('-')?;
+    }
+    write!(out, "{}", Amount(magnitude(cents)))
+}
+
+/// Returns the number of characters `format_price` writes for `cents`.
+pub fn price_width(cents: i32) -> usize {
+    let whole = (magnitude(cents) / 100) as usize;
+    let digits = crate::list::digits(whole);
+    let sign = if cents < 0 { 1 } else { 0 };
+    sign + digits + (digits - 1) / 3 + 3
+}
+
+/// Price right-aligned in a field, such as `-£2.50` for a refund.
+///
+/// A price too wide for its field is drawn as a row of `#`, like a
+/// spreadsheet does, rather than cut down to a wrong amount.
+#[derive(Copy, Clone, Debug)]
+pub struct Price<'t> {
+    pub cents: i32,
+    /// Currency drawn between the sign and the amount, as glyphs.
+    pub prefix: &'t [u8],
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+}
+
+impl<'t> Price<'t> {
+    /// Makes a price in pounds, in white on black.
+    pub const fn new(cents: i32, rect: Rect) -> Self {
+        Price {
+            cents,
+            prefix: POUND,
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+        }
+    }
+
+    /// Changes the amount, returning whether it is different and so needs a
+    /// `redraw`.
+    pub fn set(&mut self, cents: i32) -> bool {
+        let changed = cents != self.cents;
+        self.cents = cents;
+        changed
+    }
+
+    /// Draws the price at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'t> Widget for Price<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, 1, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.set_scroll(false);
+        w.clear();
+        let (_, width) = self.min_size();
+        if width > area.w {
+            w.hline(0, 0, area.w, b'#');
+            return;
+        }
+        let mut col = area.w - width;
+        if self.cents < 0 {
+            w.hline(0, col, 1, b'-');
+            col += 1;
+        }
+        w.puts_at(0, col, self.prefix).ok();
+        col += self.prefix.len();
+        let amount = Amount(magnitude(self.cents));
+        write_at!(w, 0, col, "{}", amount).ok();
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, self.prefix.len() + price_width(self.cents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    struct Text(String);
+
+    impl fmt::Write for Text {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.push_str(s);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn formats_and_measures() {
+        let cases: [(i32, &str); 8] = [
+            (0, "0.00"),
+            (5, "0.05"),
+            (250, "2.50"),
+            (9999, "99.99"),
+            (123_456, "1,234.56"),
+            (100_000_000, "1,000,000.00"),
+            (-250, "-2.50"),
+            (i32::MIN, "-21,474,836.48"),
+        ];
+        for &(cents, expected) in cases.iter() {
+            let mut text = Text(String::new());
+            format_price(cents, &mut text).unwrap();
+            assert_eq!(text.0, expected);
+            assert_eq!(price_width(cents), expected.len(), "{}", expected);
+        }
+    }
+
+    #[test]
+    fn widget_right_aligns_or_overflows() {
+        const COLS: usize = 12;
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS];
+        let row = |buf: &[AChar]| -> String {
+            buf.iter()
+                .map(|a| match a.ascii_char() {
+                    0x9C => 'L',
+                    other => other as char,
+                })
+                .collect()
+        };
+        let mut price = Price::new(-250, Rect::new(0, 1, 1, 10));
+        price.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(row(&buf), ".    -L2.50.");
+
+        assert!(price.set(123_456));
+        price.prefix = b"$";
+        price.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(row(&buf), ". $1,234.56.");
+        assert!(!price.set(123_456));
+
+        price.prefix = b"EUR ";
+        price.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(row(&buf), ".##########.");
+    }
+}
@@ -0,0 +1,198 @@
+//! Short references for transactions, for passengers to note down and quote.
+//!
+//! A reference is `REF_LEN` characters of Crockford's base 32: digits and
+//! capital letters, leaving out `I`, `L`, `O` and `U` so that none can be
+//! mistaken for another. The last character is a check on the rest, so a
+//! reference copied down wrong can be told from one never issued.
+//!
+//! The check is the Luhn algorithm worked in base 32 instead of base 10.
+//! Crockford's own check needs five symbols more than the alphabet, which
+//! couldn't all be drawn as a banner. It catches any one
+//! character changed, and any two side by side swapped except `0` and `Z`.
+
+/// The symbols of Crockford's base 32, in order of their values.
+pub const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Characters in a reference, the check character included.
+pub const REF_LEN: usize = 8;
+
+/// Characters before the check character, and the bits they hold.
+const DATA_LEN: usize = REF_LEN - 1;
+const DATA_BITS: u32 = 5 * DATA_LEN as u32;
+
+/// Odd, so that multiplying by it shuffles the numbers below `2^DATA_BITS`
+/// without two ending up the same.
+const SPREAD: u64 = 0x5_DEEC_E66D;
+
+/// A transaction's reference, such as `3QW8K0TM`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Reference([u8; REF_LEN]);
+
+impl Reference {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Hands out references, each different from every other it has handed
+/// out.
+///
+/// References are counted, and each count is spread over every reference
+/// there could be and then offset by a nonce, which should differ from one
+/// boot to the next so that references from different sessions don't
+/// repeat in the same order. No reference repeats within a session until
+/// `2^35` have been handed out.
+#[derive(Copy, Clone, Debug)]
+pub struct References {
+    nonce: u64,
+    count: u64,
+}
+
+impl References {
+    pub const fn new(nonce: u32) -> Self {
+        References {
+            nonce: nonce as u64,
+            count: 0,
+        }
+    }
+
+    /// Returns how many references have been handed out.
+    pub fn issued(&self) -> u64 {
+        self.count
+    }
+
+    /// Hands out the next reference.
+    pub fn issue(&mut self) -> Reference {
+        let mask = (1 << DATA_BITS) - 1;
+        let mut n =
+            self.count.wrapping_mul(SPREAD).wrapping_add(self.nonce) & mask;
+        self.count += 1;
+        let mut values = [0; REF_LEN];
+        for v in values[..DATA_LEN].iter_mut().rev() {
+            *v = (n % 32) as u8;
+            n /= 32;
+        }
+        values[DATA_LEN] = check_value(&values[..DATA_LEN]);
+        let mut text = [0; REF_LEN];
+        for (t, &v) in text.iter_mut().zip(values.iter()) {
+            *t = ALPHABET[usize::from(v)];
+        }
+        Reference(text)
+    }
+}
+
+/// Returns the value of a symbol as Crockford reads it: either case, and
+/// `O` as zero and `I` and `L` as one, since those are what they would be
+/// mistaken for. Returns `None` for anything else.
+fn symbol_value(c: u8) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        b'O' => Some(0),
+        b'I' | b'L' => Some(1),
+        c => ALPHABET.iter().position(|&a| a == c).map(|v| v as u8),
+    }
+}
+
+/// Returns the value of the check character for `values`, by Luhn's method
+/// in base 32: from the right, every other value doubled starting with the
+/// last, and the digits of each product added. The check value brings the
+/// sum up to a multiple of 32.
+fn check_value(values: &[u8]) -> u8 {
+    let mut double = true;
+    let mut sum = 0;
+    for &v in values.iter().rev() {
+        let mut v = u32::from(v);
+        if double {
+            v *= 2;
+            v = v / 32 + v % 32;
+        }
+        sum += v;
+        double = !double;
+    }
+    ((32 - sum % 32) % 32) as u8
+}
+
+/// Returns whether `text` is a reference with the right check character.
+///
+/// Reads it as a passenger might have written it down: in either case,
+/// with `O`, `I` and `L` for the digits they look like, and with hyphens
+/// anywhere, which are left out.
+pub fn verify_reference(text: &[u8]) -> bool {
+    let mut values = [0; REF_LEN];
+    let mut len = 0;
+    for &c in text.iter().filter(|&&c| c != b'-') {
+        match symbol_value(c) {
+            Some(v) if len < REF_LEN => values[len] = v,
+            _ => return false,
+        }
+        len += 1;
+    }
+    len == REF_LEN && check_value(&values[..DATA_LEN]) == values[DATA_LEN]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn references_are_distinct_and_check() {
+        let mut refs = References::new(0xDEAD_BEEF);
+        let issued: Vec<Reference> = (0..1000).map(|_| refs.issue()).collect();
+        assert_eq!(refs.issued(), 1000);
+        for (i, r) in issued.iter().enumerate() {
+            assert!(r.as_bytes().iter().all(|c| ALPHABET.contains(c)));
+            assert!(verify_reference(r.as_bytes()));
+            assert!(!issued[..i].contains(r));
+        }
+        // Another boot, another order.
+        let mut other = References::new(1);
+        assert_ne!(other.issue(), issued[0]);
+    }
+
+    #[test]
+    fn mistakes_are_caught() {
+        let good = References::new(42).issue().0;
+        for i in 0..REF_LEN {
+            for &c in ALPHABET.iter().filter(|&&c| c != good[i]) {
+                let mut bad = good;
+                bad[i] = c;
+                assert!(!verify_reference(&bad), "{:?}", bad);
+            }
+        }
+        for i in 0..REF_LEN - 1 {
+            let pair = [good[i], good[i + 1]];
+            if pair[0] == pair[1] || pair == *b"0Z" || pair == *b"Z0" {
+                continue;
+            }
+            let mut bad = good;
+            bad.swap(i, i + 1);
+            assert!(!verify_reference(&bad), "{:?}", bad);
+        }
+        assert!(!verify_reference(&good[..REF_LEN - 1]));
+        assert!(!verify_reference(b"0000000U"));
+    }
+
+    #[test]
+    fn read_as_written_down() {
+        assert!(verify_reference(b"00000000"));
+        assert!(verify_reference(b"oooo-OOOO"));
+        let r = References::new(7).issue();
+        let mut lower = [0; REF_LEN + 1];
+        lower[..4].copy_from_slice(&r.as_bytes()[..4]);
+        lower[4] = b'-';
+        lower[5..].copy_from_slice(&r.as_bytes()[4..]);
+        lower.make_ascii_lowercase();
+        assert!(verify_reference(&lower));
+
+        let mut refs = References::new(7);
+        let one = (0..100)
+            .map(|_| refs.issue())
+            .find(|r| r.as_bytes().contains(&b'1'))
+            .unwrap();
+        let ell: Vec<u8> = one
+            .as_bytes()
+            .iter()
+            .map(|&c| if c == b'1' { b'l' } else { c })
+            .collect();
+        assert!(verify_reference(&ell));
+    }
+}
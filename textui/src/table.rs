@@ -0,0 +1,250 @@
+//! Tables of text laid out in fixed-width columns.
+//!
+//! A `Table` is described by a list of `Column`s, usually kept in static
+//! data, and an origin. It draws a header row with a rule under it, followed
+//! by data rows, padding or truncating each cell to its column.
+
+use crate::cursor::Cursor;
+
+/// Rows taken by the header and the rule under it.
+pub const HEADER_ROWS: usize = 2;
+
+/// Cells left blank between columns.
+const GAP: usize = 1;
+
+/// CP437 glyph used for the rule under the header (`─`).
+const RULE: u8 = 0xC4;
+
+/// How text is placed in a cell wider than it is.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Description of one column of a `Table`.
+#[derive(Copy, Clone, Debug)]
+pub struct Column<'t> {
+    /// Text drawn in the header row, aligned like the column's cells.
+    pub header: &'t [u8],
+    /// Width in cells, not counting the gap before the next column.
+    pub width: usize,
+    pub align: Align,
+}
+
+impl<'t> Column<'t> {
+    pub const fn new(header: &'t [u8], width: usize, align: Align) -> Self {
+        Column {
+            header,
+            width,
+            align,
+        }
+    }
+}
+
+/// Columns placed at a fixed origin, ready to draw.
+///
+/// ```ignore
+/// static COLUMNS: [Column; 3] = [
+///     Column::new(b"Ticket", 20, Align::Left),
+///     Column::new(b"Zone", 4, Align::Center),
+///     Column::new(b"Price", 7, Align::Right),
+/// ];
+///
+/// Table::new(&COLUMNS, 5, 2).selected(Some(cursor)).draw(c, &rows);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Table<'t> {
+    columns: &'t [Column<'t>],
+    row: usize,
+    col: usize,
+    selected: Option<usize>,
+}
+
+impl<'t> Table<'t> {
+    /// Makes a table whose header row starts at `row`, `col`.
+    pub const fn new(
+        columns: &'t [Column<'t>],
+        row: usize,
+        col: usize,
+    ) -> Self {
+        Table {
+            columns,
+            row,
+            col,
+            selected: None,
+        }
+    }
+
+    /// Sets the data row, if any, that is drawn highlighted, with the
+    /// cursor's colors swapped.
+    pub fn selected(mut self, index: Option<usize>) -> Self {
+        self.selected = index;
+        self
+    }
+
+    /// Returns the width of the table in cells, including the gaps between
+    /// columns.
+    pub fn width(&self) -> usize {
+        let cells: usize = self.columns.iter().map(|col| col.width).sum();
+        cells + GAP * self.columns.len().saturating_sub(1)
+    }
+
+    /// Draws the header row and the rule under it, in the current colors.
+    pub fn draw_header(&self, c: &mut Cursor) {
+        let headers = self.columns.iter().map(|col| col.header);
+        self.draw_cells(c, self.row, headers);
+        c.hline(self.row + 1, self.col, self.width(), RULE);
+    }
+
+    /// Draws data row `index` (counting from 0 below the header) with the
+    /// given cells, one per column. Missing cells are left blank and extra
+    /// ones are ignored. Text too long for its column is cut short and marked
+    /// like `Cursor::puts_truncated`.
+    pub fn draw_row(&self, c: &mut Cursor, index: usize, cells: &[&[u8]]) {
+        let row = self.row + HEADER_ROWS + index;
+        let cells = cells.iter().cloned();
+        if self.selected == Some(index) {
+            let (fg, bg) = (c.fg, c.bg);
+            self.draw_cells(&mut c.styled(bg, fg), row, cells);
+        } else {
+            self.draw_cells(c, row, cells);
+        }
+    }
+
+    /// Draws a rule like the one under the header across data row `index`,
+    /// such as to set a total apart from the rows above it.
+    pub fn draw_rule(&self, c: &mut Cursor, index: usize) {
+        let row = self.row + HEADER_ROWS + index;
+        c.hline(row, self.col, self.width(), RULE);
+    }
+
+    /// Draws the header and as many of `rows` as fit above the bottom of the
+    /// window. Returns the number of data rows drawn.
+    pub fn draw(&self, c: &mut Cursor, rows: &[&[&[u8]]]) -> usize {
+        self.draw_header(c);
+        let (height, _) = c.size();
+        let room = height.saturating_sub(self.row + HEADER_ROWS);
+        let n = usize::min(rows.len(), room);
+        for (i, cells) in rows[..n].iter().enumerate() {
+            self.draw_row(c, i, cells);
+        }
+        n
+    }
+
+    /// Blanks the table's full width on `row`, then draws `cells` into their
+    /// columns.
+    fn draw_cells<'c>(
+        &self,
+        c: &mut Cursor,
+        row: usize,
+        cells: impl Iterator<Item = &'c [u8]>,
+    ) {
+        c.hline(row, self.col, self.width(), b' ');
+        let mut x = self.col;
+        for (column, text) in self.columns.iter().zip(cells) {
+            if column.width > 0 {
+                let mut cell = c.window(row, x, 1, column.width);
+                draw_cell(&mut cell, column, text);
+            }
+            x += column.width + GAP;
+        }
+    }
+}
+
+/// Places `text` in the one-row window `cell`, which has already been
+/// blanked.
+fn draw_cell(cell: &mut Cursor, column: &Column, text: &[u8]) {
+    let width = column.width;
+    if text.len() > width || column.align == Align::Left {
+        // Typing up to the last cell moves the cursor off the window; keep
+        // that from scrolling the text away.
+        cell.set_scroll(false);
+        cell.puts_truncated(text, width);
+    } else if column.align == Align::Right {
+        cell.puts_right(0, width - 1, text);
+    } else {
+        cell.puts_centered(0, text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::TRUNCATION_MARK;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 20;
+    const ROWS: usize = 5;
+
+    static COLUMNS: [Column; 3] = [
+        Column::new(b"Name", 6, Align::Left),
+        Column::new(b"Z", 3, Align::Center),
+        Column::new(b"Price", 5, Align::Right),
+    ];
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        RULE => '-',
+                        TRUNCATION_MARK => '>',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn draws_header_rule_and_rows() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let rows: [&[&[u8]]; 4] = [
+            &[b"Adult", b"1", b"2.50"],
+            &[b"Student", b"12", b"1.25"],
+            &[b"Child"],
+            &[b"Never", b"shown", b"!"],
+        ];
+        let drawn = {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let table = Table::new(&COLUMNS, 0, 1);
+            assert_eq!(table.width(), 16);
+            table.draw(&mut c, &rows)
+        };
+        assert_eq!(drawn, 3);
+        assert_eq!(
+            text(&buf),
+            [
+                ".Name    Z  Price...",
+                ".----------------...",
+                ".Adult   1   2.50...",
+                ".Stude> 12   1.25...",
+                ".Child           ...",
+            ]
+        );
+    }
+
+    #[test]
+    fn selected_row_swaps_colors() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut c = Cursor::new_with(&mut buf, COLS, 1, 2);
+            let table = Table::new(&COLUMNS, 1, 0).selected(Some(1));
+            table.draw_row(&mut c, 0, &[b"a"]);
+            table.draw_row(&mut c, 1, &[b"b"]);
+            assert_eq!((c.fg, c.bg), (1, 2));
+        }
+        let colors = |row: usize| {
+            let cells = &buf[row * COLS..row * COLS + 16];
+            assert!(cells
+                .iter()
+                .all(|a| a.background() == cells[0].background()));
+            (cells[0].foreground(), cells[0].background())
+        };
+        assert_eq!(colors(3), (1, 2));
+        assert_eq!(colors(4), (2, 1));
+        assert_eq!(buf[4 * COLS + 16].ascii_char(), b'.');
+    }
+}
@@ -0,0 +1,187 @@
+//! Scrolling view of text too long for the screen.
+
+use crate::banner::FULL_BLOCK;
+use crate::chart::LIGHT_SHADE;
+use crate::cursor::{paragraph_lines, Cursor, Rect};
+
+/// Most rows of wrapped text a `TextViewer` keeps. Anything past that is not
+/// shown.
+pub const VIEWER_MAX_LINES: usize = 256;
+
+/// Long text wrapped as prose, like `Cursor::put_paragraph`, with a window of
+/// it showing and a scrollbar in the rightmost column.
+///
+/// The text is wrapped once, when it is set, and the viewer remembers where
+/// each row starts, so drawing any part of it costs no more than drawing the
+/// first screenful. Offsets are kept in 16 bits, so text past the first 64KiB
+/// is not shown either.
+#[derive(Clone, Debug)]
+pub struct TextViewer<'t> {
+    text: &'t [u8],
+    rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Start and end in `text` of each row, as laid out.
+    lines: [(u16, u16); VIEWER_MAX_LINES],
+    count: usize,
+    /// Index of the row at the top of the window.
+    top: usize,
+}
+
+impl<'t> TextViewer<'t> {
+    /// Makes a viewer of `text`, scrolled to the top, in white on black.
+    pub fn new(text: &'t [u8], rect: Rect) -> Self {
+        let mut viewer = TextViewer {
+            text: b"",
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            lines: [(0, 0); VIEWER_MAX_LINES],
+            count: 0,
+            top: 0,
+        };
+        viewer.set_text(text);
+        viewer
+    }
+
+    /// Replaces the text shown, wrapping it again and scrolling to the top.
+    pub fn set_text(&mut self, text: &'t [u8]) {
+        let text = &text[..usize::min(text.len(), usize::from(u16::MAX))];
+        let width = self.text_w();
+        let lines = &mut self.lines;
+        let mut count = 0;
+        paragraph_lines(text, width, |range| {
+            if count < VIEWER_MAX_LINES {
+                lines[count] = (range.start as u16, range.end as u16);
+                count += 1;
+            }
+        });
+        self.text = text;
+        self.count = count;
+        self.top = 0;
+    }
+
+    /// Returns the number of rows the text takes.
+    pub fn lines(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the index of the row at the top of the window.
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Returns the index of the top row when scrolled all the way down.
+    pub fn max_top(&self) -> usize {
+        self.count.saturating_sub(self.rect.h)
+    }
+
+    /// Scrolls down `lines` rows, or up if negative, stopping at either end.
+    /// Returns whether the view moved and so needs a `draw`.
+    pub fn scroll_lines(&mut self, lines: i32) -> bool {
+        let top = if lines < 0 {
+            self.top.saturating_sub(-i64::from(lines) as usize)
+        } else {
+            usize::min(self.top + lines as usize, self.max_top())
+        };
+        let moved = top != self.top;
+        self.top = top;
+        moved
+    }
+
+    /// Scrolls down `pages` windowfuls, or up if negative, like
+    /// `scroll_lines`.
+    pub fn page(&mut self, pages: i32) -> bool {
+        self.scroll_lines(pages.saturating_mul(self.rect.h as i32))
+    }
+
+    /// Draws the visible rows and the scrollbar. The scrollbar is left blank
+    /// if the text fits without scrolling.
+    pub fn draw(&self, c: &mut Cursor) {
+        let r = self.rect;
+        let mut w = c.window(r.row, r.col, r.h, r.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        let text_w = self.text_w();
+        let visible = self.lines[self.top..self.count].iter().take(r.h);
+        for (row, &(start, end)) in visible.enumerate() {
+            let line = &self.text[usize::from(start)..usize::from(end)];
+            w.put_paragraph(Rect::new(row, 0, 1, text_w), line);
+        }
+
+        if self.count <= r.h || r.w == 0 {
+            return;
+        }
+        // The thumb is as tall a share of the bar as the window is of the
+        // text, and sits as far down it as the window is.
+        let col = r.w - 1;
+        let thumb = usize::max(r.h * r.h / self.count, 1);
+        let at = self.top * (r.h - thumb) / self.max_top();
+        w.vline(0, col, r.h, LIGHT_SHADE);
+        w.vline(at, col, thumb, FULL_BLOCK);
+    }
+
+    /// Returns the width the text is wrapped to, leaving the last column for
+    /// the scrollbar.
+    fn text_w(&self) -> usize {
+        self.rect.w.saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 8;
+    const ROWS: usize = 3;
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        FULL_BLOCK => '#',
+                        LIGHT_SHADE => ':',
+                        ch => ch as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scrolls_within_bounds_and_keeps_paragraph_breaks() {
+        let mut viewer = TextViewer::new(
+            b"one two three\n\nfour five six seven",
+            Rect::new(0, 0, ROWS, COLS),
+        );
+        assert_eq!(viewer.lines(), 7);
+        assert!(!viewer.scroll_lines(-1));
+        assert!(viewer.scroll_lines(1));
+        assert!(viewer.page(1));
+        assert_eq!(viewer.top(), 4);
+        assert!(!viewer.page(1));
+
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        viewer.draw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(text(&buf), ["five   :", "six    :", "seven  #"]);
+
+        assert!(viewer.scroll_lines(-2));
+        viewer.draw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(text(&buf), ["       :", "four   #", "five   :"]);
+
+        assert!(viewer.page(-9));
+        assert_eq!(viewer.top(), 0);
+    }
+
+    #[test]
+    fn short_text_has_no_scrollbar() {
+        let mut viewer = TextViewer::new(b"hi", Rect::new(0, 0, ROWS, COLS));
+        assert!(!viewer.scroll_lines(5));
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        viewer.draw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(text(&buf), ["hi      ", "        ", "        "]);
+    }
+}
@@ -0,0 +1,2261 @@
+//! Reusable pieces of screen layout.
+//!
+//! A widget is a plain struct describing something to draw, such as a message
+//! box, with no storage of its own beyond the borrowed text it shows. That
+//! keeps widgets usable from `static`s. Where a widget goes is decided by the
+//! screen drawing it, which passes in the area for it to fill.
+
+use crate::banner::FULL_BLOCK;
+use crate::chart::{bar_fill, LIGHT_SHADE};
+use crate::chrome::{draw_title_bar, HEADER_ROWS, TITLE_ROWS};
+use crate::color::fill_gradient;
+use crate::cursor::{BoxStyle, Cursor, Rect};
+use crate::list::digits;
+
+/// Something that can draw itself into an area of the screen.
+pub trait Widget {
+    /// Draws the widget filling `area`. Anything outside `area` is left
+    /// alone, except where a widget documents otherwise (such as a shadow).
+    /// The cursor's position and colors are left as they were.
+    fn draw(&self, c: &mut Cursor, area: Rect);
+
+    /// Returns the smallest (rows, columns) the widget can be drawn in
+    /// without cutting anything off.
+    fn min_size(&self) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+/// Widget that can hold the input focus, for screens that step through
+/// several controls with the buttons.
+pub trait Focusable: Widget {
+    fn set_focused(&mut self, focused: bool);
+    fn is_focused(&self) -> bool;
+
+    /// Reacts to a button press while the widget has the focus. Returns
+    /// whether the widget changed and so needs drawing again. By default
+    /// widgets ignore presses.
+    fn on_event(&mut self, _event: MenuEvent) -> bool {
+        false
+    }
+}
+
+/// Moves the focus from whichever of `widgets` has it to the next one, or
+/// the previous one if `forward` is false, wrapping around at the ends. If
+/// none has the focus, the first (or last) one gets it. Returns the index of
+/// the widget with the focus, or `None` if `widgets` is empty.
+///
+/// The caller redraws the two widgets whose focus changed.
+pub fn move_focus(
+    widgets: &mut [&mut dyn Focusable],
+    forward: bool,
+) -> Option<usize> {
+    let n = widgets.len();
+    if n == 0 {
+        return None;
+    }
+    let next = match widgets.iter().position(|w| w.is_focused()) {
+        Some(i) if forward => (i + 1) % n,
+        Some(i) => (i + n - 1) % n,
+        None if forward => 0,
+        None => n - 1,
+    };
+    for (i, w) in widgets.iter_mut().enumerate() {
+        w.set_focused(i == next);
+    }
+    Some(next)
+}
+
+/// Most widgets a `FocusManager` can hold.
+pub const FOCUS_CAP: usize = 8;
+
+/// Keeps track of which of a screen's widgets has the focus, and passes
+/// button presses to it.
+///
+/// Widgets are held by reference, in the order the focus goes through them,
+/// each with the area it is drawn in, so the manager can draw them too. The
+/// first widget added gets the focus. Widgets are told when they gain or
+/// lose the focus through `Focusable::set_focused`, so that they can change
+/// how they look; the caller then draws them again.
+pub struct FocusManager<'w> {
+    widgets: [Option<&'w mut dyn Focusable>; FOCUS_CAP],
+    areas: [Rect; FOCUS_CAP],
+    len: usize,
+    focus: Option<usize>,
+}
+
+impl<'w> FocusManager<'w> {
+    pub fn new() -> Self {
+        FocusManager {
+            widgets: Default::default(),
+            areas: [Rect::new(0, 0, 0, 0); FOCUS_CAP],
+            len: 0,
+            focus: None,
+        }
+    }
+
+    /// Adds `widget`, drawn in `area`, after the others. Returns false, and
+    /// leaves the widget out, if there are already `FOCUS_CAP` of them.
+    pub fn push(&mut self, widget: &'w mut dyn Focusable, area: Rect) -> bool {
+        if self.len == FOCUS_CAP {
+            return false;
+        }
+        widget.set_focused(self.focus.is_none());
+        if self.focus.is_none() {
+            self.focus = Some(self.len);
+        }
+        self.widgets[self.len] = Some(widget);
+        self.areas[self.len] = area;
+        self.len += 1;
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the index of the widget with the focus, counting in the order
+    /// they were added.
+    pub fn focused(&self) -> Option<usize> {
+        self.focus
+    }
+
+    /// Gives the focus to widget `index`, if there is one. Returns whether
+    /// the focus moved.
+    pub fn focus(&mut self, index: usize) -> bool {
+        if index >= self.len || self.focus == Some(index) {
+            return false;
+        }
+        if let Some(old) = self.focus {
+            self.set_focused(old, false);
+        }
+        self.set_focused(index, true);
+        self.focus = Some(index);
+        true
+    }
+
+    /// Moves the focus to the next widget, wrapping around to the first, and
+    /// returns its index.
+    pub fn focus_next(&mut self) -> Option<usize> {
+        let next = match self.focus {
+            Some(i) => (i + 1) % usize::max(self.len, 1),
+            None => 0,
+        };
+        self.focus(next);
+        self.focus
+    }
+
+    /// Moves the focus to the previous widget, wrapping around to the last,
+    /// and returns its index.
+    pub fn focus_prev(&mut self) -> Option<usize> {
+        let prev = match self.focus {
+            Some(i) if i > 0 => i - 1,
+            _ => self.len.saturating_sub(1),
+        };
+        self.focus(prev);
+        self.focus
+    }
+
+    /// Passes `event` to the widget with the focus. Returns whether it
+    /// changed and so needs drawing again.
+    pub fn handle(&mut self, event: MenuEvent) -> bool {
+        match self.focus.and_then(|i| self.widgets[i].as_mut()) {
+            Some(widget) => widget.on_event(event),
+            None => false,
+        }
+    }
+
+    /// Draws all the widgets in their areas.
+    pub fn draw(&self, c: &mut Cursor) {
+        let widgets = self.widgets.iter().zip(self.areas.iter());
+        for (widget, &area) in widgets.take(self.len) {
+            if let Some(widget) = widget {
+                widget.draw(c, area);
+            }
+        }
+    }
+
+    fn set_focused(&mut self, index: usize, focused: bool) {
+        if let Some(widget) = self.widgets[index].as_mut() {
+            widget.set_focused(focused);
+        }
+    }
+}
+
+impl<'w> Default for FocusManager<'w> {
+    fn default() -> Self {
+        FocusManager::new()
+    }
+}
+
+/// Width of the longest of `lines`.
+fn widest(lines: &[&[u8]]) -> usize {
+    lines.iter().map(|line| line.len()).max().unwrap_or(0)
+}
+
+/// Title bar drawn by `chrome::draw_title_bar`, in an area of its own.
+#[derive(Copy, Clone, Debug)]
+pub struct TitleBar<'t> {
+    pub title: &'t [u8],
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+}
+
+impl<'t> Widget for TitleBar<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        draw_title_bar(&mut w, self.title, self.bg, self.fg);
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (TITLE_ROWS, self.title.len())
+    }
+}
+
+/// Colors of a `Header`.
+#[derive(Copy, Clone, Debug)]
+pub struct HeaderStyle {
+    pub fg: m4vga::Pixel,
+    /// Color the background fades from, at the left edge, to black.
+    pub bg: m4vga::Pixel,
+    /// Foreground of the subtitle, usually dimmer than `fg`.
+    pub subtitle_fg: m4vga::Pixel,
+}
+
+/// Title bar like `TitleBar`, with an optional subtitle centered on the row
+/// under the title.
+///
+/// With a subtitle the header is `HEADER_ROWS` tall, rather than
+/// `TITLE_ROWS`; frame the screen with `chrome::draw_screen_border_under`
+/// and `rows` so that the divider goes under the subtitle, not through it.
+#[derive(Copy, Clone, Debug)]
+pub struct Header<'t> {
+    pub title: &'t [u8],
+    pub subtitle: Option<&'t [u8]>,
+    pub style: HeaderStyle,
+}
+
+impl<'t> Header<'t> {
+    /// Returns the number of rows the header takes at the top of the
+    /// screen, counting the row the border's divider goes on.
+    pub fn rows(&self) -> usize {
+        if self.subtitle.is_some() {
+            HEADER_ROWS
+        } else {
+            TITLE_ROWS
+        }
+    }
+}
+
+impl<'t> Widget for Header<'t> {
+    /// Draws the header in the top `rows` rows of `area`.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let style = self.style;
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        draw_title_bar(&mut w, self.title, style.bg, style.fg);
+        if let Some(subtitle) = self.subtitle {
+            let rows = HEADER_ROWS - TITLE_ROWS;
+            let below = Rect::new(TITLE_ROWS, 0, rows, area.w);
+            fill_gradient(&mut w, below, style.bg, 0);
+            let mut w = w.styled(style.subtitle_fg, style.bg);
+            w.bg_transparent = true;
+            w.puts_centered(TITLE_ROWS - 1, subtitle);
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        let subtitle = self.subtitle.map_or(0, |s| s.len());
+        (self.rows(), usize::max(self.title.len(), subtitle))
+    }
+}
+
+/// Filled box with lines of text centered in it, optionally casting a shadow.
+#[derive(Copy, Clone, Debug)]
+pub struct MessageBox<'t> {
+    pub lines: &'t [&'t [u8]],
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Whether to shade the cells below and to the right of the box, as by
+    /// `Cursor::draw_shadow`. These are outside the box's area.
+    pub shadow: bool,
+}
+
+impl<'t> Widget for MessageBox<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        if self.shadow {
+            c.draw_shadow(area);
+        }
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        let top = area.h.saturating_sub(self.lines.len()) / 2;
+        for (i, line) in self.lines.iter().enumerate() {
+            w.puts_centered(top + i, line);
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (self.lines.len(), widest(self.lines))
+    }
+}
+
+/// Framed box with a label centered inside, like the option buttons at the
+/// edges of the kiosk screens.
+#[derive(Copy, Clone, Debug)]
+pub struct OptionBox<'t> {
+    pub label: &'t [u8],
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub style: &'t BoxStyle,
+}
+
+impl<'t> Widget for OptionBox<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        w.draw_box(0, 0, area.h, area.w, self.style);
+        let row = area.h.saturating_sub(1) / 2;
+        let mut inside = w.window(row, 1, 1, area.w.saturating_sub(2));
+        inside.puts_centered(0, self.label);
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (3, self.label.len() + 2)
+    }
+}
+
+/// What a `Button` looks like at the moment.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ButtonState {
+    Normal,
+    /// Selected, and what a press would act on.
+    Focused,
+    /// Being pressed right now.
+    Pressed,
+}
+
+/// Colors for each `ButtonState`, as (foreground, background) pairs, and the
+/// frame drawn around the label.
+#[derive(Copy, Clone, Debug)]
+pub struct ButtonStyle {
+    pub normal: (m4vga::Pixel, m4vga::Pixel),
+    pub focused: (m4vga::Pixel, m4vga::Pixel),
+    pub pressed: (m4vga::Pixel, m4vga::Pixel),
+    pub frame: BoxStyle,
+}
+
+/// Framed, labeled button that knows where it goes and how it looks in each
+/// state.
+///
+/// Drawn through `Widget::draw` it fills whatever area it is given, like an
+/// `OptionBox`; `redraw` puts it back at its own `rect`, which is what to do
+/// after `set_state`.
+#[derive(Copy, Clone, Debug)]
+pub struct Button<'t> {
+    pub label: &'t [u8],
+    pub rect: Rect,
+    pub style: &'t ButtonStyle,
+    pub state: ButtonState,
+}
+
+impl<'t> Button<'t> {
+    /// Makes a button in the `Normal` state.
+    pub const fn new(
+        label: &'t [u8],
+        rect: Rect,
+        style: &'t ButtonStyle,
+    ) -> Self {
+        Button {
+            label,
+            rect,
+            style,
+            state: ButtonState::Normal,
+        }
+    }
+
+    /// Changes the button's state, returning whether it actually changed and
+    /// so needs a `redraw`.
+    pub fn set_state(&mut self, state: ButtonState) -> bool {
+        let changed = self.state != state;
+        self.state = state;
+        changed
+    }
+
+    /// Draws the button at its own `rect`. This only touches the button's
+    /// cells, so it is cheap enough to do every frame, for instance to flash
+    /// a press.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'t> Widget for Button<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let (fg, bg) = match self.state {
+            ButtonState::Normal => self.style.normal,
+            ButtonState::Focused => self.style.focused,
+            ButtonState::Pressed => self.style.pressed,
+        };
+        let option = OptionBox {
+            label: self.label,
+            fg,
+            bg,
+            style: &self.style.frame,
+        };
+        option.draw(c, area)
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (3, self.label.len() + 2)
+    }
+}
+
+impl<'t> Focusable for Button<'t> {
+    /// Focuses or unfocuses the button. A pressed button stays pressed.
+    fn set_focused(&mut self, focused: bool) {
+        if self.state != ButtonState::Pressed {
+            self.state = if focused {
+                ButtonState::Focused
+            } else {
+                ButtonState::Normal
+            };
+        }
+    }
+
+    fn is_focused(&self) -> bool {
+        self.state != ButtonState::Normal
+    }
+
+    /// Presses the button for `Select`.
+    fn on_event(&mut self, event: MenuEvent) -> bool {
+        event == MenuEvent::Select && self.set_state(ButtonState::Pressed)
+    }
+}
+
+/// Where a `ProgressBar` shows its percentage, if anywhere.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Percent {
+    Hidden,
+    /// After the bar, right-aligned in the last `PERCENT_W` cells.
+    After,
+    /// Over the middle of the bar, in inverted colors where it covers the
+    /// filled part so that it can be read on either side of the edge.
+    Overlay,
+}
+
+/// Cells used by `Percent::After`: a blank and up to `100%`.
+pub const PERCENT_W: usize = 5;
+
+/// Bar showing how far along something is, as in `[#####....]  55%`.
+///
+/// Drawn on the middle row of its area, with the other rows blanked. Like a
+/// `Button`, it knows its own `rect`, so it can be advanced and redrawn every
+/// frame with `redraw`.
+#[derive(Copy, Clone, Debug)]
+pub struct ProgressBar {
+    pub rect: Rect,
+    pub value: u32,
+    pub max: u32,
+    /// Glyphs for the filled and empty parts of the bar.
+    pub fill: u8,
+    pub empty: u8,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub percent: Percent,
+}
+
+impl ProgressBar {
+    /// Returns how far along the bar is, from 0 to 100, rounded down so that
+    /// 100% is only shown once `value` reaches `max`. A `max` of zero counts
+    /// as 0%.
+    pub fn percent(&self) -> u32 {
+        if self.max == 0 {
+            return 0;
+        }
+        let value = u64::from(u32::min(self.value, self.max));
+        (value * 100 / u64::from(self.max)) as u32
+    }
+
+    /// Draws the bar at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl Widget for ProgressBar {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        if area.h == 0 || area.w == 0 {
+            return;
+        }
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        let row = (area.h - 1) / 2;
+
+        let mut digits = [0; 4];
+        let text = percent_text(self.percent(), &mut digits);
+        let bar_w = match self.percent {
+            Percent::After => area.w.saturating_sub(PERCENT_W),
+            _ => area.w,
+        };
+        if self.percent == Percent::After {
+            w.puts_right(row, area.w - 1, text);
+        }
+
+        // Brackets first, each only if there's room for it.
+        w.hline(row, 0, usize::min(bar_w, 1), b'[');
+        if bar_w >= 2 {
+            w.hline(row, bar_w - 1, 1, b']');
+        }
+        let inner = bar_w.saturating_sub(2);
+        let filled = bar_fill(inner, self.value, self.max);
+        w.hline(row, 1, filled, self.fill);
+        w.hline(row, 1 + filled, inner - filled, self.empty);
+
+        if self.percent == Percent::Overlay && text.len() <= inner {
+            let start = (inner - text.len()) / 2;
+            for (i, &ch) in text.iter().enumerate() {
+                let col = 1 + start + i;
+                if start + i < filled {
+                    w.styled(self.bg, self.fg).hline(row, col, 1, ch);
+                } else {
+                    w.hline(row, col, 1, ch);
+                }
+            }
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        match self.percent {
+            Percent::After => (1, 2 + PERCENT_W),
+            _ => (1, 2),
+        }
+    }
+}
+
+/// Writes `percent` followed by `%` into `buf`, returning the part used.
+fn percent_text(percent: u32, buf: &mut [u8; 4]) -> &[u8] {
+    let mut value = u32::min(percent, 100);
+    let mut start = 3;
+    buf[3] = b'%';
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    &buf[start..]
+}
+
+/// Spinner frames in plain ASCII.
+pub const SPINNER_ASCII: [u8; 4] = *b"|/-\\";
+/// Spinner frames made of CP437 half blocks going clockwise: upper, right,
+/// lower, left.
+pub const SPINNER_BLOCKS: [u8; 4] = [0xDF, 0xDE, 0xDC, 0xDD];
+
+/// One-cell activity indicator for waits of unknown length, with an optional
+/// label after it, as in `/ Processing...`.
+///
+/// The spinner keeps no time of its own: the main loop passes in its frame
+/// count with `tick`, and `redraw`s when that moves it on.
+#[derive(Copy, Clone, Debug)]
+pub struct Spinner<'t> {
+    pub rect: Rect,
+    /// Glyphs to cycle through.
+    pub frames: &'t [u8],
+    /// Number of ticks each glyph is shown for. Zero counts as one.
+    pub divisor: u32,
+    /// Text drawn after the spinner and a blank, in the rest of the row.
+    pub label: &'t [u8],
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Index in `frames` of the glyph being shown.
+    pub frame: usize,
+}
+
+impl<'t> Spinner<'t> {
+    /// Makes a spinner showing the first of `frames`, in white on black.
+    pub const fn new(
+        rect: Rect,
+        frames: &'t [u8],
+        divisor: u32,
+        label: &'t [u8],
+    ) -> Self {
+        Spinner {
+            rect,
+            frames,
+            divisor,
+            label,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            frame: 0,
+        }
+    }
+
+    /// Picks the glyph for frame `frame_no` of the main loop, returning
+    /// whether it changed and so needs a `redraw`.
+    pub fn tick(&mut self, frame_no: u32) -> bool {
+        if self.frames.is_empty() {
+            return false;
+        }
+        let step = frame_no / u32::max(self.divisor, 1);
+        let frame = step as usize % self.frames.len();
+        let changed = frame != self.frame;
+        self.frame = frame;
+        changed
+    }
+
+    /// Draws the spinner and its label at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'t> Widget for Spinner<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        let glyph = self.frames.get(self.frame).cloned().unwrap_or(b' ');
+        w.hline(0, 0, 1, glyph);
+        w.hline(0, 1, area.w.saturating_sub(1), b' ');
+        w.set_scroll(false);
+        w.goto(0, 2);
+        w.puts_truncated(self.label, area.w.saturating_sub(2));
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        if self.label.is_empty() {
+            (1, 1)
+        } else {
+            (1, self.label.len() + 2)
+        }
+    }
+}
+
+/// One row of text that scrolls to the left, for messages too long to fit.
+///
+/// The text runs past the window followed by `gap` blanks, then repeats.
+/// Text that fits in the window is drawn as is and never moves. Like the
+/// `Spinner`, the marquee is driven by the main loop: call `tick` once a
+/// frame and `redraw` when it says to.
+#[derive(Copy, Clone, Debug)]
+pub struct Marquee<'t> {
+    pub text: &'t [u8],
+    /// Area to scroll through. Only its top row is used.
+    pub rect: Rect,
+    /// Blanks between the end of the text and its next repeat.
+    pub gap: usize,
+    /// Ticks spent on each step. Zero counts as one.
+    pub frames_per_cell: u32,
+    /// Extra ticks spent with the start of the text at the left edge, to give
+    /// readers a chance to catch it.
+    pub pause: u32,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Index in the text, plus gap, shown at the left edge.
+    pub offset: usize,
+    /// Ticks since the last step.
+    ticks: u32,
+}
+
+impl<'t> Marquee<'t> {
+    /// Makes a marquee showing the start of `text`, in white on black.
+    pub const fn new(
+        text: &'t [u8],
+        rect: Rect,
+        gap: usize,
+        frames_per_cell: u32,
+        pause: u32,
+    ) -> Self {
+        Marquee {
+            text,
+            rect,
+            gap,
+            frames_per_cell,
+            pause,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            offset: 0,
+            ticks: 0,
+        }
+    }
+
+    /// Returns whether the text is too long to fit and so scrolls.
+    pub fn scrolls(&self) -> bool {
+        self.text.len() > self.rect.w
+    }
+
+    /// Advances the marquee by a frame, returning whether the text moved and
+    /// so needs a `redraw`.
+    pub fn tick(&mut self) -> bool {
+        if !self.scrolls() {
+            return false;
+        }
+        self.ticks += 1;
+        let mut wait = u32::max(self.frames_per_cell, 1);
+        if self.offset == 0 {
+            wait = wait.saturating_add(self.pause);
+        }
+        if self.ticks < wait {
+            return false;
+        }
+        self.ticks = 0;
+        self.offset = (self.offset + 1) % (self.text.len() + self.gap);
+        true
+    }
+
+    /// Goes back to showing the start of the text, pause and all.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+        self.ticks = 0;
+    }
+
+    /// Draws the marquee at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'t> Widget for Marquee<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, 1, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        if self.text.len() <= area.w {
+            w.puts_at(0, 0, self.text).ok();
+            w.hline(0, self.text.len(), area.w - self.text.len(), b' ');
+            return;
+        }
+        let period = self.text.len() + self.gap;
+        for i in 0..area.w {
+            let index = (self.offset + i) % period;
+            let ch = self.text.get(index).cloned().unwrap_or(b' ');
+            w.hline(0, i, 1, ch);
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, 1)
+    }
+}
+
+/// What a `Blink` shows in the part of its period the inner widget is off.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BlinkOff {
+    /// Blanks the area in this background color.
+    Blank(m4vga::Pixel),
+    /// Draws the inner widget with this foreground color instead.
+    Dim(m4vga::Pixel),
+}
+
+/// Wrapper that blinks another widget, to draw the eye to it.
+///
+/// Like the `Spinner`, the blink is driven by the main loop, which passes in
+/// its frame count with `tick` and `redraw`s when that says to. Both phases
+/// cover the whole area, so switching between them leaves nothing behind.
+/// Anything the inner widget draws outside its area, such as a
+/// `MessageBox`'s shadow, is drawn again on every change; leave that to be
+/// drawn once, with the screen.
+#[derive(Copy, Clone)]
+pub struct Blink<'w> {
+    pub inner: &'w dyn Widget,
+    pub rect: Rect,
+    /// Number of frames in a full on and off cycle. Zero counts as one.
+    pub period: u32,
+    /// Percentage of the period the inner widget is shown for.
+    pub duty: u32,
+    pub off: BlinkOff,
+    /// Whether the inner widget is in its on phase.
+    visible: bool,
+}
+
+impl<'w> Blink<'w> {
+    /// Makes a blink showing `inner`, on for half of each `period` frames
+    /// and blanked to black for the rest.
+    pub const fn new(inner: &'w dyn Widget, rect: Rect, period: u32) -> Self {
+        Blink {
+            inner,
+            rect,
+            period,
+            duty: 50,
+            off: BlinkOff::Blank(crate::cursor::DEFAULT_BG),
+            visible: true,
+        }
+    }
+
+    /// Picks the phase for frame `frame_no` of the main loop, returning
+    /// whether it changed and so needs a `redraw`.
+    pub fn tick(&mut self, frame_no: u32) -> bool {
+        let period = u64::from(u32::max(self.period, 1));
+        let phase = u64::from(frame_no) % period;
+        let visible = phase * 100 < period * u64::from(self.duty);
+        let changed = visible != self.visible;
+        self.visible = visible;
+        changed
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Draws the current phase at the blink's own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'w> Widget for Blink<'w> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        if self.visible {
+            self.inner.draw(c, area);
+            return;
+        }
+        match self.off {
+            BlinkOff::Blank(bg) => {
+                let mut c = c.styled(c.fg, bg);
+                c.fill_rect(area.row, area.col, area.h, area.w, b' ');
+            }
+            BlinkOff::Dim(fg) => {
+                self.inner.draw(c, area);
+                c.recolor_region(area, Some(fg), None);
+            }
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        self.inner.min_size()
+    }
+}
+
+/// CP437 glyphs marking that a `Menu` has more items above or below (`▲`,
+/// `▼`).
+pub const MORE_ABOVE: u8 = 0x1E;
+pub const MORE_BELOW: u8 = 0x1F;
+
+/// Input to a `Menu`, or another widget worked with the buttons.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MenuEvent {
+    Up,
+    Down,
+    Select,
+    /// Only meaningful to widgets laid out in two dimensions, such as a
+    /// `Keypad`; others ignore `Left` and `Right`.
+    Left,
+    Right,
+}
+
+/// Vertical list of choices, one per row, with one of them selected.
+///
+/// The selected item is drawn with the colors swapped and a `>` in front.
+/// If there are more items than rows, the menu scrolls to keep the selection
+/// in view, and marks the rightmost column with `MORE_ABOVE` and
+/// `MORE_BELOW` where there are items out of sight.
+#[derive(Copy, Clone, Debug)]
+pub struct Menu<'t> {
+    pub items: &'t [&'t [u8]],
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Index of the selected item.
+    pub selected: usize,
+    /// Index of the item on the top row.
+    top: usize,
+}
+
+impl<'t> Menu<'t> {
+    /// Makes a menu with the first item selected, in white on black.
+    pub const fn new(items: &'t [&'t [u8]], rect: Rect) -> Self {
+        Menu {
+            items,
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            selected: 0,
+            top: 0,
+        }
+    }
+
+    /// Moves the selection for `Up` and `Down`, wrapping around at either
+    /// end. Returns the index of the selected item for `Select`, and `None`
+    /// otherwise.
+    pub fn handle(&mut self, event: MenuEvent) -> Option<usize> {
+        let n = self.items.len();
+        if n == 0 {
+            return None;
+        }
+        match event {
+            MenuEvent::Up => self.selected = (self.selected + n - 1) % n,
+            MenuEvent::Down => self.selected = (self.selected + 1) % n,
+            MenuEvent::Select => return Some(self.selected),
+            MenuEvent::Left | MenuEvent::Right => return None,
+        }
+        self.top = self.top_for(self.rect.h);
+        None
+    }
+
+    /// Draws the menu at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+
+    /// Returns the index of the item to put on the top row of `rows` rows,
+    /// scrolling as little as possible from where the menu is to bring the
+    /// selection into view.
+    fn top_for(&self, rows: usize) -> usize {
+        if self.selected < self.top {
+            self.selected
+        } else if rows > 0 && self.selected >= self.top + rows {
+            self.selected + 1 - rows
+        } else {
+            self.top
+        }
+    }
+}
+
+impl<'t> Widget for Menu<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        let top = self.top_for(area.h);
+        let scrolls = self.items.len() > area.h;
+        // Room for the marker and a blank before, and the scroll column
+        // after, each label.
+        let label_w = area.w.saturating_sub(2 + scrolls as usize);
+
+        for row in 0..area.h {
+            let index = top + row;
+            let item = match self.items.get(index) {
+                Some(item) => item,
+                None => break,
+            };
+            let line_w = area.w.saturating_sub(scrolls as usize);
+            let mut line = w.window(row, 0, 1, line_w);
+            if index == self.selected {
+                line.fg = self.bg;
+                line.bg = self.fg;
+                line.fill_row(0, b' ');
+                line.hline(0, 0, 1, b'>');
+            }
+            let mut line = line.window(0, 2, 1, label_w);
+            line.set_scroll(false);
+            line.puts_truncated(item, label_w);
+        }
+        if scrolls && area.h > 0 {
+            let col = area.w.saturating_sub(1);
+            if top > 0 {
+                w.hline(0, col, 1, MORE_ABOVE);
+            }
+            if top + area.h < self.items.len() {
+                w.hline(area.h - 1, col, 1, MORE_BELOW);
+            }
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (self.items.len(), widest(self.items) + 2)
+    }
+}
+
+/// Items split into pages of a fixed size, with one page showing at a time.
+///
+/// Drawn as a `Widget`, the current page's items go one per row, with the
+/// page indicator, as in `Page 2/3`, right-aligned on the bottom row. Screens
+/// that lay the items out themselves can use `page_items` and
+/// `draw_indicator` instead.
+#[derive(Copy, Clone, Debug)]
+pub struct PagedList<'t> {
+    pub items: &'t [&'t [u8]],
+    /// Number of items on a page. Zero counts as one.
+    pub page_size: usize,
+    /// Word before the numbers in the page indicator.
+    pub label: &'t [u8],
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    /// Index of the page showing, counting from 0.
+    page: usize,
+}
+
+impl<'t> PagedList<'t> {
+    /// Makes a list showing its first page, in white on black.
+    pub const fn new(
+        items: &'t [&'t [u8]],
+        page_size: usize,
+        rect: Rect,
+    ) -> Self {
+        PagedList {
+            items,
+            page_size,
+            label: b"Page",
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            page: 0,
+        }
+    }
+
+    /// Returns the number of pages. An empty list still has one, empty,
+    /// page.
+    pub fn pages(&self) -> usize {
+        let size = usize::max(self.page_size, 1);
+        self.items.len().saturating_sub(1) / size + 1
+    }
+
+    /// Returns the index of the page showing.
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Shows page `page`, or the last page if there aren't that many.
+    pub fn set_page(&mut self, page: usize) {
+        self.page = usize::min(page, self.pages() - 1);
+    }
+
+    /// Moves on a page, unless on the last one. Returns whether the page
+    /// changed.
+    pub fn next_page(&mut self) -> bool {
+        let before = self.page;
+        self.set_page(before + 1);
+        self.page != before
+    }
+
+    /// Moves back a page, unless on the first one. Returns whether the page
+    /// changed.
+    pub fn prev_page(&mut self) -> bool {
+        let before = self.page;
+        self.page = before.saturating_sub(1);
+        self.page != before
+    }
+
+    /// Returns the items on the page showing.
+    pub fn page_items(&self) -> &'t [&'t [u8]] {
+        let size = usize::max(self.page_size, 1);
+        let start = usize::min(self.page * size, self.items.len());
+        let end = usize::min(start + size, self.items.len());
+        &self.items[start..end]
+    }
+
+    /// Draws the page indicator, as in `Page 2/3` with `label` first, in
+    /// the current colors, with its last character at `row`, `right_col`.
+    pub fn draw_indicator(&self, c: &mut Cursor, row: usize, right_col: usize) {
+        let (page, pages) = (self.page + 1, self.pages());
+        write_right!(c, row, right_col, " {}/{}", page, pages).ok();
+        let numbers = 1 + digits(page) + 1 + digits(pages);
+        if let Some(label_col) = right_col.checked_sub(numbers) {
+            c.puts_right(row, label_col, self.label);
+        }
+    }
+
+    /// Draws the list at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'t> Widget for PagedList<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        if area.h == 0 || area.w == 0 {
+            return;
+        }
+        let rows = area.h - 1;
+        for (row, item) in self.page_items().iter().take(rows).enumerate() {
+            let mut line = w.window(row, 0, 1, area.w);
+            line.set_scroll(false);
+            line.puts_truncated(item, area.w);
+        }
+        self.draw_indicator(&mut w, rows, area.w - 1);
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        let rows = usize::min(self.items.len(), usize::max(self.page_size, 1));
+        let indicator = self.label.len() + b" 1/1".len();
+        (rows + 1, usize::max(widest(self.items), indicator))
+    }
+}
+
+/// On/off option drawn as `[X] Label` or `[ ] Label`, with the row in
+/// inverted colors while it has the focus.
+#[derive(Copy, Clone, Debug)]
+pub struct Checkbox<'t> {
+    pub label: &'t [u8],
+    pub checked: bool,
+    pub focused: bool,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+}
+
+impl<'t> Checkbox<'t> {
+    /// Makes an unfocused checkbox in white on black.
+    pub const fn new(label: &'t [u8], checked: bool) -> Self {
+        Checkbox {
+            label,
+            checked,
+            focused: false,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+        }
+    }
+
+    /// Flips the option, returning its new value.
+    pub fn toggle(&mut self) -> bool {
+        self.checked = !self.checked;
+        self.checked
+    }
+}
+
+impl<'t> Widget for Checkbox<'t> {
+    /// Draws the checkbox on the top row of `area`.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, 1, area.w);
+        if self.focused {
+            w.fg = self.bg;
+            w.bg = self.fg;
+        } else {
+            w.fg = self.fg;
+            w.bg = self.bg;
+        }
+        w.clear();
+        w.set_scroll(false);
+        w.puts(if self.checked { b"[X] " } else { b"[ ] " });
+        w.puts_truncated(self.label, area.w.saturating_sub(4));
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, self.label.len() + 4)
+    }
+}
+
+impl<'t> Focusable for Checkbox<'t> {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+    /// Flips the option for `Select`.
+    fn on_event(&mut self, event: MenuEvent) -> bool {
+        if event == MenuEvent::Select {
+            self.toggle();
+        }
+        event == MenuEvent::Select
+    }
+}
+
+/// Cells taken by the `ON`/`OFF` pill of a `Toggle`.
+pub const PILL_W: usize = 5;
+
+/// On/off option drawn as its label with an `ON` or `OFF` pill at the right
+/// end of its area, the pill in `on_bg` or `off_bg`. The label is in inverted
+/// colors while the toggle has the focus.
+#[derive(Copy, Clone, Debug)]
+pub struct Toggle<'t> {
+    pub label: &'t [u8],
+    pub on: bool,
+    pub focused: bool,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub on_bg: m4vga::Pixel,
+    pub off_bg: m4vga::Pixel,
+}
+
+impl<'t> Toggle<'t> {
+    /// Makes an unfocused toggle in white on black, with a green pill when
+    /// on and a red one when off.
+    pub const fn new(label: &'t [u8], on: bool) -> Self {
+        Toggle {
+            label,
+            on,
+            focused: false,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            on_bg: 0b00_10_00,
+            off_bg: 0b00_00_10,
+        }
+    }
+
+    /// Flips the option, returning its new value.
+    pub fn toggle(&mut self) -> bool {
+        self.on = !self.on;
+        self.on
+    }
+}
+
+impl<'t> Widget for Toggle<'t> {
+    /// Draws the toggle on the top row of `area`.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, 1, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        let label_w = area.w.saturating_sub(PILL_W + 1);
+        {
+            let mut label = w.window(0, 0, 1, label_w);
+            if self.focused {
+                label.fg = self.bg;
+                label.bg = self.fg;
+                label.clear();
+            }
+            label.set_scroll(false);
+            label.puts_truncated(self.label, label_w);
+        }
+        let col = area.w.saturating_sub(PILL_W);
+        let mut pill = w.window(0, col, 1, PILL_W);
+        pill.bg = if self.on { self.on_bg } else { self.off_bg };
+        pill.clear();
+        pill.puts_centered(0, if self.on { b"ON" } else { b"OFF" });
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, self.label.len() + 1 + PILL_W)
+    }
+}
+
+impl<'t> Focusable for Toggle<'t> {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+    /// Flips the option for `Select`.
+    fn on_event(&mut self, event: MenuEvent) -> bool {
+        if event == MenuEvent::Select {
+            self.toggle();
+        }
+        event == MenuEvent::Select
+    }
+}
+
+/// Pick-one-of-N control drawn as `(•) Label` rows, with the selected item's
+/// circle filled in.
+///
+/// The row with the focus, which `Up` and `Down` move, is drawn with the
+/// colors swapped while the group itself has the focus; `Select` makes it the
+/// selected item. Items whose bit is set in `disabled` are drawn in
+/// `disabled_fg` and skipped over.
+#[derive(Copy, Clone, Debug)]
+pub struct RadioGroup<'t> {
+    pub items: &'t [&'t [u8]],
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub disabled_fg: m4vga::Pixel,
+    /// Bit `i` set disables item `i`. Items past the 32nd can't be disabled.
+    pub disabled: u32,
+    /// Whether the group has the focus.
+    pub focused: bool,
+    /// Index of the selected item.
+    selected: usize,
+    /// Index of the item with the focus.
+    focus: usize,
+}
+
+impl<'t> RadioGroup<'t> {
+    /// Makes an unfocused group with `selected` selected and focused, in
+    /// white on black.
+    pub const fn new(
+        items: &'t [&'t [u8]],
+        rect: Rect,
+        selected: usize,
+    ) -> Self {
+        RadioGroup {
+            items,
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            disabled_fg: 0b01_01_01,
+            disabled: 0,
+            focused: false,
+            selected,
+            focus: selected,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Returns whether item `index` is disabled.
+    pub fn is_disabled(&self, index: usize) -> bool {
+        index < 32 && self.disabled & (1 << index) != 0
+    }
+
+    /// Selects item `index` and moves the focus to it, unless it doesn't
+    /// exist or is disabled. Returns whether the selection changed.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.items.len() || self.is_disabled(index) {
+            return false;
+        }
+        self.focus = index;
+        let changed = index != self.selected;
+        self.selected = index;
+        changed
+    }
+
+    /// Moves the focus for `Up` and `Down` to the next enabled item, wrapping
+    /// around at either end, and selects the focused item for `Select`.
+    /// Returns the newly selected index if the selection changed.
+    pub fn handle(&mut self, event: MenuEvent) -> Option<usize> {
+        let n = self.items.len();
+        let step = match event {
+            MenuEvent::Up => n.saturating_sub(1),
+            MenuEvent::Down => 1,
+            MenuEvent::Select => {
+                return if self.select(self.focus) {
+                    Some(self.selected)
+                } else {
+                    None
+                };
+            }
+            MenuEvent::Left | MenuEvent::Right => return None,
+        };
+        let mut index = self.focus;
+        for _ in 1..n {
+            index = (index + step) % n;
+            if !self.is_disabled(index) {
+                self.focus = index;
+                break;
+            }
+        }
+        None
+    }
+
+    /// Draws the group at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl<'t> Widget for RadioGroup<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        for (row, &item) in self.items.iter().enumerate().take(area.h) {
+            let mut line = w.window(row, 0, 1, area.w);
+            if self.is_disabled(row) {
+                line.fg = self.disabled_fg;
+            }
+            if self.focused && row == self.focus {
+                line.bg = line.fg;
+                line.fg = self.bg;
+                line.fill_row(0, b' ');
+            }
+            line.set_scroll(false);
+            line.puts(if row == self.selected {
+                &[b'(', crate::list::BULLET, b')', b' ']
+            } else {
+                b"( ) "
+            });
+            line.puts_truncated(item, area.w.saturating_sub(4));
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (self.items.len(), widest(self.items) + 4)
+    }
+}
+
+impl<'t> Focusable for RadioGroup<'t> {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+    /// Handles `event` like `handle`.
+    fn on_event(&mut self, event: MenuEvent) -> bool {
+        let before = (self.focus, self.selected);
+        self.handle(event);
+        (self.focus, self.selected) != before
+    }
+}
+
+/// Rows taken by a `TabBar`.
+pub const TAB_ROWS: usize = 3;
+
+/// Row of boxed tab labels with one of them active, to go across the top of
+/// a set of panes.
+///
+/// The bottom row is a line the width of the bar, which the active tab opens
+/// up into so that it joins the pane below. The other tabs have their labels
+/// in `inactive_fg`. If the labels don't all fit, they are shortened equally
+/// and cut off with `TRUNCATION_MARK`.
+///
+/// The bar only keeps track of which tab is active; the caller draws the
+/// matching pane underneath.
+#[derive(Copy, Clone, Debug)]
+pub struct TabBar<'t> {
+    pub labels: &'t [&'t [u8]],
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub inactive_fg: m4vga::Pixel,
+    pub style: BoxStyle,
+    /// Index of the active tab.
+    active: usize,
+}
+
+impl<'t> TabBar<'t> {
+    /// Makes a bar with the first tab active, in white on black with single
+    /// lines.
+    pub const fn new(labels: &'t [&'t [u8]], rect: Rect) -> Self {
+        TabBar {
+            labels,
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            inactive_fg: 0b01_01_01,
+            style: BoxStyle::SINGLE,
+            active: 0,
+        }
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Makes tab `index` the active one, if there is such a tab. Returns
+    /// whether the active tab changed.
+    pub fn set_active(&mut self, index: usize) -> bool {
+        if index >= self.labels.len() || index == self.active {
+            return false;
+        }
+        self.active = index;
+        true
+    }
+
+    /// Activates the tab to the right, wrapping around to the first, and
+    /// returns its index.
+    pub fn next_tab(&mut self) -> usize {
+        let n = usize::max(self.labels.len(), 1);
+        self.active = (self.active + 1) % n;
+        self.active
+    }
+
+    /// Activates the tab to the left, wrapping around to the last, and
+    /// returns its index.
+    pub fn prev_tab(&mut self) -> usize {
+        let n = usize::max(self.labels.len(), 1);
+        self.active = (self.active + n - 1) % n;
+        self.active
+    }
+
+    /// Draws the bar at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+
+    /// Returns the widest a label can be drawn in `width` columns.
+    fn label_w(&self, width: usize) -> usize {
+        // Each tab has a frame on either side and a blank before the next.
+        let n = self.labels.len();
+        if self.min_size().1 <= width || n == 0 {
+            widest(self.labels)
+        } else {
+            ((width + 1) / n).saturating_sub(3)
+        }
+    }
+}
+
+impl<'t> Widget for TabBar<'t> {
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, area.h, area.w);
+        w.fg = self.fg;
+        w.bg = self.bg;
+        w.clear();
+        if area.h < TAB_ROWS {
+            return;
+        }
+        w.set_scroll(false);
+        w.hline(TAB_ROWS - 1, 0, area.w, self.style.bottom);
+        let label_w = self.label_w(area.w);
+        let mut col = 0;
+        for (i, &label) in self.labels.iter().enumerate() {
+            let tab_w = usize::min(label.len(), label_w) + 2;
+            if col + tab_w > area.w {
+                break;
+            }
+            let mut tab = w.window(0, col, TAB_ROWS, tab_w);
+            tab.set_scroll(false);
+            tab.draw_box(0, 0, TAB_ROWS, tab_w, &self.style);
+            if i == self.active {
+                tab.hline(TAB_ROWS - 1, 0, 1, self.style.bottom_right);
+                tab.hline(TAB_ROWS - 1, 1, tab_w - 2, b' ');
+                tab.hline(TAB_ROWS - 1, tab_w - 1, 1, self.style.bottom_left);
+            } else {
+                tab.hline(TAB_ROWS - 1, 0, tab_w, self.style.bottom);
+                tab.fg = self.inactive_fg;
+            }
+            tab.puts_truncated_at(1, 1, label, tab_w - 2).ok();
+            col += tab_w + 1;
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        let w: usize = self.labels.iter().map(|l| l.len() + 3).sum();
+        (TAB_ROWS, w.saturating_sub(1))
+    }
+}
+
+/// Value picked from a range in steps, drawn as a track of `LIGHT_SHADE`
+/// with a `FULL_BLOCK` thumb, and the value itself at the right end.
+///
+/// Steps count from `min`. If they don't divide the range evenly, the last
+/// step up stops at `max`, and the first step down from there goes back to
+/// the last whole step. The row is drawn with its colors swapped while the
+/// slider has the focus.
+#[derive(Copy, Clone, Debug)]
+pub struct Slider {
+    pub rect: Rect,
+    pub fg: m4vga::Pixel,
+    pub bg: m4vga::Pixel,
+    pub focused: bool,
+    min: u32,
+    max: u32,
+    /// Amount `inc` and `dec` move by; never 0.
+    step: u32,
+    value: u32,
+}
+
+impl Slider {
+    /// Makes a slider at `min`, in white on black. `max` is raised to `min`
+    /// if it is smaller, and a `step` of 0 counts as 1.
+    pub fn new(rect: Rect, min: u32, max: u32, step: u32) -> Self {
+        Slider {
+            rect,
+            fg: crate::cursor::DEFAULT_FG,
+            bg: crate::cursor::DEFAULT_BG,
+            focused: false,
+            min,
+            max: u32::max(min, max),
+            step: u32::max(step, 1),
+            value: min,
+        }
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    /// Sets the value, clamped into range, returning whether it changed.
+    pub fn set(&mut self, value: u32) -> bool {
+        let value = u32::min(u32::max(value, self.min), self.max);
+        let changed = value != self.value;
+        self.value = value;
+        changed
+    }
+
+    /// Moves up a step, stopping at the maximum. Returns whether the value
+    /// changed.
+    pub fn inc(&mut self) -> bool {
+        let steps = (self.value - self.min) / self.step;
+        let next =
+            u64::from(self.min) + u64::from(steps + 1) * u64::from(self.step);
+        self.set(u64::min(next, u64::from(self.max)) as u32)
+    }
+
+    /// Moves down a step, or onto the step below if between steps, stopping
+    /// at the minimum. Returns whether the value changed.
+    pub fn dec(&mut self) -> bool {
+        let above = self.value - self.min;
+        let back = match above % self.step {
+            0 => u32::min(self.step, above),
+            part => part,
+        };
+        self.set(self.value - back)
+    }
+
+    /// Draws the slider at its own `rect`.
+    pub fn redraw(&self, c: &mut Cursor) {
+        self.draw(c, self.rect)
+    }
+}
+
+impl Widget for Slider {
+    /// Draws the slider on the top row of `area`, with the value taking as
+    /// many columns on the right as the maximum needs and the track the
+    /// rest, less a blank between them.
+    fn draw(&self, c: &mut Cursor, area: Rect) {
+        let mut w = c.window(area.row, area.col, 1, area.w);
+        if self.focused {
+            w.fg = self.bg;
+            w.bg = self.fg;
+        } else {
+            w.fg = self.fg;
+            w.bg = self.bg;
+        }
+        w.clear();
+        w.set_scroll(false);
+        let value_w = crate::list::digits(self.max as usize);
+        let track_w = area.w.saturating_sub(value_w + 1);
+        if track_w > 0 {
+            let range = u64::from(self.max - self.min);
+            let thumb = match range {
+                0 => 0,
+                _ => {
+                    u64::from(self.value - self.min) * (track_w as u64 - 1)
+                        / range
+                }
+            };
+            w.hline(0, 0, track_w, LIGHT_SHADE);
+            w.hline(0, thumb as usize, 1, FULL_BLOCK);
+        }
+        if area.w > 0 {
+            write_right!(w, 0, area.w - 1, "{}", self.value).ok();
+        }
+    }
+
+    fn min_size(&self) -> (usize, usize) {
+        (1, crate::list::digits(self.max as usize) + 2)
+    }
+}
+
+impl Focusable for Slider {
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Steps the value up for `Right` or `Up`, and down for `Left` or
+    /// `Down`.
+    fn on_event(&mut self, event: MenuEvent) -> bool {
+        match event {
+            MenuEvent::Right | MenuEvent::Up => self.inc(),
+            MenuEvent::Left | MenuEvent::Down => self.dec(),
+            MenuEvent::Select => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use m4vga::rast::text_10x16::AChar;
+
+    const COLS: usize = 12;
+    const ROWS: usize = 6;
+
+    fn text(buf: &[AChar]) -> Vec<String> {
+        buf.chunks(COLS)
+            .map(|row| row.iter().map(|a| a.char()).collect())
+            .collect()
+    }
+
+    static MESSAGE: MessageBox = MessageBox {
+        lines: &[b"Press", b"any key"],
+        fg: 1,
+        bg: 2,
+        shadow: false,
+    };
+
+    static OPTION: OptionBox = OptionBox {
+        label: b"OK",
+        fg: 3,
+        bg: 4,
+        style: &BoxStyle::ASCII,
+    };
+
+    #[test]
+    fn message_box_centers_lines() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            c.goto(5, 5);
+            MESSAGE.draw(&mut c, Rect::new(1, 1, 4, 9));
+            assert_eq!(c.position(), (5, 5));
+            assert_eq!((c.fg, c.bg), (0b11_11_11, 0));
+        }
+        assert_eq!(
+            text(&buf),
+            [
+                "............",
+                ".         ..",
+                ".  Press  ..",
+                ". any key ..",
+                ".         ..",
+                "............",
+            ]
+        );
+        assert_eq!(buf[COLS + 1].background(), 2);
+        assert_eq!(MESSAGE.min_size(), (2, 7));
+    }
+
+    #[test]
+    fn shadowed_message_box_shades_outside() {
+        let mut buf =
+            [AChar::from_ascii_char(b'.').with_background(3); COLS * ROWS];
+        let shadowed = MessageBox {
+            shadow: true,
+            ..MESSAGE
+        };
+        shadowed.draw(&mut Cursor::new(&mut buf, COLS), Rect::new(0, 0, 2, 3));
+        assert_eq!(buf[2 * COLS + 1].background(), 1);
+        assert_eq!(buf[COLS + 3].background(), 1);
+        assert_eq!(buf[2 * COLS].background(), 3);
+    }
+
+    #[test]
+    fn option_box_frames_label() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        OPTION.draw(&mut Cursor::new(&mut buf, COLS), Rect::new(0, 2, 3, 6));
+        assert_eq!(
+            &text(&buf)[..4],
+            [
+                "..+----+....",
+                "..| OK |....",
+                "..+----+....",
+                "............"
+            ]
+        );
+        assert_eq!(buf[2].foreground(), 3);
+        assert_eq!(buf[COLS + 4].background(), 4);
+        assert_eq!(OPTION.min_size(), (3, 4));
+    }
+
+    #[test]
+    fn title_bar_fills_its_area() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let title = TitleBar {
+            title: b"Hi",
+            fg: 1,
+            bg: 0,
+        };
+        title.draw(&mut Cursor::new(&mut buf, COLS), Rect::new(1, 2, 3, 8));
+        assert_eq!(
+            text(&buf),
+            [
+                "............",
+                "..        ..",
+                "..   Hi   ..",
+                "..        ..",
+                "............",
+                "............",
+            ]
+        );
+    }
+
+    static STYLE: ButtonStyle = ButtonStyle {
+        normal: (1, 2),
+        focused: (2, 1),
+        pressed: (3, 0),
+        frame: BoxStyle::ASCII,
+    };
+
+    #[test]
+    fn button_colors_follow_state() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let mut button = Button::new(b"GO", Rect::new(1, 1, 3, 4), &STYLE);
+        let mut c = Cursor::new(&mut buf, COLS);
+        let colors = |c: &Cursor| {
+            let mut cells = [AChar::from_ascii_char(0); 1];
+            c.read_region(Rect::new(2, 2, 1, 1), &mut cells).unwrap();
+            (cells[0].foreground(), cells[0].background())
+        };
+
+        button.redraw(&mut c);
+        assert_eq!(colors(&c), (1, 2));
+        assert!(button.set_state(ButtonState::Pressed));
+        assert!(!button.set_state(ButtonState::Pressed));
+        button.redraw(&mut c);
+        assert_eq!(colors(&c), (3, 0));
+        button.set_state(ButtonState::Focused);
+        button.redraw(&mut c);
+        assert_eq!(colors(&c), (2, 1));
+        assert_eq!(button.min_size(), (3, 4));
+
+        let mut row = [AChar::from_ascii_char(0); 4];
+        c.read_region(Rect::new(2, 1, 1, 4), &mut row).unwrap();
+        let row: String = row.iter().map(|a| a.char()).collect();
+        assert_eq!(row, "|GO|");
+    }
+
+    fn bar(value: u32, max: u32, percent: Percent) -> ProgressBar {
+        ProgressBar {
+            rect: Rect::new(0, 0, 1, COLS),
+            value,
+            max,
+            fill: b'#',
+            empty: b'.',
+            fg: 1,
+            bg: 2,
+            percent,
+        }
+    }
+
+    fn bar_row(bar: &ProgressBar, w: usize) -> (String, String) {
+        let mut buf = [AChar::from_ascii_char(b'x'); COLS * ROWS];
+        bar.draw(&mut Cursor::new(&mut buf, COLS), Rect::new(0, 0, 1, w));
+        let text = buf[..COLS].iter().map(|a| a.char()).collect();
+        let inverted = buf[..COLS]
+            .iter()
+            .map(|a| if a.foreground() == 2 { '^' } else { ' ' })
+            .collect();
+        (text, inverted)
+    }
+
+    #[test]
+    fn progress_bar_states() {
+        assert_eq!(bar_row(&bar(0, 10, Percent::Hidden), 7).0, "[.....]xxxxx");
+        assert_eq!(bar_row(&bar(10, 10, Percent::Hidden), 7).0, "[#####]xxxxx");
+        assert_eq!(bar_row(&bar(99, 10, Percent::Hidden), 2).0, "[]xxxxxxxxxx");
+        assert_eq!(bar_row(&bar(5, 10, Percent::Hidden), 1).0, "[xxxxxxxxxxx");
+        assert_eq!(bar_row(&bar(5, 10, Percent::Hidden), 0).0, "xxxxxxxxxxxx");
+        assert_eq!(bar_row(&bar(5, 0, Percent::After), COLS).0, "[.....]   0%");
+        assert_eq!(
+            bar_row(&bar(11, 20, Percent::After), COLS).0,
+            "[###..]  55%"
+        );
+        assert_eq!(bar_row(&bar(1, 1, Percent::After), COLS).0, "[#####] 100%");
+        assert_eq!(bar_row(&bar(1, 1, Percent::After), 4).0, "100%xxxxxxxx");
+    }
+
+    #[test]
+    fn progress_overlay_inverts_over_fill() {
+        let (text, inverted) = bar_row(&bar(9, 20, Percent::Overlay), COLS);
+        assert_eq!(text, "[###45%....]");
+        assert_eq!(inverted, "    ^^      ");
+        // Too narrow for the text: just the bar.
+        let (text, _) = bar_row(&bar(1, 2, Percent::Overlay), 4);
+        assert_eq!(text, "[#.]xxxxxxxx");
+        assert_eq!(bar(2, 3, Percent::Hidden).percent(), 66);
+    }
+
+    #[test]
+    fn spinner_ticks_and_draws_label() {
+        let mut spinner =
+            Spinner::new(Rect::new(0, 1, 1, 9), &SPINNER_ASCII, 3, b"Working");
+        assert!(!spinner.tick(2));
+        assert!(spinner.tick(3));
+        assert!(!spinner.tick(5));
+        assert!(spinner.tick(11));
+        assert_eq!(spinner.frame, 3);
+        assert!(spinner.tick(12));
+        assert_eq!(spinner.frame, 0);
+
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        spinner.tick(4);
+        spinner.redraw(&mut Cursor::new(&mut buf, COLS));
+        let row: String = buf[..COLS].iter().map(|a| a.char()).collect();
+        assert_eq!(row, "./ Working..");
+    }
+
+    fn marquee_row(marquee: &Marquee) -> String {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        marquee.redraw(&mut Cursor::new(&mut buf, COLS));
+        buf[..COLS].iter().map(|a| a.char()).collect()
+    }
+
+    #[test]
+    fn marquee_pauses_scrolls_and_wraps() {
+        let mut m = Marquee::new(b"abcdef", Rect::new(0, 1, 1, 4), 2, 2, 3);
+        assert!(m.scrolls());
+        assert_eq!(marquee_row(&m), ".abcd.......");
+        // Paused at the start for 3 ticks on top of the usual 2.
+        for _ in 0..4 {
+            assert!(!m.tick());
+        }
+        assert!(m.tick());
+        assert_eq!(marquee_row(&m), ".bcde.......");
+        assert!(!m.tick());
+        assert!(m.tick());
+        m.offset = 5;
+        assert_eq!(marquee_row(&m), ".f  a.......");
+        m.offset = 7;
+        assert!(!m.tick());
+        assert!(m.tick());
+        assert_eq!(m.offset, 0);
+        m.reset();
+        assert_eq!(marquee_row(&m), ".abcd.......");
+    }
+
+    #[test]
+    fn short_marquee_is_static() {
+        let mut m = Marquee::new(b"hi", Rect::new(0, 0, 1, 4), 2, 1, 0);
+        assert!(!m.scrolls());
+        assert!(!m.tick());
+        assert_eq!(marquee_row(&m), "hi  ........");
+    }
+
+    fn menu_rows(menu: &Menu) -> Vec<String> {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        menu.redraw(&mut Cursor::new_with(&mut buf, COLS, 1, 2));
+        buf.chunks(COLS)
+            .take(menu.rect.h)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        MORE_ABOVE => '^',
+                        MORE_BELOW => 'v',
+                        b' ' if a.background() == 1 => '#',
+                        other => other as char,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn menu_wraps_and_selects() {
+        static ITEMS: [&[u8]; 3] = [b"One", b"Two", b"Three"];
+        let mut menu = Menu::new(&ITEMS, Rect::new(0, 0, 4, 8));
+        menu.fg = 1;
+        menu.bg = 2;
+        assert_eq!(menu.handle(MenuEvent::Up), None);
+        assert_eq!(menu.selected, 2);
+        assert_eq!(menu.handle(MenuEvent::Down), None);
+        assert_eq!(menu.handle(MenuEvent::Down), None);
+        assert_eq!(menu.handle(MenuEvent::Select), Some(1));
+        assert_eq!(
+            menu_rows(&menu),
+            [
+                "  One   ....",
+                ">#Two###....",
+                "  Three ....",
+                "        ...."
+            ]
+        );
+        assert_eq!(Menu::new(&[], menu.rect).handle(MenuEvent::Select), None);
+    }
+
+    #[test]
+    fn menu_scrolls_with_indicators() {
+        static ITEMS: [&[u8]; 5] = [b"a", b"b", b"c", b"d", b"e"];
+        let mut menu = Menu::new(&ITEMS, Rect::new(0, 0, 2, 5));
+        menu.fg = 1;
+        menu.bg = 2;
+        assert_eq!(menu_rows(&menu), [">#a# .......", "  b v......."]);
+        menu.handle(MenuEvent::Down);
+        menu.handle(MenuEvent::Down);
+        assert_eq!(menu_rows(&menu), ["  b ^.......", ">#c#v......."]);
+        menu.handle(MenuEvent::Down);
+        menu.handle(MenuEvent::Down);
+        assert_eq!(menu_rows(&menu), ["  d ^.......", ">#e# ......."]);
+        menu.handle(MenuEvent::Down);
+        assert_eq!(menu.selected, 0);
+        assert_eq!(menu_rows(&menu), [">#a# .......", "  b v......."]);
+
+        // Too narrow for even the scroll column, it draws nothing.
+        menu.rect.w = 0;
+        assert_eq!(menu_rows(&menu), ["............", "............"]);
+    }
+
+    #[test]
+    fn paged_list_clamps_and_draws_pages() {
+        static ITEMS: [&[u8]; 5] = [b"one", b"two", b"three", b"four", b"five"];
+        let mut list = PagedList::new(&ITEMS, 2, Rect::new(0, 1, 3, 10));
+        assert_eq!(list.pages(), 3);
+        assert!(!list.prev_page());
+        assert!(list.next_page());
+        assert_eq!(list.page_items(), &ITEMS[2..4]);
+        assert!(list.next_page());
+        assert!(!list.next_page());
+        assert_eq!(list.page(), 2);
+        assert_eq!(list.page_items(), &ITEMS[4..]);
+        list.set_page(99);
+        assert_eq!(list.page(), 2);
+        list.set_page(1);
+
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        list.redraw(&mut Cursor::new(&mut buf, COLS));
+        let rows: Vec<String> = buf
+            .chunks(COLS)
+            .take(3)
+            .map(|row| row.iter().map(|a| a.char()).collect())
+            .collect();
+        assert_eq!(rows, [".three     .", ".four      .", ".  Page 2/3."]);
+        assert_eq!(PagedList::new(&[], 0, list.rect).pages(), 1);
+
+        list.label = b"P.";
+        list.draw_indicator(&mut Cursor::new(&mut buf, COLS), 3, 9);
+        let row: String =
+            buf[3 * COLS..4 * COLS].iter().map(|a| a.char()).collect();
+        assert_eq!(row, "....P. 2/3..");
+    }
+
+    #[test]
+    fn checkbox_and_toggle_draw_state_and_focus() {
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        let mut sound = Checkbox::new(b"Sound", false);
+        let mut contrast = Toggle::new(b"Contrast", true);
+        sound.fg = 1;
+        contrast.fg = 1;
+        assert!(sound.toggle());
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            let mut widgets: [&mut dyn Focusable; 2] =
+                [&mut sound, &mut contrast];
+            assert_eq!(move_focus(&mut widgets, true), Some(0));
+            assert_eq!(move_focus(&mut widgets, false), Some(1));
+            assert_eq!(move_focus(&mut widgets, false), Some(0));
+            sound.draw(&mut c, Rect::new(0, 0, 1, COLS));
+            contrast.draw(&mut c, Rect::new(1, 0, 1, COLS));
+            contrast.toggle();
+            contrast.draw(&mut c, Rect::new(2, 0, 1, 10));
+        }
+        let rows: Vec<String> = buf
+            .chunks(COLS)
+            .take(3)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match (a.ascii_char(), a.background()) {
+                        (b' ', 1) => '#',
+                        (b' ', 0b00_10_00) => '+',
+                        (b' ', 0b00_00_10) => '-',
+                        (crate::cursor::TRUNCATION_MARK, _) => '~',
+                        (ch, _) => ch as char,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, ["[X]#Sound###", "Contr~ +ON++", "Con~ -OFF-.."]);
+        assert!(sound.is_focused());
+        assert!(!contrast.is_focused());
+        assert_eq!(move_focus(&mut [], true), None);
+    }
+
+    #[test]
+    fn radio_group_skips_disabled_and_reports_changes() {
+        let items: [&[u8]; 3] = [b"Cash", b"Card", b"Mobile"];
+        let mut pay = RadioGroup::new(&items, Rect::new(0, 0, 3, 8), 0);
+        pay.disabled = 0b010;
+        pay.focused = true;
+        assert_eq!(pay.handle(MenuEvent::Down), None);
+        assert_eq!(pay.handle(MenuEvent::Select), Some(2));
+        assert_eq!(pay.handle(MenuEvent::Select), None);
+        assert_eq!(pay.handle(MenuEvent::Down), None);
+        assert_eq!(pay.selected(), 2);
+        assert!(!pay.select(1));
+
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * 3];
+        pay.fg = 1;
+        pay.redraw(&mut Cursor::new(&mut buf, COLS));
+        let rows: Vec<String> = buf
+            .chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        crate::list::BULLET => '*',
+                        crate::cursor::TRUNCATION_MARK => '~',
+                        b' ' if a.background() == 1 => '#',
+                        ch => ch as char,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, ["(#)#Cash....", "( ) Card....", "(*) Mob~...."]);
+        assert_eq!(buf[COLS + 4].foreground(), pay.disabled_fg);
+    }
+
+    #[test]
+    fn tab_bar_opens_into_the_active_pane_and_truncates() {
+        let labels: [&[u8]; 3] = [b"Stats", b"Diagnostics", b"Config"];
+        const W: usize = 24;
+        let mut tabs = TabBar::new(&labels, Rect::new(0, 0, TAB_ROWS, W));
+        tabs.style = BoxStyle::ASCII;
+        assert_eq!(tabs.min_size(), (TAB_ROWS, 30));
+        assert_eq!(tabs.next_tab(), 1);
+        assert_eq!(tabs.prev_tab(), 0);
+        assert_eq!(tabs.prev_tab(), 2);
+        assert!(!tabs.set_active(3));
+        assert!(tabs.set_active(1));
+
+        let mut buf = [AChar::from_ascii_char(b'.'); W * TAB_ROWS];
+        tabs.redraw(&mut Cursor::new(&mut buf, W));
+        let rows: Vec<String> = buf
+            .chunks(W)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        crate::cursor::TRUNCATION_MARK => '~',
+                        ch => ch as char,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "+-----+ +-----+ +-----+ ",
+                "|Stats| |Diag~| |Conf~| ",
+                "--------+     +---------",
+            ]
+        );
+        assert_eq!(buf[W + 9].foreground(), tabs.fg);
+        assert_eq!(buf[W + 1].foreground(), tabs.inactive_fg);
+    }
+
+    #[test]
+    fn focus_manager_routes_events_to_the_focused_widget() {
+        static STYLE: ButtonStyle = ButtonStyle {
+            normal: (1, 0),
+            focused: (0, 1),
+            pressed: (2, 0),
+            frame: BoxStyle::ASCII,
+        };
+        let mut sound = Checkbox::new(b"Sound", false);
+        let mut ok = Button::new(b"OK", Rect::new(1, 0, 3, 4), &STYLE);
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut focus = FocusManager::new();
+            assert!(focus.push(&mut sound, Rect::new(0, 0, 1, COLS)));
+            assert!(focus.push(&mut ok, Rect::new(1, 0, 3, 4)));
+            assert_eq!(focus.focused(), Some(0));
+            assert!(focus.handle(MenuEvent::Select));
+            assert!(!focus.handle(MenuEvent::Down));
+            assert_eq!(focus.focus_prev(), Some(1));
+            assert!(focus.handle(MenuEvent::Select));
+            assert!(!focus.handle(MenuEvent::Select));
+            assert_eq!(focus.focus_next(), Some(0));
+            assert!(!focus.focus(0));
+            focus.draw(&mut Cursor::new(&mut buf, COLS));
+        }
+        assert!(sound.checked && sound.focused);
+        assert_eq!(ok.state, ButtonState::Pressed);
+        let text: String = buf[..COLS * 2].iter().map(|a| a.char()).collect();
+        assert_eq!(text, "[X] Sound   +--+........");
+        assert_eq!(buf[COLS + 1].foreground(), 2);
+    }
+
+    #[test]
+    fn slider_steps_unevenly_and_draws_thumb() {
+        let mut volume = Slider::new(Rect::new(0, 0, 1, COLS), 0, 10, 4);
+        assert!(!volume.dec());
+        assert!(volume.inc() && volume.inc());
+        assert_eq!(volume.value(), 8);
+        assert!(volume.inc());
+        assert_eq!(volume.value(), 10);
+        assert!(!volume.inc());
+        assert!(volume.dec());
+        assert_eq!(volume.value(), 8);
+        volume.set(5);
+        assert!(volume.dec());
+        assert_eq!(volume.value(), 4);
+
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * 2];
+        volume.focused = true;
+        volume.fg = 1;
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            volume.redraw(&mut c);
+            // No room for a track: just the value.
+            let mut level = Slider::new(Rect::new(1, 0, 1, 3), 1, 99, 0);
+            level.set(98);
+            level.redraw(&mut c);
+        }
+        let rows: Vec<String> = buf
+            .chunks(COLS)
+            .map(|row| {
+                row.iter()
+                    .map(|a| match a.ascii_char() {
+                        FULL_BLOCK => '#',
+                        LIGHT_SHADE => '-',
+                        ch => ch as char,
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, ["---#-----  4", " 98........."]);
+        assert_eq!(buf[0].background(), 1);
+    }
+
+    #[test]
+    fn header_subtitle_sits_above_the_divider() {
+        let header = Header {
+            title: b"Pay",
+            subtitle: Some(b"Card only"),
+            style: HeaderStyle {
+                fg: 0b11_11_11,
+                bg: 0b11_00_00,
+                subtitle_fg: 0b01_01_01,
+            },
+        };
+        assert_eq!(header.rows(), HEADER_ROWS);
+        assert!(crate::chrome::SOFT_KEY_ROWS[0] >= HEADER_ROWS);
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        {
+            let mut c = Cursor::new(&mut buf, COLS);
+            header.draw(&mut c, Rect::new(0, 0, ROWS, COLS));
+            crate::chrome::draw_screen_border_under(
+                &mut c,
+                &BoxStyle::ASCII,
+                header.rows(),
+            );
+        }
+        let rows: Vec<String> = buf
+            .chunks(COLS)
+            .map(|row| row.iter().map(|a| a.char()).collect())
+            .collect();
+        assert_eq!(
+            &rows[..5],
+            [
+                "+----------+",
+                "|   Pay    |",
+                "|Card only |",
+                "+----------+",
+                "|..........|",
+            ]
+        );
+        assert_eq!(buf[2 * COLS + 1].foreground(), 0b01_01_01);
+        assert_eq!(buf[3 * COLS + 1].background(), 0b11_00_00);
+    }
+
+    #[test]
+    fn blink_follows_duty_and_covers_both_phases() {
+        let area = Rect::new(1, 1, 4, 9);
+        let mut blink = Blink::new(&MESSAGE, area, 10);
+        blink.duty = 70;
+        let shown: String = (0..12)
+            .map(|frame| {
+                blink.tick(frame);
+                if blink.is_visible() {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        assert_eq!(shown, "#######...##");
+
+        let mut buf = [AChar::from_ascii_char(b'.'); COLS * ROWS];
+        assert!(blink.tick(7));
+        blink.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(text(&buf)[2], ".         ..");
+        assert_eq!(buf[COLS + 1].background(), 0);
+
+        blink.off = BlinkOff::Dim(5);
+        blink.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(text(&buf)[2], ".  Press  ..");
+        assert_eq!(buf[2 * COLS + 3].foreground(), 5);
+        assert_eq!(buf[2 * COLS + 3].background(), 2);
+
+        assert!(blink.tick(10));
+        blink.redraw(&mut Cursor::new(&mut buf, COLS));
+        assert_eq!(buf[2 * COLS + 3].foreground(), 1);
+        assert!(!blink.tick(11));
+    }
+}